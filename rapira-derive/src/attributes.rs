@@ -2,82 +2,659 @@ extern crate proc_macro2;
 extern crate quote;
 extern crate syn;
 
-use syn::{Attribute, Expr, Ident, MetaNameValue, Path};
+use proc_macro_crate::{FoundCrate, crate_name};
+use proc_macro2::Span;
+use syn::{
+    Attribute, Expr, ExprLit, ExprPath, Ident, Lit, LitByteStr, LitInt, Meta, MetaList,
+    MetaNameValue, Path, Token, Type, WherePredicate,
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token::Comma,
+};
+
+use crate::shared::Errors;
+
+/// container keys of structs
+pub const STRUCT_KEYS: &[&str] = &[
+    "crate",
+    "bound",
+    "version",
+    "retired",
+    "migrate",
+    "debug",
+    "validate",
+    "transparent",
+    "from",
+    "try_from",
+    "into",
+    "max_cap",
+    "sparse",
+    "fixed",
+    "magic",
+    "endian",
+    "extensible",
+];
+/// container keys of enums with data
+pub const ENUM_KEYS: &[&str] = &[
+    "crate",
+    "bound",
+    "version",
+    "migrate",
+    "debug",
+    "static_size",
+    "min_size",
+    "tag",
+    "validate",
+    "from",
+    "try_from",
+    "into",
+    "max_cap",
+    "fixed",
+    "endian",
+    "extensible",
+];
+/// container keys of `#[primitive(..)]` enums
+pub const PRIMITIVE_ENUM_KEYS: &[&str] = &[
+    "crate", "version", "migrate", "tag", "from", "try_from", "into", "max_cap",
+];
+/// container keys of simple (fieldless) enums
+pub const SIMPLE_ENUM_KEYS: &[&str] = &[
+    "crate", "tag", "from", "try_from", "into", "max_cap", "endian",
+];
+/// container keys allowed with `#[rapira(transparent)]`
+const TRANSPARENT_KEYS: &[&str] = &["crate", "bound", "transparent", "max_cap"];
+/// container keys allowed with `from`, `try_from` or `into`
+const PROXY_KEYS: &[&str] = &["crate", "bound", "from", "try_from", "into", "max_cap"];
 
 /// `#[primitive(PrimitiveName)]` in enums
-pub fn get_primitive_name(attrs: &[Attribute]) -> Option<Ident> {
-    attrs.iter().find_map(|attr| {
-        if !attr.path().is_ident("primitive") {
-            return None;
+pub fn get_primitive_name(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("primitive"))
+        .map(|attr| attr.parse_args())
+        .transpose()
+}
+
+/// all items of `#[rapira(..)]` attributes,
+/// several items can be set in one attribute: `#[rapira(version = 2, debug)]`
+pub fn rapira_metas(attrs: &[Attribute]) -> syn::Result<Vec<Meta>> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("rapira")) {
+        metas.extend(attr.parse_args_with(|input: ParseStream| {
+            Punctuated::<Meta, Comma>::parse_terminated_with(input, parse_meta)
+        })?);
+    }
+    Ok(metas)
+}
+
+/// `Meta` with the `if = expr` key, which is a keyword
+fn parse_meta(input: ParseStream) -> syn::Result<Meta> {
+    if !input.peek(Token![if]) {
+        return input.parse();
+    }
+    Ok(Meta::NameValue(MetaNameValue {
+        path: input.call(Ident::parse_any)?.into(),
+        eq_token: input.parse()?,
+        value: input.parse()?,
+    }))
+}
+
+/// error for every `#[rapira(..)]` item not in `keys`, `target` - "struct", "field", ..
+pub fn check_keys(attrs: &[Attribute], keys: &[&str], target: &str) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    for meta in rapira_metas(attrs)? {
+        if !keys.iter().any(|key| meta.path().is_ident(key)) {
+            let key = meta
+                .path()
+                .get_ident()
+                .map(Ident::to_string)
+                .unwrap_or_else(|| quote::ToTokens::to_token_stream(meta.path()).to_string());
+            let expected = if keys.is_empty() {
+                format!("{target} doesn't support rapira attributes")
+            } else {
+                format!("expected one of: {}", keys.join(", "))
+            };
+            errors.push(syn::Error::new_spanned(
+                meta.path(),
+                format!("unknown rapira attribute `{key}` on {target}, {expected}"),
+            ));
         }
+    }
+    errors.finish()
+}
 
-        attr.parse_args().unwrap()
-    })
+/// value of `#[rapira(key = value)]`
+pub fn name_value(attrs: &[Attribute], key: &str) -> syn::Result<Option<Expr>> {
+    for meta in rapira_metas(attrs)? {
+        if meta.path().is_ident(key) {
+            return Ok(Some(meta.require_name_value()?.value.clone()));
+        }
+    }
+    Ok(None)
 }
 
-/// `#[rapira(static_size = None)]` in enums
-pub fn enum_static_size(attrs: &[Attribute]) -> Option<Expr> {
-    attrs.iter().find_map(|attr| {
-        if !attr.path().is_ident("rapira") {
-            return None;
+/// `#[rapira(key)]` flag
+pub fn flag(attrs: &[Attribute], key: &str) -> syn::Result<bool> {
+    for meta in rapira_metas(attrs)? {
+        if meta.path().is_ident(key) {
+            meta.require_path_only()?;
+            return Ok(true);
         }
+    }
+    Ok(false)
+}
 
-        if let Ok(nv) = attr.parse_args::<MetaNameValue>() {
-            return nv.path.is_ident("static_size").then_some(nv.value);
+/// all `#[rapira(key(..))]` lists
+fn lists(attrs: &[Attribute], key: &str) -> syn::Result<Vec<MetaList>> {
+    let mut lists = Vec::new();
+    for meta in rapira_metas(attrs)? {
+        if meta.path().is_ident(key) {
+            lists.push(meta.require_list()?.clone());
         }
+    }
+    Ok(lists)
+}
 
-        None
+/// parse `key = value, ..` list, `parse_value` is called after `key =`
+fn parse_key_values(
+    list: &MetaList,
+    mut parse_value: impl FnMut(&Ident, ParseStream) -> syn::Result<()>,
+) -> syn::Result<()> {
+    list.parse_args_with(|input: ParseStream| {
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            parse_value(&key, input)?;
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Comma>()?;
+        }
+        Ok(())
     })
 }
 
-/// `#[rapira(min_size = 0)]`
-pub fn min_size(attrs: &[Attribute]) -> Option<Expr> {
-    attrs.iter().find_map(|attr| {
-        if !attr.path().is_ident("rapira") {
-            return None;
-        }
+fn parse_u16(input: ParseStream) -> syn::Result<u16> {
+    input.parse::<LitInt>()?.base10_parse()
+}
+
+fn parse<T: Parse>(input: ParseStream) -> syn::Result<T> {
+    input.parse()
+}
 
-        if let Ok(nv) = attr.parse_args::<MetaNameValue>() {
-            return nv.path.is_ident("min_size").then_some(nv.value);
+/// integer literal value of `expr`
+pub fn int_value<T>(expr: &Expr, key: &str) -> syn::Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    match expr {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(i),
+            ..
+        }) => i.base10_parse(),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            format!("{key} value must be an integer literal"),
+        )),
+    }
+}
+
+/// `#[rapira(crate = "facade::rapira")]` - path of the rapira crate in generated code,
+/// by default the name of the `rapira` dependency in Cargo.toml (it can be renamed)
+pub fn crate_path(attrs: &[Attribute]) -> syn::Result<Path> {
+    if let Some(value) = name_value(attrs, "crate")? {
+        return match &value {
+            Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(path),
+                ..
+            }) => path.parse(),
+            _ => Err(syn::Error::new_spanned(
+                value,
+                "crate value must be a string literal: `crate = \"path::to::rapira\"`",
+            )),
+        };
+    }
+
+    let name = match crate_name("rapira") {
+        Ok(FoundCrate::Name(name)) => name,
+        // `FoundCrate::Itself` (tests and examples of rapira) or rapira isn't a direct dependency
+        _ => "rapira".to_owned(),
+    };
+    let name = Ident::new(&name, Span::call_site());
+    Ok(syn::parse_quote! { ::#name })
+}
+
+/// `#[rapira(bound = "T: Trait, U: Other")]` on container or field:
+/// where predicates replacing the inferred `T: rapira::Rapira` bounds
+pub fn bound_attr(attrs: &[Attribute]) -> syn::Result<Option<Vec<WherePredicate>>> {
+    let Some(value) = name_value(attrs, "bound")? else {
+        return Ok(None);
+    };
+    match &value {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(bound),
+            ..
+        }) => {
+            let predicates =
+                bound.parse_with(Punctuated::<WherePredicate, Comma>::parse_terminated)?;
+            Ok(Some(predicates.into_iter().collect()))
         }
+        _ => Err(syn::Error::new_spanned(
+            value,
+            "bound value must be a string literal: `bound = \"T: Trait\"`",
+        )),
+    }
+}
 
-        None
-    })
+/// `#[rapira(static_size = None)]` in enums
+pub fn enum_static_size(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+    name_value(attrs, "static_size")
+}
+
+/// `#[rapira(min_size = 0)]`
+pub fn min_size(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+    name_value(attrs, "min_size")
+}
+
+/// `#[rapira(max_cap = 1000)]` - `Rapira::MAX_CAP` of the container
+pub fn max_cap_attr(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+    name_value(attrs, "max_cap")
 }
 
 /// `#[rapira(version = 2)]` on struct
-pub fn version_attr(attrs: &[Attribute]) -> Option<u8> {
-    attrs.iter().find_map(|attr| {
-        if !attr.path().is_ident("rapira") {
-            return None;
+pub fn version_attr(attrs: &[Attribute]) -> syn::Result<Option<u16>> {
+    name_value(attrs, "version")?
+        .map(|value| int_value(&value, "version"))
+        .transpose()
+}
+
+/// field removed from the struct, but still present in old data
+pub struct RetiredField {
+    pub name: Ident,
+    pub ty: Type,
+    /// first version with the field (inclusive)
+    pub since: u16,
+    /// version the field was removed in (exclusive)
+    pub until: u16,
+    /// field (current or retired) the retired field followed, `None` - first field
+    pub after: Option<Ident>,
+    pub span: Span,
+}
+
+/// `#[rapira(retired(name = old_field, ty = u32, since = 1, until = 3, after = prev_field))]`
+///
+/// `since` defaults to 1, `after` defaults to the start of the struct
+pub fn retired_attrs(attrs: &[Attribute]) -> syn::Result<Vec<RetiredField>> {
+    let mut retired = Vec::new();
+    let mut errors = Errors::default();
+
+    for list in lists(attrs, "retired")? {
+        let mut name: Option<Ident> = None;
+        let mut ty: Option<Type> = None;
+        let mut since: Option<u16> = None;
+        let mut until: Option<u16> = None;
+        let mut after: Option<Ident> = None;
+
+        // `ty` is a type, not an expression, so `MetaNameValue` can't be used
+        let parsed = parse_key_values(&list, |key, input| {
+            match key.to_string().as_str() {
+                "name" => name = Some(parse(input)?),
+                "ty" => ty = Some(parse(input)?),
+                "since" => since = Some(parse_u16(input)?),
+                "until" => until = Some(parse_u16(input)?),
+                "after" => after = Some(parse(input)?),
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown key `{key}` in retired, expected one of: name, ty, since, until, after"
+                        ),
+                    ));
+                }
+            }
+            Ok(())
+        });
+        if errors.check(parsed).is_none() {
+            continue;
         }
 
-        if let Ok(nv) = attr.parse_args::<MetaNameValue>()
-            && nv.path.is_ident("version")
-        {
-            if let Expr::Lit(syn::ExprLit {
-                lit: syn::Lit::Int(i),
-                ..
-            }) = &nv.value
-            {
-                return Some(i.base10_parse::<u8>().expect("version value must be u8"));
+        let span = list.span();
+        let (Some(name), Some(ty), Some(until)) = (name, ty, until) else {
+            errors.push(syn::Error::new(
+                span,
+                "retired: `name`, `ty` and `until` are required",
+            ));
+            continue;
+        };
+
+        retired.push(RetiredField {
+            name,
+            ty,
+            since: since.unwrap_or(1),
+            until,
+            after,
+            span,
+        });
+    }
+
+    errors.finish()?;
+    Ok(retired)
+}
+
+/// conversion of data written by an older version
+pub struct Migration {
+    /// newest data version decoded via this migration
+    pub from: u16,
+    /// legacy type of version `from`
+    pub via: Type,
+    /// `fn(via) -> Self` or `fn(via) -> rapira::Result<Self>` (`fallible`)
+    pub with: ExprPath,
+    pub fallible: bool,
+    /// `fn(&Self) -> via` for writing at versions `<= from`
+    pub downgrade: Option<ExprPath>,
+    pub span: Span,
+}
+
+/// `#[rapira(migrate(from = 2, via = UserV2, with = migrate_v2))]`,
+/// `try_with` instead of `with` for fallible conversion,
+/// optional `downgrade = path` for writing at older versions
+///
+/// sorted by `from`
+pub fn migrate_attrs(attrs: &[Attribute]) -> syn::Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+    let mut errors = Errors::default();
+
+    for list in lists(attrs, "migrate")? {
+        let mut from: Option<u16> = None;
+        let mut via: Option<Type> = None;
+        let mut with: Option<(ExprPath, bool)> = None;
+        let mut downgrade: Option<ExprPath> = None;
+
+        let parsed = parse_key_values(&list, |key, input| {
+            match key.to_string().as_str() {
+                "from" => from = Some(parse_u16(input)?),
+                "via" => via = Some(parse(input)?),
+                "with" | "try_with" if with.is_some() => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "`with` and `try_with` cannot be combined",
+                    ));
+                }
+                "with" => with = Some((parse(input)?, false)),
+                "try_with" => with = Some((parse(input)?, true)),
+                "downgrade" => downgrade = Some(parse(input)?),
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown key `{key}` in migrate, expected one of: from, via, with, try_with, downgrade"
+                        ),
+                    ));
+                }
             }
-            panic!("version value must be an integer literal");
+            Ok(())
+        });
+        if errors.check(parsed).is_none() {
+            continue;
         }
 
-        None
+        let span = list.span();
+        let (Some(from), Some(via), Some((with, fallible))) = (from, via, with) else {
+            errors.push(syn::Error::new(
+                span,
+                "migrate: `from`, `via` and `with` (or `try_with`) are required",
+            ));
+            continue;
+        };
+
+        migrations.push(Migration {
+            from,
+            via,
+            with,
+            fallible,
+            downgrade,
+            span,
+        });
+    }
+
+    errors.finish()?;
+    migrations.sort_by_key(|m| m.from);
+    Ok(migrations)
+}
+
+/// type in a string literal of `#[rapira(key = "Type")]`
+fn type_value(attrs: &[Attribute], key: &str) -> syn::Result<Option<Type>> {
+    let Some(value) = name_value(attrs, key)? else {
+        return Ok(None);
+    };
+    match &value {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(ty),
+            ..
+        }) => ty.parse().map(Some),
+        _ => Err(syn::Error::new_spanned(
+            &value,
+            format!("{key} value must be a string literal: `{key} = \"Type\"`"),
+        )),
+    }
+}
+
+/// `#[rapira(validate = path)]` on container or field:
+/// `fn(&T) -> rapira::Result<()>` called on decoded values
+pub fn validate_attr(attrs: &[Attribute]) -> syn::Result<Option<ExprPath>> {
+    match name_value(attrs, "validate")? {
+        Some(Expr::Path(path)) => Ok(Some(path)),
+        Some(value) => Err(syn::Error::new_spanned(
+            &value,
+            "validate value must be a path: `validate = path::to::fn`",
+        )),
+        None => Ok(None),
+    }
+}
+
+/// `#[rapira(transparent)]` on struct
+pub fn transparent_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "transparent")
+}
+
+/// `#[rapira(from = "T")]` or `#[rapira(try_from = "T")]` and `#[rapira(into = "U")]`:
+/// read as `T` and converted by `From`/`TryFrom`, written converted by `Into<U>`,
+/// a missing side uses the type of the other one
+pub struct Proxy {
+    pub from: Type,
+    pub try_from: bool,
+    pub into: Type,
+}
+
+pub fn proxy_attr(attrs: &[Attribute]) -> syn::Result<Option<Proxy>> {
+    let from = type_value(attrs, "from")?;
+    let try_from = type_value(attrs, "try_from")?;
+    let into = type_value(attrs, "into")?;
+    if let (Some(_), Some(try_from)) = (&from, &try_from) {
+        return Err(syn::Error::new_spanned(
+            try_from,
+            "#[rapira(from)] and #[rapira(try_from)] cannot be combined",
+        ));
+    }
+    let (from, fallible) = match (from, try_from) {
+        (Some(from), _) => (Some(from), false),
+        (None, Some(try_from)) => (Some(try_from), true),
+        (None, None) => (None, false),
+    };
+    Ok(match (from, into) {
+        (None, None) => None,
+        (Some(from), into) => Some(Proxy {
+            into: into.unwrap_or_else(|| from.clone()),
+            from,
+            try_from: fallible,
+        }),
+        (None, Some(into)) => Some(Proxy {
+            from: into.clone(),
+            try_from: false,
+            into,
+        }),
     })
 }
 
+/// `#[rapira(sparse)]` on struct
+pub fn sparse_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "sparse")
+}
+
+/// `#[rapira(fixed)]` on struct, enum or field
+pub fn fixed_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "fixed")
+}
+
+/// `#[rapira(extensible)]` on struct or enum with data
+pub fn extensible_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "extensible")
+}
+
+/// `#[rapira(magic = b"RPR1")]` on struct: bytes written before the fields and checked on read
+pub fn magic_attr(attrs: &[Attribute]) -> syn::Result<Option<LitByteStr>> {
+    match name_value(attrs, "magic")? {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::ByteStr(magic),
+            ..
+        })) if !magic.value().is_empty() => Ok(Some(magic)),
+        Some(value) => Err(syn::Error::new_spanned(
+            &value,
+            "magic value must be a non-empty byte string literal: `magic = b\"RPR1\"`",
+        )),
+        None => Ok(None),
+    }
+}
+
+/// `#[rapira(endian = "big")]` or `#[rapira(endian = "little")]` on container or field,
+/// `Some(true)` for big endian
+pub fn endian_attr(attrs: &[Attribute]) -> syn::Result<Option<bool>> {
+    match name_value(attrs, "endian")? {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Str(endian),
+            ..
+        })) if matches!(endian.value().as_str(), "big" | "little") => {
+            Ok(Some(endian.value() == "big"))
+        }
+        Some(value) => Err(syn::Error::new_spanned(
+            &value,
+            "endian value must be \"big\" or \"little\"",
+        )),
+        None => Ok(None),
+    }
+}
+
+/// `transparent`, `from`, `try_from` and `into` replace the derived layout,
+/// so only `crate` and `bound` can be set with them
+fn check_repr(attrs: &[Attribute]) -> syn::Result<()> {
+    let metas = rapira_metas(attrs)?;
+    let is_key = |meta: &Meta, keys: &[&str]| keys.iter().any(|key| meta.path().is_ident(key));
+    let (repr, allowed) =
+        if let Some(meta) = metas.iter().find(|meta| is_key(meta, &["transparent"])) {
+            (meta, TRANSPARENT_KEYS)
+        } else if let Some(meta) = metas.iter().find(|meta| is_key(meta, &PROXY_KEYS[2..])) {
+            (meta, PROXY_KEYS)
+        } else {
+            return Ok(());
+        };
+    let repr = quote::ToTokens::to_token_stream(repr.path()).to_string();
+
+    let mut errors = Errors::default();
+    for meta in metas.iter().filter(|meta| !is_key(meta, allowed)) {
+        let key = quote::ToTokens::to_token_stream(meta.path()).to_string();
+        errors.push(syn::Error::new_spanned(
+            meta.path(),
+            format!("#[rapira({key})] cannot be combined with #[rapira({repr})]"),
+        ));
+    }
+    errors.finish()
+}
+
 /// `#[rapira(debug)]`
-pub fn debug_attr(attrs: &[Attribute]) -> bool {
-    for item in attrs.iter() {
-        if item.path().is_ident("rapira")
-            && let Ok(nv) = item.parse_args::<Path>()
-            && nv.is_ident("debug")
-        {
-            return true;
+pub fn debug_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "debug")
+}
+
+fn is_tag_type(ident: &Ident) -> bool {
+    ident == "u8" || ident == "u16" || ident == "u32"
+}
+
+fn explicit_tag(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
+    let Some(value) = name_value(attrs, "tag")? else {
+        return Ok(None);
+    };
+    match &value {
+        Expr::Path(path) if path.path.get_ident().is_some_and(is_tag_type) => {
+            Ok(path.path.get_ident().cloned())
         }
+        _ => Err(syn::Error::new_spanned(
+            value,
+            "tag value must be one of `u8`, `u16`, `u32`",
+        )),
     }
-    false
+}
+
+/// `#[rapira(tag = u16)]` in enums: type of the variant tag, `u8` by default
+pub fn tag_attr(attrs: &[Attribute]) -> syn::Result<Ident> {
+    Ok(explicit_tag(attrs)?.unwrap_or_else(|| Ident::new("u8", Span::call_site())))
+}
+
+/// tag type of a simple enum: `#[rapira(tag = ..)]`,
+/// then `#[repr(u8 | u16 | u32)]`, `u8` by default;
+/// other integer reprs need an explicit tag
+pub fn simple_enum_tag(attrs: &[Attribute]) -> syn::Result<Ident> {
+    const INTEGERS: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+    ];
+    if let Some(tag) = explicit_tag(attrs)? {
+        return Ok(tag);
+    }
+    let repr = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<Ident, Comma>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .find(|ident| INTEGERS.iter().any(|int| ident == int));
+    match repr {
+        Some(repr) if is_tag_type(&repr) => Ok(repr),
+        Some(repr) => Err(syn::Error::new_spanned(
+            &repr,
+            format!(
+                "#[repr({repr})] is not a tag type, set #[rapira(tag = u8)], #[rapira(tag = u16)] or #[rapira(tag = u32)]"
+            ),
+        )),
+        None => Ok(Ident::new("u8", Span::call_site())),
+    }
+}
+
+/// check container attributes: unknown keys and malformed values
+pub fn check_container(attrs: &[Attribute], keys: &[&str], target: &str) -> syn::Result<()> {
+    rapira_metas(attrs)?;
+    let mut errors = Errors::default();
+    errors.check(check_keys(attrs, keys, target));
+    errors.check(crate_path(attrs));
+    errors.check(bound_attr(attrs));
+    errors.check(get_primitive_name(attrs));
+    errors.check(version_attr(attrs));
+    errors.check(retired_attrs(attrs));
+    errors.check(migrate_attrs(attrs));
+    errors.check(debug_attr(attrs));
+    errors.check(explicit_tag(attrs));
+    errors.check(validate_attr(attrs));
+    errors.check(transparent_attr(attrs));
+    errors.check(sparse_attr(attrs));
+    errors.check(fixed_attr(attrs));
+    errors.check(magic_attr(attrs));
+    errors.check(extensible_attr(attrs));
+    errors.check(endian_attr(attrs));
+    errors.check(proxy_attr(attrs));
+    errors.check(check_repr(attrs));
+    errors.check(max_cap_attr(attrs));
+    errors.finish()
 }
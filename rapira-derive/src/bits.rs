@@ -0,0 +1,145 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{Path, Type};
+
+use crate::shared::FieldCheck;
+
+/// Code of a group of consecutive `#[rapira(bits = N)]` fields packed into one bitfield
+pub struct BitGroup {
+    rapira: Path,
+    members: Vec<BitMember>,
+    used: u32,
+}
+
+struct BitMember {
+    ident: Ident,
+    value: TokenStream,
+    typ: Type,
+    offset: u32,
+    width: u32,
+    check: FieldCheck,
+}
+
+/// statements of a finished group for the generated methods
+pub struct BitCode {
+    /// bitfield bytes
+    pub size: usize,
+    /// `let` bindings of the fields, validated
+    pub read: TokenStream,
+    /// `let` bindings of the fields, for unchecked and unsafe methods
+    pub read_unchecked: TokenStream,
+    pub check: TokenStream,
+    pub write: TokenStream,
+    pub try_write: TokenStream,
+}
+
+/// for each field: whether a bitfield group ends with it, groups are consecutive
+/// `bits` fields with at most 64 bits in total
+pub fn group_ends(widths: &[Option<u32>]) -> Vec<bool> {
+    let mut ends = vec![false; widths.len()];
+    let mut used = 0;
+    for (idx, width) in widths.iter().enumerate() {
+        let Some(width) = width else {
+            used = 0;
+            continue;
+        };
+        used += width;
+        match widths.get(idx + 1).copied().flatten() {
+            Some(next) if used + next <= 64 => {}
+            _ => {
+                ends[idx] = true;
+                used = 0;
+            }
+        }
+    }
+    ends
+}
+
+impl BitGroup {
+    pub fn new(rapira: &Path) -> Self {
+        BitGroup {
+            rapira: rapira.clone(),
+            members: Vec::new(),
+            used: 0,
+        }
+    }
+
+    /// add a field read into `ident` and written from `value` (`&self.field`)
+    pub fn push(
+        &mut self,
+        ident: &Ident,
+        value: TokenStream,
+        typ: &Type,
+        width: u32,
+        check: FieldCheck,
+    ) {
+        self.members.push(BitMember {
+            ident: ident.clone(),
+            value,
+            typ: typ.clone(),
+            offset: self.used,
+            width,
+            check,
+        });
+        self.used += width;
+    }
+
+    /// code of the collected fields, the group is emptied for the next one
+    pub fn finish(&mut self) -> BitCode {
+        let rapira = &self.rapira;
+        let used = self.used;
+        let size = used.div_ceil(8) as usize;
+        let members = std::mem::take(&mut self.members);
+        self.used = 0;
+
+        let read_bits = quote! {
+            let __rapira_bits = #rapira::bits::read_bits(__rapira_slice, #size, #used)?;
+        };
+        let mut read = read_bits.clone();
+        let mut read_unchecked = read_bits.clone();
+        let mut check = read_bits;
+        let mut puts: Vec<TokenStream> = Vec::with_capacity(members.len());
+        let mut try_puts: Vec<TokenStream> = Vec::with_capacity(members.len());
+
+        for BitMember {
+            ident,
+            value,
+            typ,
+            offset,
+            width,
+            check: field_check,
+        } in &members
+        {
+            let get = quote! { #rapira::bits::get(__rapira_bits, #offset, #width)? };
+            let validate = field_check.validate(ident);
+            read.extend(quote! {
+                let #ident: #typ = #get;
+                #validate
+            });
+            read_unchecked.extend(quote! { let #ident: #typ = #get; });
+            check.extend(if validate.is_empty() {
+                quote! { let _: #typ = #get; }
+            } else {
+                quote! {
+                    let #ident: #typ = #get;
+                    #validate
+                }
+            });
+            puts.push(quote! { #rapira::bits::put(#value, #offset, #width) });
+            try_puts.push(quote! { #rapira::bits::try_put(#value, #offset, #width)? });
+        }
+
+        BitCode {
+            size,
+            read,
+            read_unchecked,
+            check,
+            write: quote! {
+                #rapira::bits::write_bits(0 #(| #puts)*, #size, __rapira_slice, __rapira_cursor);
+            },
+            try_write: quote! {
+                #rapira::bits::try_write_bits(0 #(| #try_puts)*, #size, __rapira_slice, __rapira_cursor)?;
+            },
+        }
+    }
+}
@@ -4,18 +4,43 @@ extern crate syn;
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
-use syn::{DataEnum, Field, Fields};
+use syn::{Attribute, DataEnum, Field, Fields};
 
-use crate::field_attrs::extract_idx_attr;
+use crate::{
+    attributes,
+    field_attrs::{extract_idx_attr, extract_since_attr},
+    shared::{
+        MigratePrelude, default_value, field_codec, max_cap_const, migrate_prelude, tag_read,
+        tag_size, tag_write, variant_since_checks, versioned_field, versioned_field_write,
+    },
+};
 
 pub fn enum_with_primitive_serializer(
     data_enum: &DataEnum,
     name: &Ident,
     primitive_name: Ident,
-) -> proc_macro::TokenStream {
+    attrs: &[Attribute],
+) -> syn::Result<TokenStream> {
+    let rapira = &attributes::crate_path(attrs)?;
+    let enum_version = attributes::version_attr(attrs)?;
+    let MigratePrelude {
+        read: migrations_read,
+        size: migrations_size,
+        write: migrations_write,
+    } = migrate_prelude(
+        rapira,
+        &attributes::migrate_attrs(attrs)?,
+        enum_version,
+        "enum",
+        name,
+    )?;
+    let tag = attributes::tag_attr(attrs)?;
     let variants_len = data_enum.variants.len();
 
     let mut from_slice: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut from_slice_versioned: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut size_versioned: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut convert_to_bytes_versioned: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut check_bytes: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut from_slice_unchecked: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut from_slice_unsafe: Vec<TokenStream> = Vec::with_capacity(variants_len);
@@ -27,9 +52,16 @@ pub fn enum_with_primitive_serializer(
     let mut convert_to_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut size_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
 
     for variant in &data_enum.variants {
         let variant_name = &variant.ident;
+
+        // variant added in version `since` is unknown for older data
+        let variant_since = extract_since_attr(&variant.attrs)?;
+        let (version_check, version_check_write) =
+            variant_since_checks(rapira, variant_since, name, variant_name);
+
         match &variant.fields {
             Fields::Unit => {
                 from_slice.push(quote! {
@@ -38,6 +70,26 @@ pub fn enum_with_primitive_serializer(
                     }
                 });
 
+                from_slice_versioned.push(quote! {
+                    #primitive_name::#variant_name => {
+                        #version_check
+                        Ok(#name::#variant_name)
+                    }
+                });
+
+                size_versioned.push(quote! {
+                    #name::#variant_name => {
+                        #version_check_write
+                        0
+                    }
+                });
+
+                convert_to_bytes_versioned.push(quote! {
+                    #name::#variant_name => {
+                        #version_check_write
+                    }
+                });
+
                 check_bytes.push(quote! {
                     #primitive_name::#variant_name => {}
                 });
@@ -85,12 +137,17 @@ pub fn enum_with_primitive_serializer(
                 size_ctx.push(quote! {
                     #name::#variant_name => 0,
                 });
+                check_bytes_ctx.push(quote! {
+                    #primitive_name::#variant_name => {}
+                });
             }
             Fields::Unnamed(fields) => {
                 let len = fields.unnamed.len();
                 if len == 1 {
                     let field = fields.unnamed.first().unwrap();
                     let typ = &field.ty;
+                    let since = extract_since_attr(&field.attrs)?;
+                    let default = default_value(&field.attrs)?;
 
                     from_slice.push(quote! {
                         #primitive_name::#variant_name => {
@@ -99,6 +156,39 @@ pub fn enum_with_primitive_serializer(
                         }
                     });
 
+                    let v = Ident::new("v", Span::call_site());
+                    let versioned_v = versioned_field(
+                        &field_codec(rapira, typ, None),
+                        &v,
+                        typ,
+                        since,
+                        None,
+                        &default,
+                        None,
+                    );
+                    from_slice_versioned.push(quote! {
+                        #primitive_name::#variant_name => {
+                            #version_check
+                            #versioned_v
+                            Ok(#name::#variant_name(v))
+                        }
+                    });
+
+                    let (v_size, v_write) =
+                        versioned_field_write(rapira, &quote! { v }, typ, None, since, None);
+                    size_versioned.push(quote! {
+                        #name::#variant_name(v) => {
+                            #version_check_write
+                            0 #v_size
+                        }
+                    });
+                    convert_to_bytes_versioned.push(quote! {
+                        #name::#variant_name(v) => {
+                            #version_check_write
+                            #v_write
+                        }
+                    });
+
                     check_bytes.push(quote! {
                         #primitive_name::#variant_name => {
                             <#typ>::check_bytes(__rapira_slice)?;
@@ -155,7 +245,7 @@ pub fn enum_with_primitive_serializer(
                     });
                     from_slice_ctx.push(quote! {
                         #primitive_name::#variant_name => {
-                            let v = <#typ as rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
+                            let v = <#typ as #rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
                             Ok(#name::#variant_name(v))
                         }
                     });
@@ -167,11 +257,21 @@ pub fn enum_with_primitive_serializer(
                             }
                         },
                     });
+                    check_bytes_ctx.push(quote! {
+                        #primitive_name::#variant_name => {
+                            <#typ as #rapira::Rapira>::check_bytes_ctx(__rapira_slice, __rapira_flags)?;
+                        }
+                    });
                 } else {
                     let unnamed = &fields.unnamed;
 
                     let mut field_names: Vec<TokenStream> = Vec::with_capacity(len);
                     let mut unnamed_from_slice: Vec<TokenStream> = Vec::with_capacity(len);
+                    let mut unnamed_from_slice_versioned: Vec<TokenStream> =
+                        Vec::with_capacity(len);
+                    let mut unnamed_size_versioned: Vec<TokenStream> = Vec::with_capacity(len);
+                    let mut unnamed_convert_to_bytes_versioned: Vec<TokenStream> =
+                        Vec::with_capacity(len);
                     let mut unnamed_check_bytes: Vec<TokenStream> = Vec::with_capacity(len);
                     let mut unnamed_from_slice_unchecked: Vec<TokenStream> =
                         Vec::with_capacity(len);
@@ -186,14 +286,36 @@ pub fn enum_with_primitive_serializer(
                         Vec::with_capacity(len);
                     let mut unnamed_from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(len);
                     let mut unnamed_size_ctx: Vec<TokenStream> = Vec::with_capacity(len);
+                    let mut unnamed_check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(len);
 
                     for (idx, field) in unnamed.iter().enumerate() {
                         let typ = &field.ty;
                         let field_name = syn::Ident::new(&format!("arg{idx}"), Span::call_site());
+                        let since = extract_since_attr(&field.attrs)?;
+                        let default = default_value(&field.attrs)?;
 
                         unnamed_from_slice.push(quote! {
                             let #field_name = <#typ>::from_slice(__rapira_slice)?;
                         });
+                        unnamed_from_slice_versioned.push(versioned_field(
+                            &field_codec(rapira, typ, None),
+                            &field_name,
+                            typ,
+                            since,
+                            None,
+                            &default,
+                            None,
+                        ));
+                        let (field_size, field_write) = versioned_field_write(
+                            rapira,
+                            &quote! { #field_name },
+                            typ,
+                            None,
+                            since,
+                            None,
+                        );
+                        unnamed_size_versioned.push(field_size);
+                        unnamed_convert_to_bytes_versioned.push(field_write);
                         unnamed_check_bytes.push(quote! {
                             <#typ>::check_bytes(__rapira_slice)?;
                         });
@@ -223,12 +345,15 @@ pub fn enum_with_primitive_serializer(
                             #field_name.convert_to_bytes_ctx(__rapira_slice, __rapira_cursor, __rapira_flags);
                         });
                         unnamed_from_slice_ctx.push(quote! {
-                            let #field_name = <#typ as rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
+                            let #field_name = <#typ as #rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
                         });
                         unnamed_size_ctx.push(quote! { + (match <#typ>::STATIC_SIZE {
                             Some(s) => s,
                             None => #field_name.size_ctx(__rapira_flags)
                         }) });
+                        unnamed_check_bytes_ctx.push(quote! {
+                            <#typ as #rapira::Rapira>::check_bytes_ctx(__rapira_slice, __rapira_flags)?;
+                        });
                         field_names.push(quote! { #field_name, });
                     }
 
@@ -239,6 +364,28 @@ pub fn enum_with_primitive_serializer(
                         }
                     });
 
+                    from_slice_versioned.push(quote! {
+                        #primitive_name::#variant_name => {
+                            #version_check
+                            #(#unnamed_from_slice_versioned)*
+                            Ok(#name::#variant_name(#(#field_names)*))
+                        }
+                    });
+
+                    size_versioned.push(quote! {
+                        #name::#variant_name(#(#field_names)*) => {
+                            #version_check_write
+                            0 #(#unnamed_size_versioned)*
+                        }
+                    });
+
+                    convert_to_bytes_versioned.push(quote! {
+                        #name::#variant_name(#(#field_names)*) => {
+                            #version_check_write
+                            #(#unnamed_convert_to_bytes_versioned)*
+                        }
+                    });
+
                     check_bytes.push(quote! {
                         #primitive_name::#variant_name => {
                             #(#unnamed_check_bytes)*
@@ -278,11 +425,11 @@ pub fn enum_with_primitive_serializer(
                     });
 
                     enum_sizes.push(quote! {
-                        rapira::static_size([#(#unnamed_static_sizes)*]),
+                        #rapira::static_size([#(#unnamed_static_sizes)*]),
                     });
 
                     min_sizes.push(quote! {
-                        rapira::min_size(&[#(#unnamed_min_sizes)*]),
+                        #rapira::min_size(&[#(#unnamed_min_sizes)*]),
                     });
 
                     convert_to_bytes_ctx.push(quote! {
@@ -301,6 +448,11 @@ pub fn enum_with_primitive_serializer(
                             0 #(#unnamed_size_ctx)*
                         },
                     });
+                    check_bytes_ctx.push(quote! {
+                        #primitive_name::#variant_name => {
+                            #(#unnamed_check_bytes_ctx)*
+                        }
+                    });
                 }
             }
             Fields::Named(fields) => {
@@ -311,7 +463,7 @@ pub fn enum_with_primitive_serializer(
                 let mut seq = 0u32;
 
                 for field in named.iter() {
-                    let field_idx = extract_idx_attr(&field.attrs);
+                    let field_idx = extract_idx_attr(&field.attrs)?;
                     let field_idx = field_idx.unwrap_or_else(|| {
                         let current_seq = seq;
                         seq += 1;
@@ -321,10 +473,14 @@ pub fn enum_with_primitive_serializer(
                     fields_insert.push((field.clone(), field_idx));
                 }
 
-                fields_insert.sort_by(|(_, idx_a), (_, idx_b)| idx_a.cmp(idx_b));
+                fields_insert.sort_by_key(|(_, idx)| *idx);
 
                 let mut field_names: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut named_from_slice: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut named_from_slice_versioned: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut named_size_versioned: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut named_convert_to_bytes_versioned: Vec<TokenStream> =
+                    Vec::with_capacity(len);
                 let mut named_check_bytes: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut named_from_slice_unchecked: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut named_from_slice_unsafe: Vec<TokenStream> = Vec::with_capacity(len);
@@ -336,14 +492,36 @@ pub fn enum_with_primitive_serializer(
                 let mut named_convert_to_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut named_from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut named_size_ctx: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut named_check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(len);
 
                 for field in fields_insert.iter().map(|(f, _)| f) {
                     let typ = &field.ty;
                     let field_name = field.ident.as_ref().unwrap();
+                    let since = extract_since_attr(&field.attrs)?;
+                    let default = default_value(&field.attrs)?;
 
                     named_from_slice.push(quote! {
                         let #field_name = <#typ>::from_slice(__rapira_slice)?;
                     });
+                    named_from_slice_versioned.push(versioned_field(
+                        &field_codec(rapira, typ, None),
+                        field_name,
+                        typ,
+                        since,
+                        None,
+                        &default,
+                        None,
+                    ));
+                    let (field_size, field_write) = versioned_field_write(
+                        rapira,
+                        &quote! { #field_name },
+                        typ,
+                        None,
+                        since,
+                        None,
+                    );
+                    named_size_versioned.push(field_size);
+                    named_convert_to_bytes_versioned.push(field_write);
                     named_check_bytes.push(quote! {
                         <#typ>::check_bytes(__rapira_slice)?;
                     });
@@ -373,12 +551,15 @@ pub fn enum_with_primitive_serializer(
                         #field_name.convert_to_bytes_ctx(__rapira_slice, __rapira_cursor, __rapira_flags);
                     });
                     named_from_slice_ctx.push(quote! {
-                        let #field_name = <#typ as rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
+                        let #field_name = <#typ as #rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
                     });
                     named_size_ctx.push(quote! { + (match <#typ>::STATIC_SIZE {
                         Some(s) => s,
                         None => #field_name.size_ctx(__rapira_flags)
                     }) });
+                    named_check_bytes_ctx.push(quote! {
+                        <#typ as #rapira::Rapira>::check_bytes_ctx(__rapira_slice, __rapira_flags)?;
+                    });
                     field_names.push(quote! { #field_name, });
                 }
 
@@ -389,6 +570,28 @@ pub fn enum_with_primitive_serializer(
                     }
                 });
 
+                from_slice_versioned.push(quote! {
+                    #primitive_name::#variant_name => {
+                        #version_check
+                        #(#named_from_slice_versioned)*
+                        Ok(#name::#variant_name{#(#field_names)*})
+                    }
+                });
+
+                size_versioned.push(quote! {
+                    #name::#variant_name{#(#field_names)*} => {
+                        #version_check_write
+                        0 #(#named_size_versioned)*
+                    }
+                });
+
+                convert_to_bytes_versioned.push(quote! {
+                    #name::#variant_name{#(#field_names)*} => {
+                        #version_check_write
+                        #(#named_convert_to_bytes_versioned)*
+                    }
+                });
+
                 check_bytes.push(quote! {
                     #primitive_name::#variant_name => {
                         #(#named_check_bytes)*
@@ -428,11 +631,11 @@ pub fn enum_with_primitive_serializer(
                 });
 
                 enum_sizes.push(quote! {
-                    rapira::static_size([#(#named_static_sizes)*]),
+                    #rapira::static_size([#(#named_static_sizes)*]),
                 });
 
                 min_sizes.push(quote! {
-                    rapira::min_size(&[#(#named_min_sizes)*]),
+                    #rapira::min_size(&[#(#named_min_sizes)*]),
                 });
 
                 convert_to_bytes_ctx.push(quote! {
@@ -451,34 +654,93 @@ pub fn enum_with_primitive_serializer(
                         0 #(#named_size_ctx)*
                     },
                 });
+                check_bytes_ctx.push(quote! {
+                    #primitive_name::#variant_name => {
+                        #(#named_check_bytes_ctx)*
+                    }
+                });
             }
         };
     }
 
+    let tag_size = tag_size(&tag);
+    let read_tag = tag_read(rapira, &tag, "from_slice", false);
+    let read_tag_unsafe = tag_read(rapira, &tag, "from_slice_unsafe", false);
+    let write_tag = tag_write(rapira, &tag, &quote! { t }, false);
+    // `u8` tags are converted by `FromU8`, wider ones by comparing with the primitive discriminants
+    let (to_primitive, to_primitive_unsafe) = if tag == "u8" {
+        (
+            quote! { <#primitive_name as TryFrom<u8>>::try_from(val).map_err(|_| #rapira::RapiraError::EnumVariant)? },
+            quote! { <#primitive_name as #rapira::FromU8>::from_u8(val) },
+        )
+    } else {
+        let variant_names = data_enum.variants.iter().map(|variant| &variant.ident);
+        let to_primitive = quote! {
+            match val {
+                #(v if v == #primitive_name::#variant_names as #tag => #primitive_name::#variant_names,)*
+                _ => return Err(#rapira::RapiraError::EnumVariant),
+            }
+        };
+        (to_primitive.clone(), to_primitive)
+    };
+
+    let max_cap = max_cap_const(attrs)?;
     let res = quote! {
-        impl rapira::Rapira for #name {
-            const STATIC_SIZE: Option<usize> = rapira::enum_size([#(#enum_sizes)*]);
-            const MIN_SIZE: usize = rapira::enum_min_size(&[#(#min_sizes)*]);
+        impl #rapira::Rapira for #name {
+            const STATIC_SIZE: Option<usize> = #rapira::enum_size_tagged([#(#enum_sizes)*], #tag_size);
+            const MIN_SIZE: usize = #rapira::enum_min_size_tagged(&[#(#min_sizes)*], #tag_size);
+            #max_cap
 
             #[inline]
-            fn from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                let t = <#primitive_name as TryFrom<u8>>::try_from(val).map_err(|_| rapira::RapiraError::EnumVariant)?;
+                let val: #tag = #read_tag;
+                let t = #to_primitive;
                 match t {
                     #(#from_slice)*
                 }
             }
 
             #[inline]
-            fn check_bytes(__rapira_slice: &mut &[u8]) -> rapira::Result<()>
+            fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u16) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                #migrations_read
+                let val: #tag = #read_tag;
+                let t = #to_primitive;
+                match t {
+                    #(#from_slice_versioned)*
+                }
+            }
+
+            #[inline]
+            fn convert_to_bytes_versioned(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_version: u16) {
+                #migrations_write
+                let t = #primitive_name::from(self) as #tag;
+                #write_tag
+                match self {
+                    #(#convert_to_bytes_versioned)*
+                }
+            }
+
+            #[inline]
+            fn size_versioned(&self, __rapira_version: u16) -> usize {
+                #migrations_size
+                #tag_size + match self {
+                    #(#size_versioned)*
+                }
+            }
+
+            #[inline]
+            fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                let t = <#primitive_name as TryFrom<u8>>::try_from(val).map_err(|_| rapira::RapiraError::EnumVariant)?;
+                let val: #tag = #read_tag;
+                let t = #to_primitive;
                 match t {
                     #(#check_bytes)*
                 }
@@ -486,34 +748,33 @@ pub fn enum_with_primitive_serializer(
             }
 
             #[inline]
-            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                let t = <#primitive_name as TryFrom<u8>>::try_from(val)
-                    .map_err(|_| rapira::RapiraError::EnumVariant)?;
+                let val: #tag = #read_tag;
+                let t = #to_primitive;
                 match t {
                     #(#from_slice_unchecked)*
                 }
             }
 
             #[inline]
-            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice_unsafe(__rapira_slice)?;
-                let t = <#primitive_name as rapira::FromU8>::from_u8(val);
+                let val: #tag = #read_tag_unsafe;
+                let t = #to_primitive_unsafe;
                 match t {
                     #(#from_slice_unsafe)*
                 }
             }
 
             #[inline]
-            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> rapira::Result<()> {
-                let t = #primitive_name::from(self) as u8;
-                rapira::push(__rapira_slice, __rapira_cursor, t);
+            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
+                let t = #primitive_name::from(self) as #tag;
+                #write_tag
                 match self {
                     #(#try_convert_to_bytes)*
                 }
@@ -522,8 +783,8 @@ pub fn enum_with_primitive_serializer(
 
             #[inline]
             fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {
-                let t = #primitive_name::from(self) as u8;
-                rapira::push(__rapira_slice, __rapira_cursor, t);
+                let t = #primitive_name::from(self) as #tag;
+                #write_tag
                 match self {
                     #(#convert_to_bytes)*
                 }
@@ -531,40 +792,53 @@ pub fn enum_with_primitive_serializer(
 
             #[inline]
             fn size(&self) -> usize {
-                1 + match self {
+                #tag_size + match self {
                     #(#size)*
                 }
             }
 
             #[inline]
-            fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: rapira::RapiraFlags) {
-                let t = #primitive_name::from(self) as u8;
-                rapira::push(__rapira_slice, __rapira_cursor, t);
+            fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: #rapira::RapiraFlags) {
+                let t = #primitive_name::from(self) as #tag;
+                #write_tag
                 match self {
                     #(#convert_to_bytes_ctx)*
                 }
             }
 
             #[inline]
-            fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: rapira::RapiraFlags) -> rapira::Result<Self>
+            fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                let t = <#primitive_name as TryFrom<u8>>::try_from(val).map_err(|_| rapira::RapiraError::EnumVariant)?;
+                let val: #tag = #read_tag;
+                let t = #to_primitive;
                 match t {
                     #(#from_slice_ctx)*
                 }
             }
 
             #[inline]
-            fn size_ctx(&self, __rapira_flags: rapira::RapiraFlags) -> usize {
-                1 + match self {
+            fn size_ctx(&self, __rapira_flags: #rapira::RapiraFlags) -> usize {
+                #tag_size + match self {
                     #(#size_ctx)*
                 }
             }
+
+            #[inline]
+            fn check_bytes_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<()>
+            where
+                Self: Sized,
+            {
+                let val: #tag = #read_tag;
+                let t = #to_primitive;
+                match t {
+                    #(#check_bytes_ctx)*
+                }
+                Ok(())
+            }
         }
     };
 
-    proc_macro::TokenStream::from(res)
+    Ok(res)
 }
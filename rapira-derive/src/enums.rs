@@ -4,11 +4,19 @@ extern crate syn;
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
-use syn::{DataEnum, Expr, Field, Fields, Generics};
+use syn::{Attribute, DataEnum, Expr, Field, Fields, Generics, Member, Path, Type, Variant};
 
 use crate::{
-    field_attrs::{extract_idx_attr, extract_with_attr},
-    shared::build_ident,
+    attributes,
+    field_attrs::{
+        extract_idx_attr, extract_since_attr, flag_condition_attr, flatten_attr, unknown_attr,
+    },
+    shared::{
+        FieldCheck, MigratePrelude, build_ident, debug_read, default_value, field_codec,
+        field_with, flag_present, flatten_check, impl_bounds, max_cap_const, migrate_prelude,
+        read_if, size_if, tag_lit, tag_read, tag_size, tag_write, validated, variant_since_checks,
+        variant_tags, versioned_field, versioned_field_write, write_if,
+    },
 };
 
 pub fn enum_serializer(
@@ -18,14 +26,52 @@ pub fn enum_serializer(
     min_size: Option<Expr>,
     generics: Generics,
     is_debug: bool,
-) -> proc_macro::TokenStream {
-    let variants_len = data_enum.variants.len();
+    attrs: &[Attribute],
+) -> syn::Result<TokenStream> {
+    let rapira = &attributes::crate_path(attrs)?;
+    let validate = attributes::validate_attr(attrs)?;
+    let fixed = attributes::fixed_attr(attrs)?;
+    let big = attributes::endian_attr(attrs)?.unwrap_or(false);
+    let enum_version = attributes::version_attr(attrs)?;
+    let MigratePrelude {
+        read: migrations_read,
+        size: migrations_size,
+        write: migrations_write,
+    } = migrate_prelude(
+        rapira,
+        &attributes::migrate_attrs(attrs)?,
+        enum_version,
+        "enum",
+        name,
+    )?;
+    let tag = attributes::tag_attr(attrs)?;
+    let tag_size = tag_size(&tag);
+    let read_tag = tag_read(rapira, &tag, "from_slice", big);
+    let read_tag_unsafe = tag_read(rapira, &tag, "from_slice_unsafe", big);
+    let extensible = attributes::extensible_attr(attrs)?;
+    if extensible && fixed {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!("enum `{name}`: #[rapira(extensible)] and #[rapira(fixed)] cannot be combined"),
+        ));
+    }
+    let unknown = unknown_variant(data_enum, name, &tag, extensible)?;
+    // the `unknown` variant is the last one and has no tag of its own
+    let known: Vec<&Variant> = data_enum
+        .variants
+        .iter()
+        .take(data_enum.variants.len() - usize::from(unknown.is_some()))
+        .collect();
+    let variants_len = known.len();
 
     let mut enum_sizes: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut min_sizes: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut size: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut check_bytes: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut from_slice: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut from_slice_versioned: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut size_versioned: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut convert_to_bytes_versioned: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut debug_from_slice: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut from_slice_unchecked: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut from_slice_unsafe: Vec<TokenStream> = Vec::with_capacity(variants_len);
@@ -34,17 +80,31 @@ pub fn enum_serializer(
     let mut convert_to_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
     let mut size_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
+    let mut check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(variants_len);
 
-    let variants_iter = data_enum.variants.iter().enumerate().map(|(idx, variant)| {
-        let id: u8 = extract_idx_attr(&variant.attrs)
-            .map(|idx| idx as u8)
-            .unwrap_or(idx as u8);
+    let mut idxs = Vec::with_capacity(variants_len);
+    for variant in &known {
+        idxs.push((&variant.ident, extract_idx_attr(&variant.attrs)?));
+    }
+    let tags = variant_tags(idxs.into_iter(), &tag, name)?;
 
-        (id, variant)
-    });
+    // `extensible`: reserve the payload length at `__rapira_payload`, written after the match
+    let payload_begin = if extensible {
+        quote! { __rapira_payload = #rapira::extensible::begin(__rapira_cursor); }
+    } else {
+        quote!()
+    };
 
-    for (variant_id, variant) in variants_iter {
+    for (variant, id) in known.iter().zip(tags) {
         let variant_name = &variant.ident;
+        let variant_id = tag_lit(id, &tag);
+        let write_tag = tag_write(rapira, &tag, &quote! { #variant_id }, big);
+        let write_tag = quote! { #write_tag #payload_begin };
+
+        // variant added in version `since` is unknown for older data
+        let variant_since = extract_since_attr(&variant.attrs)?;
+        let (version_check, version_check_write) =
+            variant_since_checks(rapira, variant_since, name, variant_name);
 
         match &variant.fields {
             Fields::Unit => {
@@ -53,6 +113,24 @@ pub fn enum_serializer(
                         Ok(#name::#variant_name)
                     }
                 });
+                from_slice_versioned.push(quote! {
+                    #variant_id => {
+                        #version_check
+                        Ok(#name::#variant_name)
+                    }
+                });
+                size_versioned.push(quote! {
+                    #name::#variant_name => {
+                        #version_check_write
+                        0
+                    }
+                });
+                convert_to_bytes_versioned.push(quote! {
+                    #name::#variant_name => {
+                        #version_check_write
+                        #write_tag
+                    }
+                });
                 check_bytes.push(quote! {
                     #variant_id => {}
                 });
@@ -68,19 +146,22 @@ pub fn enum_serializer(
                 });
                 try_convert_to_bytes.push(quote! {
                     #name::#variant_name => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                     }
                 });
                 convert_to_bytes.push(quote! {
                     #name::#variant_name => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                     }
                 });
                 size.push(quote! {
                     #name::#variant_name => 0,
                 });
-                enum_sizes.push(quote! {
-                    None,
+                // padded like the other variants in fixed enums
+                enum_sizes.push(if fixed {
+                    quote! { Some(0), }
+                } else {
+                    quote! { None, }
                 });
                 min_sizes.push(quote! {
                     0,
@@ -93,7 +174,7 @@ pub fn enum_serializer(
                 });
                 convert_to_bytes_ctx.push(quote! {
                     #name::#variant_name => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                     }
                 });
                 from_slice_ctx.push(quote! {
@@ -104,6 +185,9 @@ pub fn enum_serializer(
                 size_ctx.push(quote! {
                     #name::#variant_name => 0,
                 });
+                check_bytes_ctx.push(quote! {
+                    #variant_id => {}
+                });
             }
             Fields::Unnamed(fields) => {
                 let len = fields.unnamed.len();
@@ -114,6 +198,10 @@ pub fn enum_serializer(
                 let mut fields_min_sizes: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_size: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_from_slice: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_from_slice_versioned: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_size_versioned: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_convert_to_bytes_versioned: Vec<TokenStream> =
+                    Vec::with_capacity(len);
                 let mut fields_debug_from_slice: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_check_bytes: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_from_slice_unchecked: Vec<TokenStream> = Vec::with_capacity(len);
@@ -123,122 +211,140 @@ pub fn enum_serializer(
                 let mut fields_convert_to_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_size_ctx: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(len);
 
                 for (idx, field) in fields.iter().enumerate() {
                     let typ = &field.ty;
+                    // `omit_if`/`only_if` fields: plain methods behave as `RapiraFlags::NONE`
+                    let condition = flag_condition_attr(&field.attrs)?;
+                    let present = flag_present(&condition, &quote! { #rapira::RapiraFlags::NONE });
+                    let present_ctx = flag_present(&condition, &quote! { __rapira_flags });
+                    let flatten = flatten_attr(&field.attrs)?;
+                    fields_from_slice.push(flatten_check(rapira, typ, flatten));
+                    let default = default_value(&field.attrs)?;
+                    let check = FieldCheck::new(rapira, field, format!("{variant_name}.{idx}"))?;
+                    let len_check = check.len_check();
                     let field_name = syn::Ident::new(&format!("arg{idx}"), Span::call_site());
-                    let with_attr = extract_with_attr(&field.attrs);
+                    let with_attr = field_with(rapira, field, attrs)?;
+                    let since = extract_since_attr(&field.attrs)?;
 
                     field_names.push(quote! { #field_name, });
+                    fields_from_slice_versioned.push(versioned_field(
+                        &field_codec(rapira, typ, with_attr.as_ref()),
+                        &field_name,
+                        typ,
+                        since,
+                        present.as_ref(),
+                        &default,
+                        Some(&check),
+                    ));
+                    fields_from_slice_versioned.push(check.validate(&field_name));
+                    let (field_size, field_write) = versioned_field_write(
+                        rapira,
+                        &quote! { #field_name },
+                        typ,
+                        with_attr.as_ref(),
+                        since,
+                        present.as_ref(),
+                    );
+                    fields_size_versioned.push(field_size);
+                    fields_convert_to_bytes_versioned.push(field_write);
 
-                    match with_attr {
-                        Some(with_attr) => {
-                            fields_static_sizes.push(quote! {
-                                #with_attr::static_size(core::marker::PhantomData::<#typ>),
-                            });
-                            fields_min_sizes.push(quote! {
-                                #with_attr::min_size(core::marker::PhantomData::<#typ>),
-                            });
-                            fields_size.push(
-                                quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                                    Some(s) => s,
-                                    None => #with_attr::size(#field_name)
-                                }) },
-                            );
-                            fields_check_bytes.push(quote! {
-                                #with_attr::check_bytes(core::marker::PhantomData::<#typ>, __rapira_slice)?;
-                            });
-                            fields_from_slice.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice(__rapira_slice)?;
-                            });
-                            fields_debug_from_slice.push(quote! {
+                    let codec = field_codec(rapira, typ, with_attr.as_ref());
+                    if present.is_some() {
+                        fields_static_sizes.push(quote! { None, });
+                        fields_min_sizes.push(quote! { 0, });
+                    } else {
+                        fields_static_sizes.push(quote! { #codec::STATIC_SIZE, });
+                        fields_min_sizes.push(quote! { #codec::MIN_SIZE, });
+                    }
+                    fields_size.push(size_if(
+                        present.as_ref(),
+                        quote! { (match #codec::STATIC_SIZE {
+                            Some(s) => s,
+                            None => #codec::size(#field_name)
+                        }) },
+                    ));
+                    fields_check_bytes.push(write_if(
+                        present.as_ref(),
+                        check.check_bytes(&codec, typ, false),
+                    ));
+                    fields_from_slice.push(read_if(
+                        present.as_ref(),
+                        &field_name,
+                        typ,
+                        check.read_slice(&codec),
+                        &default,
+                    ));
+                    fields_from_slice.push(check.validate(&field_name));
+                    fields_debug_from_slice.push(read_if(
+                        present.as_ref(),
+                        &field_name,
+                        typ,
+                        debug_read(
+                            rapira,
+                            typ,
+                            flatten,
+                            quote! {{
+                                #len_check
                                 let len = __rapira_slice.len();
                                 println!("Field: unnamed (index {}), Type: {}", #idx, stringify!(#typ));
-                                let res = #with_attr::from_slice(__rapira_slice).inspect(|v| {
+                                let res = #codec::from_slice(__rapira_slice).inspect(|v| {
                                     println!("len: {len}, unnamed (index {}): {v:?}", #idx);
                                 }).inspect_err(|err| {
                                     println!("len: {len}, err: {err:?}");
                                 });
-                                let #field_name: #typ = res?;
-                            });
-                            fields_from_slice_unchecked.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice_unchecked(__rapira_slice)?;
-                            });
-                            fields_from_slice_unsafe.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice_unsafe(__rapira_slice)?;
-                            });
-                            fields_try_convert_to_bytes.push(quote! {
-                                #with_attr::try_convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor)?;
-                            });
-                            fields_convert_to_bytes.push(quote! {
-                                #with_attr::convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor);
-                            });
-                            fields_convert_to_bytes_ctx.push(quote! {
-                                #with_attr::convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor);
-                            });
-                            fields_from_slice_ctx.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice(__rapira_slice)?;
-                            });
-                            fields_size_ctx.push(quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                                Some(s) => s,
-                                None => #with_attr::size(#field_name)
-                            }) });
-                        }
-                        None => {
-                            fields_static_sizes.push(quote! {
-                                <#typ as rapira::Rapira>::STATIC_SIZE,
-                            });
-                            fields_min_sizes.push(quote! {
-                                <#typ as rapira::Rapira>::MIN_SIZE,
-                            });
-                            fields_size.push(
-                                quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                                    Some(s) => s,
-                                    None => #field_name.size()
-                                }) },
-                            );
-                            fields_from_slice.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice(__rapira_slice)?;
-                            });
-                            fields_debug_from_slice.push(quote! {
-                                let len = __rapira_slice.len();
-                                println!("Field: unnamed (index {}), Type: {}", #idx, stringify!(#typ));
-                                let res = <#typ as rapira::Rapira>::from_slice(__rapira_slice).inspect(|v| {
-                                    println!("len: {len}, unnamed: {v:?}");
-                                }).inspect_err(|err| {
-                                    println!("len: {len}, err: {err:?}");
-                                });
-                                let #field_name: #typ = res?;
-                            });
-                            fields_check_bytes.push(quote! {
-                                <#typ as rapira::Rapira>::check_bytes(__rapira_slice)?;
-                            });
-                            fields_from_slice_unchecked.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice_unchecked(__rapira_slice)?;
-                            });
-                            fields_from_slice_unsafe.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice_unsafe(__rapira_slice)?;
-                            });
-                            fields_try_convert_to_bytes.push(quote! {
-                                #field_name.try_convert_to_bytes(__rapira_slice, __rapira_cursor)?;
-                            });
-                            fields_convert_to_bytes.push(quote! {
-                                #field_name.convert_to_bytes(__rapira_slice, __rapira_cursor);
-                            });
-                            fields_convert_to_bytes_ctx.push(quote! {
-                                #field_name.convert_to_bytes_ctx(__rapira_slice, __rapira_cursor, __rapira_flags);
-                            });
-                            fields_from_slice_ctx.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
-                            });
-                            fields_size_ctx.push(
-                                quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                                    Some(s) => s,
-                                    None => #field_name.size_ctx(__rapira_flags)
-                                }) },
-                            );
-                        }
-                    }
+                                res?
+                            }},
+                        ),
+                        &default,
+                    ));
+                    fields_debug_from_slice.push(check.validate(&field_name));
+                    fields_from_slice_unchecked.push(read_if(
+                        present.as_ref(),
+                        &field_name,
+                        typ,
+                        quote! { #codec::from_slice_unchecked(__rapira_slice)? },
+                        &default,
+                    ));
+                    fields_from_slice_unsafe.push(read_if(
+                        present.as_ref(),
+                        &field_name,
+                        typ,
+                        quote! { #codec::from_slice_unsafe(__rapira_slice)? },
+                        &default,
+                    ));
+                    fields_try_convert_to_bytes.push(write_if(
+                        present.as_ref(),
+                        quote! { #codec::try_convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor)?; },
+                    ));
+                    fields_convert_to_bytes.push(write_if(
+                        present.as_ref(),
+                        quote! { #codec::convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor); },
+                    ));
+                    fields_convert_to_bytes_ctx.push(write_if(
+                        present_ctx.as_ref(),
+                        quote! { #codec::convert_to_bytes_ctx(#field_name, __rapira_slice, __rapira_cursor, __rapira_flags); },
+                    ));
+                    fields_from_slice_ctx.push(read_if(
+                        present_ctx.as_ref(),
+                        &field_name,
+                        typ,
+                        check.read_ctx(&codec),
+                        &default,
+                    ));
+                    fields_from_slice_ctx.push(check.validate(&field_name));
+                    fields_check_bytes_ctx.push(write_if(
+                        present_ctx.as_ref(),
+                        check.check_bytes(&codec, typ, true),
+                    ));
+                    fields_size_ctx.push(size_if(
+                        present_ctx.as_ref(),
+                        quote! { (match #codec::STATIC_SIZE {
+                            Some(s) => s,
+                            None => #codec::size_ctx(#field_name, __rapira_flags)
+                        }) },
+                    ));
                 }
 
                 size.push(quote! {
@@ -247,10 +353,10 @@ pub fn enum_serializer(
                     },
                 });
                 enum_sizes.push(quote! {
-                    rapira::static_size([#(#fields_static_sizes)*]),
+                    #rapira::static_size([#(#fields_static_sizes)*]),
                 });
                 min_sizes.push(quote! {
-                    rapira::min_size(&[#(#fields_min_sizes)*]),
+                    #rapira::min_size(&[#(#fields_min_sizes)*]),
                 });
                 from_slice.push(quote! {
                     #variant_id => {
@@ -258,6 +364,26 @@ pub fn enum_serializer(
                         Ok(#name::#variant_name(#(#field_names)*))
                     }
                 });
+                from_slice_versioned.push(quote! {
+                    #variant_id => {
+                        #version_check
+                        #(#fields_from_slice_versioned)*
+                        Ok(#name::#variant_name(#(#field_names)*))
+                    }
+                });
+                size_versioned.push(quote! {
+                    #name::#variant_name(#(#field_names)*) => {
+                        #version_check_write
+                        0 #(#fields_size_versioned)*
+                    }
+                });
+                convert_to_bytes_versioned.push(quote! {
+                    #name::#variant_name(#(#field_names)*) => {
+                        #version_check_write
+                        #write_tag
+                        #(#fields_convert_to_bytes_versioned)*
+                    }
+                });
                 debug_from_slice.push(quote! {
                     #variant_id => {
                         println!("Variant: {}::{}", stringify!(#name), stringify!(#variant_name));
@@ -286,21 +412,21 @@ pub fn enum_serializer(
 
                 try_convert_to_bytes.push(quote! {
                     #name::#variant_name(#(#field_names)*) => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                         #(#fields_try_convert_to_bytes)*
                     }
                 });
 
                 convert_to_bytes.push(quote! {
                     #name::#variant_name(#(#field_names)*) => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                         #(#fields_convert_to_bytes)*
                     }
                 });
 
                 convert_to_bytes_ctx.push(quote! {
                     #name::#variant_name(#(#field_names)*) => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                         #(#fields_convert_to_bytes_ctx)*
                     }
                 });
@@ -315,6 +441,11 @@ pub fn enum_serializer(
                         0 #(#fields_size_ctx)*
                     },
                 });
+                check_bytes_ctx.push(quote! {
+                    #variant_id => {
+                        #(#fields_check_bytes_ctx)*
+                    }
+                });
             }
             Fields::Named(fields) => {
                 let len = fields.named.len();
@@ -324,7 +455,7 @@ pub fn enum_serializer(
                 let mut seq = 0u32;
 
                 for field in fields.iter() {
-                    let field_idx = extract_idx_attr(&field.attrs);
+                    let field_idx = extract_idx_attr(&field.attrs)?;
                     let field_idx = field_idx.unwrap_or_else(|| {
                         let current_seq = seq;
                         seq += 1;
@@ -334,10 +465,14 @@ pub fn enum_serializer(
                     fields_insert.push((field.clone(), field_idx));
                 }
 
-                fields_insert.sort_by(|(_, idx_a), (_, idx_b)| idx_a.cmp(idx_b));
+                fields_insert.sort_by_key(|(_, idx)| *idx);
 
                 let mut field_names: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_from_slice: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_from_slice_versioned: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_size_versioned: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_convert_to_bytes_versioned: Vec<TokenStream> =
+                    Vec::with_capacity(len);
                 let mut fields_check_bytes: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_from_slice_unchecked: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_from_slice_unsafe: Vec<TokenStream> = Vec::with_capacity(len);
@@ -350,122 +485,141 @@ pub fn enum_serializer(
                 let mut fields_convert_to_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(len);
                 let mut fields_size_ctx: Vec<TokenStream> = Vec::with_capacity(len);
+                let mut fields_check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(len);
 
                 for field in fields_insert.iter().map(|(f, _)| f) {
                     let typ = &field.ty;
+                    // `omit_if`/`only_if` fields: plain methods behave as `RapiraFlags::NONE`
+                    let condition = flag_condition_attr(&field.attrs)?;
+                    let present = flag_present(&condition, &quote! { #rapira::RapiraFlags::NONE });
+                    let present_ctx = flag_present(&condition, &quote! { __rapira_flags });
+                    let flatten = flatten_attr(&field.attrs)?;
+                    fields_from_slice.push(flatten_check(rapira, typ, flatten));
+                    let default = default_value(&field.attrs)?;
                     let field_name = field.ident.as_ref().unwrap();
-                    let with_attr = extract_with_attr(&field.attrs);
+                    let check =
+                        FieldCheck::new(rapira, field, format!("{variant_name}.{field_name}"))?;
+                    let len_check = check.len_check();
+                    let with_attr = field_with(rapira, field, attrs)?;
+                    let since = extract_since_attr(&field.attrs)?;
 
                     field_names.push(quote! { #field_name, });
+                    fields_from_slice_versioned.push(versioned_field(
+                        &field_codec(rapira, typ, with_attr.as_ref()),
+                        field_name,
+                        typ,
+                        since,
+                        present.as_ref(),
+                        &default,
+                        Some(&check),
+                    ));
+                    fields_from_slice_versioned.push(check.validate(field_name));
+                    let (field_size, field_write) = versioned_field_write(
+                        rapira,
+                        &quote! { #field_name },
+                        typ,
+                        with_attr.as_ref(),
+                        since,
+                        present.as_ref(),
+                    );
+                    fields_size_versioned.push(field_size);
+                    fields_convert_to_bytes_versioned.push(field_write);
 
-                    match with_attr {
-                        Some(with_attr) => {
-                            fields_static_sizes.push(quote! {
-                                #with_attr::static_size(core::marker::PhantomData::<#typ>),
-                            });
-                            fields_min_sizes.push(quote! {
-                                #with_attr::min_size(core::marker::PhantomData::<#typ>),
-                            });
-                            fields_size.push(
-                                quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                                    Some(s) => s,
-                                    None => #with_attr::size(#field_name)
-                                }) },
-                            );
-                            fields_check_bytes.push(quote! {
-                                #with_attr::check_bytes(core::marker::PhantomData::<#typ>, __rapira_slice)?;
-                            });
-                            fields_from_slice.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice(__rapira_slice)?;
-                            });
-                            fields_debug_from_slice.push(quote! {
-                                let len = __rapira_slice.len();
-                                println!("Field: {}, Type: {}", stringify!(#field_name), stringify!(#typ));
-                                let res = #with_attr::from_slice(__rapira_slice).inspect(|v| {
-                                    println!("len: {len}, {}: {v:?}", stringify!(#field_name));
-                                }).inspect_err(|err| {
-                                    println!("len: {len}, err: {err:?}");
-                                });
-                                let #field_name: #typ = res?;
-                            });
-                            fields_from_slice_unchecked.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice_unchecked(__rapira_slice)?;
-                            });
-                            fields_from_slice_unsafe.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice_unsafe(__rapira_slice)?;
-                            });
-                            fields_try_convert_to_bytes.push(quote! {
-                                #with_attr::try_convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor)?;
-                            });
-                            fields_convert_to_bytes.push(quote! {
-                                #with_attr::convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor);
-                            });
-                            fields_convert_to_bytes_ctx.push(quote! {
-                                #with_attr::convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor);
-                            });
-                            fields_from_slice_ctx.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice(__rapira_slice)?;
-                            });
-                            fields_size_ctx.push(quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                                Some(s) => s,
-                                None => #with_attr::size(#field_name)
-                            }) });
-                        }
-                        None => {
-                            fields_from_slice.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice(__rapira_slice)?;
-                            });
-                            fields_debug_from_slice.push(quote! {
+                    let codec = field_codec(rapira, typ, with_attr.as_ref());
+                    if present.is_some() {
+                        fields_static_sizes.push(quote! { None, });
+                        fields_min_sizes.push(quote! { 0, });
+                    } else {
+                        fields_static_sizes.push(quote! { #codec::STATIC_SIZE, });
+                        fields_min_sizes.push(quote! { #codec::MIN_SIZE, });
+                    }
+                    fields_size.push(size_if(
+                        present.as_ref(),
+                        quote! { (match #codec::STATIC_SIZE {
+                            Some(s) => s,
+                            None => #codec::size(#field_name)
+                        }) },
+                    ));
+                    fields_check_bytes.push(write_if(
+                        present.as_ref(),
+                        check.check_bytes(&codec, typ, false),
+                    ));
+                    fields_from_slice.push(read_if(
+                        present.as_ref(),
+                        field_name,
+                        typ,
+                        check.read_slice(&codec),
+                        &default,
+                    ));
+                    fields_from_slice.push(check.validate(field_name));
+                    fields_debug_from_slice.push(read_if(
+                        present.as_ref(),
+                        field_name,
+                        typ,
+                        debug_read(
+                            rapira,
+                            typ,
+                            flatten,
+                            quote! {{
+                                #len_check
                                 let len = __rapira_slice.len();
                                 println!("Field: {}, Type: {}", stringify!(#field_name), stringify!(#typ));
-                                let res = <#typ as rapira::Rapira>::from_slice(__rapira_slice).inspect(|v| {
+                                let res = #codec::from_slice(__rapira_slice).inspect(|v| {
                                     println!("len: {len}, {}: {v:?}", stringify!(#field_name));
                                 }).inspect_err(|err| {
                                     println!("len: {len}, err: {err:?}");
                                 });
-                                let #field_name = res?;
-                            });
-                            fields_check_bytes.push(quote! {
-                                <#typ as rapira::Rapira>::check_bytes(__rapira_slice)?;
-                            });
-                            fields_from_slice_unchecked.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice_unchecked(__rapira_slice)?;
-                            });
-                            fields_from_slice_unsafe.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice_unsafe(__rapira_slice)?;
-                            });
-                            fields_try_convert_to_bytes.push(quote! {
-                                #field_name.try_convert_to_bytes(__rapira_slice, __rapira_cursor)?;
-                            });
-                            fields_convert_to_bytes.push(quote! {
-                                #field_name.convert_to_bytes(__rapira_slice, __rapira_cursor);
-                            });
-                            fields_size.push(
-                                quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                                    Some(s) => s,
-                                    None => #field_name.size()
-                                }) },
-                            );
-                            fields_static_sizes.push(quote! {
-                                <#typ as rapira::Rapira>::STATIC_SIZE,
-                            });
-                            fields_min_sizes.push(quote! {
-                                <#typ as rapira::Rapira>::MIN_SIZE,
-                            });
-                            fields_convert_to_bytes_ctx.push(quote! {
-                                #field_name.convert_to_bytes_ctx(__rapira_slice, __rapira_cursor, __rapira_flags);
-                            });
-                            fields_from_slice_ctx.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
-                            });
-                            fields_size_ctx.push(
-                                quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                                    Some(s) => s,
-                                    None => #field_name.size_ctx(__rapira_flags)
-                                }) },
-                            );
-                        }
-                    }
+                                res?
+                            }},
+                        ),
+                        &default,
+                    ));
+                    fields_debug_from_slice.push(check.validate(field_name));
+                    fields_from_slice_unchecked.push(read_if(
+                        present.as_ref(),
+                        field_name,
+                        typ,
+                        quote! { #codec::from_slice_unchecked(__rapira_slice)? },
+                        &default,
+                    ));
+                    fields_from_slice_unsafe.push(read_if(
+                        present.as_ref(),
+                        field_name,
+                        typ,
+                        quote! { #codec::from_slice_unsafe(__rapira_slice)? },
+                        &default,
+                    ));
+                    fields_try_convert_to_bytes.push(write_if(
+                        present.as_ref(),
+                        quote! { #codec::try_convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor)?; },
+                    ));
+                    fields_convert_to_bytes.push(write_if(
+                        present.as_ref(),
+                        quote! { #codec::convert_to_bytes(#field_name, __rapira_slice, __rapira_cursor); },
+                    ));
+                    fields_convert_to_bytes_ctx.push(write_if(
+                        present_ctx.as_ref(),
+                        quote! { #codec::convert_to_bytes_ctx(#field_name, __rapira_slice, __rapira_cursor, __rapira_flags); },
+                    ));
+                    fields_from_slice_ctx.push(read_if(
+                        present_ctx.as_ref(),
+                        field_name,
+                        typ,
+                        check.read_ctx(&codec),
+                        &default,
+                    ));
+                    fields_from_slice_ctx.push(check.validate(field_name));
+                    fields_check_bytes_ctx.push(write_if(
+                        present_ctx.as_ref(),
+                        check.check_bytes(&codec, typ, true),
+                    ));
+                    fields_size_ctx.push(size_if(
+                        present_ctx.as_ref(),
+                        quote! { (match #codec::STATIC_SIZE {
+                            Some(s) => s,
+                            None => #codec::size_ctx(#field_name, __rapira_flags)
+                        }) },
+                    ));
                 }
 
                 size.push(quote! {
@@ -474,10 +628,10 @@ pub fn enum_serializer(
                     },
                 });
                 enum_sizes.push(quote! {
-                    rapira::static_size([#(#fields_static_sizes)*]),
+                    #rapira::static_size([#(#fields_static_sizes)*]),
                 });
                 min_sizes.push(quote! {
-                    rapira::min_size(&[#(#fields_min_sizes)*]),
+                    #rapira::min_size(&[#(#fields_min_sizes)*]),
                 });
                 from_slice.push(quote! {
                     #variant_id => {
@@ -485,6 +639,26 @@ pub fn enum_serializer(
                         Ok(#name::#variant_name{#(#field_names)*})
                     }
                 });
+                from_slice_versioned.push(quote! {
+                    #variant_id => {
+                        #version_check
+                        #(#fields_from_slice_versioned)*
+                        Ok(#name::#variant_name{#(#field_names)*})
+                    }
+                });
+                size_versioned.push(quote! {
+                    #name::#variant_name{#(#field_names)*} => {
+                        #version_check_write
+                        0 #(#fields_size_versioned)*
+                    }
+                });
+                convert_to_bytes_versioned.push(quote! {
+                    #name::#variant_name{#(#field_names)*} => {
+                        #version_check_write
+                        #write_tag
+                        #(#fields_convert_to_bytes_versioned)*
+                    }
+                });
                 debug_from_slice.push(quote! {
                     #variant_id => {
                         println!("Variant: {}::{}", stringify!(#name), stringify!(#variant_name));
@@ -513,21 +687,21 @@ pub fn enum_serializer(
 
                 try_convert_to_bytes.push(quote! {
                     #name::#variant_name{#(#field_names)*} => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                         #(#fields_try_convert_to_bytes)*
                     }
                 });
 
                 convert_to_bytes.push(quote! {
                     #name::#variant_name{#(#field_names)*} => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                         #(#fields_convert_to_bytes)*
                     }
                 });
 
                 convert_to_bytes_ctx.push(quote! {
                     #name::#variant_name{#(#field_names)*} => {
-                        rapira::push(__rapira_slice, __rapira_cursor, #variant_id);
+                        #write_tag
                         #(#fields_convert_to_bytes_ctx)*
                     }
                 });
@@ -542,19 +716,46 @@ pub fn enum_serializer(
                         0 #(#fields_size_ctx)*
                     },
                 });
+                check_bytes_ctx.push(quote! {
+                    #variant_id => {
+                        #(#fields_check_bytes_ctx)*
+                    }
+                });
             }
         }
     }
 
+    // `extensible`: the tag is followed by the payload length
+    let prefix_size = if extensible {
+        quote! { #tag_size + #rapira::LEN_SIZE }
+    } else {
+        quote! { #tag_size }
+    };
+    if let Some(unknown) = &unknown {
+        enum_sizes.push(quote! { None, });
+        min_sizes.push(quote! { 0, });
+        let pattern = unknown.pattern(name, false);
+        for method in [&mut size, &mut size_versioned, &mut size_ctx] {
+            method.push(quote! {
+                #pattern => AsRef::<[u8]>::as_ref(__rapira_raw).len(),
+            });
+        }
+    }
+
     let static_size = match static_size {
         Some(static_size) => {
             quote! {
                 #static_size
             }
         }
+        None if fixed => {
+            quote! {
+                #rapira::fixed::enum_size([#(#enum_sizes)*], #tag_size)
+            }
+        }
         None => {
             quote! {
-                rapira::enum_size([#(#enum_sizes)*])
+                #rapira::enum_size_tagged([#(#enum_sizes)*], #prefix_size)
             }
         }
     };
@@ -566,12 +767,159 @@ pub fn enum_serializer(
         }
         None => {
             quote! {
-                rapira::enum_min_size(&[#(#min_sizes)*])
+                #rapira::enum_min_size_tagged(&[#(#min_sizes)*], #prefix_size)
+            }
+        }
+    };
+
+    let fields = known.iter().flat_map(|variant| variant.fields.iter());
+    let bounds = impl_bounds(rapira, name, &generics, attrs, fields)?;
+    let name_with_generics = build_ident(rapira, name, generics, bounds);
+
+    // `extensible`: variants are decoded from their payload, unknown tags are
+    // the `unknown` variant or an error
+    let read_payload = if extensible {
+        quote! {
+            let mut __rapira_payload = #rapira::extensible::payload(__rapira_slice, #big)?;
+            let __rapira_slice = &mut __rapira_payload;
+        }
+    } else {
+        quote!()
+    };
+    let (read_unknown, check_unknown) = match &unknown {
+        Some(unknown) => (unknown.read(name), quote! { _ => {} }),
+        None => (
+            quote! { _ => Err(#rapira::RapiraError::EnumVariant), },
+            quote! { _ => return Err(#rapira::RapiraError::EnumVariant), },
+        ),
+    };
+    let read_match = |arms: &[TokenStream], read_tag: &TokenStream| {
+        quote! {
+            let val: #tag = #read_tag;
+            #read_payload
+            match val {
+                #(#arms)*
+                #read_unknown
+            }
+        }
+    };
+    let check_match = |arms: &[TokenStream]| {
+        quote! {
+            let val: #tag = #read_tag;
+            #read_payload
+            match val {
+                #(#arms)*
+                #check_unknown
+            }
+            Ok(())
+        }
+    };
+    let write_match = |arms: &[TokenStream], try_write: bool| {
+        if !extensible {
+            return quote! {
+                match self {
+                    #(#arms)*
+                }
+            };
+        }
+        let (extend, end) = if try_write {
+            (
+                quote! { #rapira::try_extend(__rapira_slice, __rapira_cursor, __rapira_raw)?; },
+                quote! { #rapira::extensible::try_end(__rapira_slice, __rapira_cursor, __rapira_payload, #big)?; },
+            )
+        } else {
+            (
+                quote! { #rapira::extend(__rapira_slice, __rapira_cursor, __rapira_raw); },
+                quote! { #rapira::extensible::end(__rapira_slice, __rapira_cursor, __rapira_payload, #big); },
+            )
+        };
+        // the raw payload of an unknown variant is written back as is
+        let unknown_arm = unknown.as_ref().map(|unknown| {
+            let pattern = unknown.pattern(name, true);
+            let write_tag = tag_write(rapira, &tag, &quote! { __rapira_tag }, big);
+            // the tag field may be wider than the tag
+            let tag_value = if try_write {
+                quote! { <#tag>::try_from(*__rapira_tag).map_err(|_| #rapira::RapiraError::EnumVariant)? }
+            } else {
+                quote! { <#tag>::try_from(*__rapira_tag).expect("the tag of the unknown variant doesn't fit the enum tag type") }
+            };
+            quote! {
+                #pattern => {
+                    let __rapira_tag: #tag = #tag_value;
+                    #write_tag
+                    #payload_begin
+                    let __rapira_raw: &[u8] = AsRef::<[u8]>::as_ref(__rapira_raw);
+                    #extend
+                }
             }
+        });
+        quote! {
+            let __rapira_payload: usize;
+            match self {
+                #(#arms)*
+                #unknown_arm
+            }
+            #end
         }
     };
 
-    let name_with_generics = build_ident(name, generics);
+    let padding = Padding { rapira, fixed };
+    let from_slice_body = validated(
+        rapira,
+        validate.as_ref(),
+        padding.read(read_match(&from_slice, &read_tag)),
+    );
+    let from_slice_versioned_body = padding.read(read_match(&from_slice_versioned, &read_tag));
+    let from_slice_versioned_body = validated(
+        rapira,
+        validate.as_ref(),
+        quote! {
+            #migrations_read
+            #from_slice_versioned_body
+        },
+    );
+    let debug_from_slice_body = validated(
+        rapira,
+        validate.as_ref(),
+        padding.read(read_match(&debug_from_slice, &read_tag)),
+    );
+    let from_slice_ctx_body = validated(
+        rapira,
+        validate.as_ref(),
+        padding.read(read_match(&from_slice_ctx, &read_tag)),
+    );
+    let check_bytes_body = if validate.is_some() {
+        quote! { Self::from_slice(__rapira_slice).map(|_| ()) }
+    } else {
+        padding.check(check_match(&check_bytes))
+    };
+    let check_bytes_ctx_body = if validate.is_some() {
+        quote! { Self::from_slice_ctx(__rapira_slice, __rapira_flags).map(|_| ()) }
+    } else {
+        padding.check(check_match(&check_bytes_ctx))
+    };
+    let from_slice_unchecked_body = padding.read(read_match(&from_slice_unchecked, &read_tag));
+    let from_slice_unsafe_body = padding.read(read_match(&from_slice_unsafe, &read_tag_unsafe));
+    let convert_to_bytes_body = padding.write(write_match(&convert_to_bytes, false));
+    let convert_to_bytes_versioned_body =
+        padding.write(write_match(&convert_to_bytes_versioned, false));
+    let convert_to_bytes_ctx_body = padding.write(write_match(&convert_to_bytes_ctx, false));
+    let try_convert_to_bytes_body = padding.try_write(write_match(&try_convert_to_bytes, true));
+    let size_body = padding.size(quote! {
+        #prefix_size + match self {
+            #(#size)*
+        }
+    });
+    let size_versioned_body = padding.size(quote! {
+        #prefix_size + match self {
+            #(#size_versioned)*
+        }
+    });
+    let size_ctx_body = padding.size(quote! {
+        #prefix_size + match self {
+            #(#size_ctx)*
+        }
+    });
 
     let debug_parse = if is_debug {
         quote! {
@@ -579,126 +927,318 @@ pub fn enum_serializer(
             /// This method logs the enum variant name, field names, types, and values during deserialization.
             /// Useful for debugging serialization/deserialization issues.
             #[inline]
-            fn debug_from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            fn debug_from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized + std::fmt::Debug,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                match val {
-                    #(#debug_from_slice)*
-                    _ => Err(rapira::RapiraError::EnumVariant),
-                }
+                #debug_from_slice_body
             }
         }
     } else {
         quote!()
     };
 
+    let max_cap = max_cap_const(attrs)?;
     let res = quote! {
         #name_with_generics {
             const STATIC_SIZE: Option<usize> = #static_size;
             const MIN_SIZE: usize = #min_size;
+            #max_cap
 
             #[inline]
-            fn from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                match val {
-                    #(#from_slice)*
-                    _ => Err(rapira::RapiraError::EnumVariant),
-                }
+                #from_slice_body
+            }
+
+            #[inline]
+            fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u16) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                #from_slice_versioned_body
+            }
+
+            #[inline]
+            fn convert_to_bytes_versioned(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_version: u16) {
+                #migrations_write
+                #convert_to_bytes_versioned_body
+            }
+
+            #[inline]
+            fn size_versioned(&self, __rapira_version: u16) -> usize {
+                #migrations_size
+                #size_versioned_body
             }
 
             #debug_parse
 
             #[inline]
-            fn check_bytes(__rapira_slice: &mut &[u8]) -> rapira::Result<()>
+            fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                match val {
-                    #(#check_bytes)*
-                    _ => return Err(rapira::RapiraError::EnumVariant),
-                }
-                Ok(())
+                #check_bytes_body
             }
 
             #[inline]
-            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                match val {
-                    #(#from_slice_unchecked)*
-                    _ => Err(rapira::RapiraError::EnumVariant),
-                }
+                #from_slice_unchecked_body
             }
 
             #[inline]
-            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice_unsafe(__rapira_slice)?;
-                match val {
-                    #(#from_slice_unsafe)*
-                    _ => Err(rapira::RapiraError::EnumVariant),
-                }
+                #from_slice_unsafe_body
             }
 
             #[inline]
-            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> rapira::Result<()> {
-                match self {
-                    #(#try_convert_to_bytes)*
-                }
+            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
+                #try_convert_to_bytes_body
                 Ok(())
             }
 
             #[inline]
             fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {
-                match self {
-                    #(#convert_to_bytes)*
-                }
+                #convert_to_bytes_body
             }
 
             #[inline]
             fn size(&self) -> usize {
-                1 + match self {
-                    #(#size)*
-                }
+                #size_body
             }
 
             #[inline]
-            fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: rapira::RapiraFlags) {
-                match self {
-                    #(#convert_to_bytes_ctx)*
-                }
+            fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: #rapira::RapiraFlags) {
+                #convert_to_bytes_ctx_body
             }
 
             #[inline]
-            fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: rapira::RapiraFlags) -> rapira::Result<Self>
+            fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                match val {
-                    #(#from_slice_ctx)*
-                    _ => Err(rapira::RapiraError::EnumVariant),
-                }
+                #from_slice_ctx_body
             }
 
             #[inline]
-            fn size_ctx(&self, __rapira_flags: rapira::RapiraFlags) -> usize {
-                1 + match self {
-                    #(#size_ctx)*
-                }
+            fn size_ctx(&self, __rapira_flags: #rapira::RapiraFlags) -> usize {
+                #size_ctx_body
+            }
+
+            #[inline]
+            fn check_bytes_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<()>
+            where
+                Self: Sized,
+            {
+                #check_bytes_ctx_body
             }
         }
     };
 
-    proc_macro::TokenStream::from(res)
+    Ok(res)
+}
+
+/// `#[rapira(fixed)]`: every variant is zero-padded to `STATIC_SIZE`,
+/// method bodies are unchanged without it
+struct Padding<'a> {
+    rapira: &'a Path,
+    fixed: bool,
+}
+
+impl Padding<'_> {
+    fn fixed_size(&self) -> TokenStream {
+        let rapira = self.rapira;
+        quote! { const { #rapira::fixed::size(Self::STATIC_SIZE) } }
+    }
+
+    /// decoding `body` (returning `Result<Self>`) followed by the padding of the variant
+    fn read(&self, body: TokenStream) -> TokenStream {
+        self.read_as(body, quote! { Self })
+    }
+
+    /// `check_bytes` `body` (returning `Result<()>`) followed by the padding of the variant
+    fn check(&self, body: TokenStream) -> TokenStream {
+        self.read_as(body, quote! { () })
+    }
+
+    fn read_as(&self, body: TokenStream, value: TokenStream) -> TokenStream {
+        if !self.fixed {
+            return body;
+        }
+        let rapira = self.rapira;
+        let fixed_size = self.fixed_size();
+        quote! {
+            let __rapira_len = __rapira_slice.len();
+            let __rapira_value: #rapira::Result<#value> = { #body };
+            let __rapira_value = __rapira_value?;
+            let __rapira_read = __rapira_len - __rapira_slice.len();
+            #rapira::fixed::read_padding(__rapira_slice, #fixed_size.saturating_sub(__rapira_read))?;
+            Ok(__rapira_value)
+        }
+    }
+
+    /// encoding `body` followed by the padding of the variant
+    fn write(&self, body: TokenStream) -> TokenStream {
+        if !self.fixed {
+            return body;
+        }
+        let rapira = self.rapira;
+        let fixed_size = self.fixed_size();
+        quote! {
+            let __rapira_end = *__rapira_cursor + #fixed_size;
+            #body
+            #rapira::fixed::write_padding(__rapira_slice, __rapira_cursor, __rapira_end - *__rapira_cursor);
+        }
+    }
+
+    fn try_write(&self, body: TokenStream) -> TokenStream {
+        if !self.fixed {
+            return body;
+        }
+        let rapira = self.rapira;
+        let fixed_size = self.fixed_size();
+        quote! {
+            let __rapira_end = *__rapira_cursor + #fixed_size;
+            #body
+            #rapira::fixed::try_write_padding(__rapira_slice, __rapira_cursor, __rapira_end - *__rapira_cursor)?;
+        }
+    }
+
+    fn size(&self, body: TokenStream) -> TokenStream {
+        if self.fixed { self.fixed_size() } else { body }
+    }
+}
+
+/// `#[rapira(unknown)]` variant of an `extensible` enum: the tag and the raw payload
+/// (`From<&[u8]> + AsRef<[u8]>`, e.g. `Vec<u8>`) of variants this build doesn't know
+struct UnknownVariant<'a> {
+    variant: &'a Ident,
+    tag: Member,
+    raw: Member,
+    named: bool,
+}
+
+impl UnknownVariant<'_> {
+    /// pattern binding `__rapira_raw` and, with `tag`, `__rapira_tag`
+    fn pattern(&self, name: &Ident, tag: bool) -> TokenStream {
+        let Self {
+            variant,
+            tag: tag_member,
+            raw,
+            ..
+        } = self;
+        let tag = if tag {
+            quote! { __rapira_tag }
+        } else {
+            quote! { _ }
+        };
+        if self.named {
+            quote! { #name::#variant { #tag_member: #tag, #raw: __rapira_raw } }
+        } else {
+            quote! { #name::#variant(#tag, __rapira_raw) }
+        }
+    }
+
+    /// match arm building the variant from the tag `val` and the rest of the payload
+    fn read(&self, name: &Ident) -> TokenStream {
+        let Self {
+            variant, tag, raw, ..
+        } = self;
+        quote! {
+            _ => Ok(#name::#variant {
+                #tag: From::from(val),
+                #raw: From::from(core::mem::take(__rapira_slice)),
+            }),
+        }
+    }
+}
+
+/// the `#[rapira(unknown)]` variant: last, in an `extensible` enum, with the tag field
+/// (unsigned, at least as wide as the enum tag type) and the raw payload field
+fn unknown_variant<'a>(
+    data_enum: &'a DataEnum,
+    name: &Ident,
+    tag: &Ident,
+    extensible: bool,
+) -> syn::Result<Option<UnknownVariant<'a>>> {
+    let last = data_enum.variants.len().saturating_sub(1);
+    let mut unknown = None;
+    for (pos, variant) in data_enum.variants.iter().enumerate() {
+        if !unknown_attr(&variant.attrs)? {
+            continue;
+        }
+        let variant_name = &variant.ident;
+        let error = |message: &str| {
+            Err(syn::Error::new_spanned(
+                variant_name,
+                format!("variant `{name}::{variant_name}`: {message}"),
+            ))
+        };
+        if !extensible {
+            return error("#[rapira(unknown)] needs #[rapira(extensible)] on the enum");
+        }
+        if pos != last {
+            return error("#[rapira(unknown)] must be the last variant");
+        }
+        if extract_idx_attr(&variant.attrs)?.is_some()
+            || extract_since_attr(&variant.attrs)?.is_some()
+        {
+            return error(
+                "#[rapira(unknown)] has no tag of its own, #[idx] and #[rapira(since)] are not allowed",
+            );
+        }
+        let fields: Vec<&Field> = variant.fields.iter().collect();
+        let [tag_field, raw_field] = fields[..] else {
+            return error(
+                "#[rapira(unknown)] must have two fields: the tag and the raw payload, `Unknown { tag: u32, raw: Vec<u8> }`",
+            );
+        };
+        if tag_field
+            .attrs
+            .iter()
+            .chain(&raw_field.attrs)
+            .any(|attr| attr.path().is_ident("rapira"))
+        {
+            return error(
+                "fields of the #[rapira(unknown)] variant take no #[rapira(..)] attributes",
+            );
+        }
+        // unsigned and at least as wide as the tag, so a wider tag later keeps it valid
+        const WIDTHS: &[&str] = &["u8", "u16", "u32", "u64", "u128"];
+        let tag_width = WIDTHS.iter().position(|width| tag == width);
+        let field_width = match &tag_field.ty {
+            Type::Path(typ) if typ.qself.is_none() => typ
+                .path
+                .get_ident()
+                .and_then(|ident| WIDTHS.iter().position(|width| ident == width)),
+            _ => None,
+        };
+        if field_width < tag_width {
+            return Err(syn::Error::new_spanned(
+                &tag_field.ty,
+                format!(
+                    "variant `{name}::{variant_name}`: the tag field must be an unsigned integer at least as wide as `{tag}`, the tag type of the enum (#[rapira(tag = ..)])"
+                ),
+            ));
+        }
+        let member = |idx: usize, field: &Field| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(idx.into()),
+        };
+        unknown = Some(UnknownVariant {
+            variant: variant_name,
+            tag: member(0, tag_field),
+            raw: member(1, raw_field),
+            named: matches!(variant.fields, Fields::Named(_)),
+        });
+    }
+    Ok(unknown)
 }
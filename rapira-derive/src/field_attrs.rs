@@ -1,86 +1,499 @@
 use quote::ToTokens;
-use syn::{Attribute, Expr, ExprLit, ExprPath, Lit, MetaNameValue, Path};
+use syn::{Attribute, Expr, ExprPath, Ident};
 
-/// `#[idx = 1]` in fields
-pub fn extract_idx_attr(attrs: &[Attribute]) -> Option<u32> {
-    attrs.iter().find_map(|attr| {
-        if !attr.path().is_ident("idx") {
-            return None;
-        }
+use crate::{
+    attributes::{
+        bound_attr, check_keys, endian_attr, fixed_attr, flag, int_value, name_value, validate_attr,
+    },
+    shared::Errors,
+};
 
-        let nv = attr.meta.require_name_value().unwrap();
+/// `#[rapira(..)]` keys of struct fields
+pub const FIELD_KEYS: &[&str] = &[
+    "with",
+    "skip",
+    "since",
+    "bound",
+    "omit_if",
+    "only_if",
+    "flatten",
+    "default",
+    "default_with",
+    "validate",
+    "max_len",
+    "non_empty",
+    "range",
+    "bits",
+    "fixed",
+    "pad",
+    "align",
+    "count",
+    "if",
+    "endian",
+    "rest",
+];
+/// `#[rapira(..)]` keys of tuple struct fields
+pub const UNNAMED_FIELD_KEYS: &[&str] = &[
+    "with",
+    "since",
+    "bound",
+    "omit_if",
+    "only_if",
+    "flatten",
+    "default",
+    "default_with",
+    "validate",
+    "max_len",
+    "non_empty",
+    "range",
+    "bits",
+    "fixed",
+    "pad",
+    "align",
+    "endian",
+];
+/// `#[rapira(..)]` keys of `#[primitive(..)]` enum variants
+pub const VARIANT_KEYS: &[&str] = &["since"];
+/// `#[rapira(..)]` keys of enum with data variants
+pub const DATA_VARIANT_KEYS: &[&str] = &["since", "unknown"];
+/// `#[rapira(..)]` keys of enum variant fields
+pub const VARIANT_FIELD_KEYS: &[&str] = &[
+    "with",
+    "since",
+    "bound",
+    "omit_if",
+    "only_if",
+    "flatten",
+    "default",
+    "default_with",
+    "validate",
+    "max_len",
+    "non_empty",
+    "range",
+    "fixed",
+    "endian",
+];
+/// `#[rapira(..)]` keys of `#[primitive(..)]` enum variant fields
+pub const PRIMITIVE_VARIANT_FIELD_KEYS: &[&str] = &["since", "default", "default_with"];
+/// `#[rapira(..)]` keys of simple enum variants
+pub const SIMPLE_VARIANT_KEYS: &[&str] = &["other"];
 
-        match &nv.value {
-            Expr::Lit(ExprLit {
-                lit: Lit::Int(i), ..
-            }) => Some(i.base10_parse::<u32>().unwrap()),
-            _ => {
-                panic!("error meta type");
-            }
-        }
-    })
+/// `#[idx = 1]` in fields
+pub fn extract_idx_attr(attrs: &[Attribute]) -> syn::Result<Option<u32>> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("idx")) else {
+        return Ok(None);
+    };
+    let nv = attr.meta.require_name_value()?;
+    int_value(&nv.value, "idx").map(Some)
 }
 
 /// `#[rapira(with = rapira::byte_rapira)]` in fields
-pub fn extract_with_attr(attrs: &[Attribute]) -> Option<ExprPath> {
-    attrs.iter().find_map(|attr| {
-        if !attr.path().is_ident("rapira") {
-            return None;
+pub fn extract_with_attr(attrs: &[Attribute]) -> syn::Result<Option<ExprPath>> {
+    match name_value(attrs, "with")? {
+        Some(Expr::Path(path)) => Ok(Some(path)),
+        Some(value) => Err(syn::Error::new_spanned(
+            &value,
+            format!("invalid 'with' path value: `{}`", value.to_token_stream()),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// `#[rapira(since = 2)]` in fields
+pub fn extract_since_attr(attrs: &[Attribute]) -> syn::Result<Option<u16>> {
+    Ok(since_value(attrs)?.map(|(since, _)| since))
+}
+
+fn since_value(attrs: &[Attribute]) -> syn::Result<Option<(u16, Expr)>> {
+    let Some(value) = name_value(attrs, "since")? else {
+        return Ok(None);
+    };
+    Ok(Some((int_value(&value, "since")?, value)))
+}
+
+/// `#[rapira(skip)]` in fields
+pub fn skip_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "skip")
+}
+
+/// `#[rapira(flatten)]` in fields
+pub fn flatten_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "flatten")
+}
+
+/// `#[rapira(bits = 3)]` in struct fields: width in a packed bitfield, `1..=64`
+pub fn bits_attr(attrs: &[Attribute]) -> syn::Result<Option<u32>> {
+    let Some(value) = name_value(attrs, "bits")? else {
+        return Ok(None);
+    };
+    match int_value(&value, "bits")? {
+        bits @ 1..=64 => Ok(Some(bits)),
+        bits => Err(syn::Error::new_spanned(
+            value,
+            format!("#[rapira(bits = {bits})] is invalid, width must be in 1..=64"),
+        )),
+    }
+}
+
+/// `#[rapira(pad = N)]` in struct fields: `N` zero bytes before the field
+pub fn pad_attr(attrs: &[Attribute]) -> syn::Result<Option<usize>> {
+    name_value(attrs, "pad")?
+        .map(|value| int_value(&value, "pad"))
+        .transpose()
+}
+
+/// `#[rapira(align = N)]` in struct fields: zero bytes up to an offset
+/// (from the start of the struct) multiple of `N`, a power of two
+pub fn align_attr(attrs: &[Attribute]) -> syn::Result<Option<usize>> {
+    let Some(value) = name_value(attrs, "align")? else {
+        return Ok(None);
+    };
+    match int_value(&value, "align")? {
+        align if usize::is_power_of_two(align) => Ok(Some(align)),
+        align => Err(syn::Error::new_spanned(
+            value,
+            format!("#[rapira(align = {align})] is invalid, alignment must be a power of two"),
+        )),
+    }
+}
+
+/// `#[rapira(count = field)]` in struct fields: `Vec` without a length prefix,
+/// its length is the value of an earlier `field`
+pub fn count_attr(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
+    match name_value(attrs, "count")? {
+        Some(Expr::Path(path)) if path.path.get_ident().is_some() => {
+            Ok(path.path.get_ident().cloned())
         }
+        Some(value) => Err(syn::Error::new_spanned(
+            &value,
+            "count value must be a field name: `count = len`",
+        )),
+        None => Ok(None),
+    }
+}
+
+/// `#[rapira(if = expr)]` in struct fields: the field is present when `expr` is true,
+/// earlier fields are in scope as references: `#[rapira(if = *kind == 2)]`
+pub fn if_attr(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+    name_value(attrs, "if")
+}
 
-        let Ok(nv) = attr.parse_args::<MetaNameValue>() else {
-            return None;
-        };
+/// `#[rapira(omit_if = FLAG)]` and `#[rapira(only_if = FLAG)]` in fields
+#[derive(Default)]
+pub struct FlagCondition {
+    pub omit_if: Option<Expr>,
+    pub only_if: Option<Expr>,
+}
 
-        if !nv.path.is_ident("with") {
-            return None;
+impl FlagCondition {
+    pub fn is_empty(&self) -> bool {
+        self.omit_if.is_none() && self.only_if.is_none()
+    }
+}
+
+pub fn flag_condition_attr(attrs: &[Attribute]) -> syn::Result<FlagCondition> {
+    Ok(FlagCondition {
+        omit_if: name_value(attrs, "omit_if")?,
+        only_if: name_value(attrs, "only_if")?,
+    })
+}
+
+/// `#[rapira(default = expr)]` or `#[rapira(default_with = path)]` in fields:
+/// value of a skipped, not yet added (`since`) or omitted (`omit_if`/`only_if`/`if`) field
+pub fn default_attr(attrs: &[Attribute]) -> syn::Result<Option<Expr>> {
+    let default = name_value(attrs, "default")?;
+    let default_with = match name_value(attrs, "default_with")? {
+        Some(Expr::Path(path)) => Some(path),
+        Some(value) => {
+            return Err(syn::Error::new_spanned(
+                &value,
+                format!(
+                    "invalid 'default_with' path value: `{}`",
+                    value.to_token_stream()
+                ),
+            ));
         }
+        None => None,
+    };
+    match (default, default_with) {
+        (Some(_), Some(path)) => Err(syn::Error::new_spanned(
+            path,
+            "#[rapira(default)] and #[rapira(default_with)] cannot be combined",
+        )),
+        (Some(expr), None) => Ok(Some(expr)),
+        (None, Some(path)) => Ok(Some(syn::parse_quote! { #path() })),
+        (None, None) => Ok(None),
+    }
+}
+
+/// `#[rapira(max_len = N)]`, `#[rapira(non_empty)]` and `#[rapira(range = a..=b)]` in fields
+#[derive(Default)]
+pub struct Constraints {
+    pub max_len: Option<Expr>,
+    pub non_empty: bool,
+    pub range: Option<Expr>,
+}
 
-        let Expr::Path(path) = nv.value else {
-            panic!(
-                "invalid 'with' path value: `{}`",
-                nv.value.into_token_stream()
-            );
-        };
+impl Constraints {
+    /// checked against the length prefix before decoding
+    pub fn has_len(&self) -> bool {
+        self.max_len.is_some() || self.non_empty
+    }
+}
 
-        Some(path)
+pub fn constraints_attr(attrs: &[Attribute]) -> syn::Result<Constraints> {
+    Ok(Constraints {
+        max_len: name_value(attrs, "max_len")?,
+        non_empty: flag(attrs, "non_empty")?,
+        range: name_value(attrs, "range")?,
     })
 }
 
-/// `#[rapira(since = 2)]` in fields
-pub fn extract_since_attr(attrs: &[Attribute]) -> Option<u8> {
-    attrs.iter().find_map(|attr| {
-        if !attr.path().is_ident("rapira") {
-            return None;
+/// `#[rapira(other)]` in simple enum variants
+pub fn other_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "other")
+}
+
+/// `#[rapira(rest)]` in fields of `#[rapira(extensible)]` structs
+pub fn rest_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "rest")
+}
+
+/// `#[rapira(unknown)]` in variants of `#[rapira(extensible)]` enums
+pub fn unknown_attr(attrs: &[Attribute]) -> syn::Result<bool> {
+    flag(attrs, "unknown")
+}
+
+/// Check attributes of a field or variant (`desc`): unknown keys, malformed values
+/// and `#[rapira(since = N)]` against `#[rapira(version = N)]` of the container
+pub fn check_field(
+    attrs: &[Attribute],
+    keys: &[&str],
+    desc: &str,
+    version: Option<u16>,
+    container: &str,
+) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    if errors.check(check_keys(attrs, keys, desc)).is_none() {
+        return errors.finish();
+    }
+    errors.check(extract_idx_attr(attrs));
+    let with_attr = errors.check(extract_with_attr(attrs)).flatten();
+    let skip = errors.check(skip_attr(attrs)).unwrap_or_default();
+    let flatten = errors.check(flatten_attr(attrs)).unwrap_or_default();
+    errors.check(other_attr(attrs));
+    errors.check(bound_attr(attrs));
+    if let Some(Some(validate)) = errors.check(validate_attr(attrs))
+        && skip
+    {
+        errors.push(syn::Error::new_spanned(
+            validate,
+            format!("{desc}: #[rapira(validate)] and #[rapira(skip)] cannot be combined"),
+        ));
+    }
+
+    let condition = errors.check(flag_condition_attr(attrs));
+    if let Some(condition) = &condition
+        && skip
+        && let Some(value) = condition.omit_if.as_ref().or(condition.only_if.as_ref())
+    {
+        errors.push(syn::Error::new_spanned(
+            value,
+            format!("{desc}: #[rapira(omit_if/only_if)] and #[rapira(skip)] cannot be combined"),
+        ));
+    }
+
+    if let Some(Some(default)) = errors.check(default_attr(attrs))
+        && !skip
+        && condition.as_ref().is_some_and(FlagCondition::is_empty)
+        && matches!(since_value(attrs), Ok(None))
+        && matches!(if_attr(attrs), Ok(None))
+    {
+        errors.push(syn::Error::new_spanned(
+            default,
+            format!(
+                "{desc}: #[rapira(default)] needs #[rapira(skip)], #[rapira(since)], #[rapira(omit_if)], #[rapira(only_if)] or #[rapira(if)]"
+            ),
+        ));
+    }
+
+    let constraints = errors.check(constraints_attr(attrs));
+    if let Some(constraints) = &constraints {
+        let len_attr = attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("rapira"))
+            .filter(|_| constraints.has_len());
+        if let Some(attr) = len_attr {
+            for (conflict, key) in [
+                (with_attr.is_some(), "with"),
+                (flatten, "flatten"),
+                (skip, "skip"),
+            ] {
+                if conflict {
+                    errors.push(syn::Error::new_spanned(
+                        attr,
+                        format!(
+                            "{desc}: #[rapira(max_len/non_empty)] and #[rapira({key})] cannot be combined"
+                        ),
+                    ));
+                }
+            }
+        }
+        if let Some(range) = &constraints.range {
+            for (conflict, key) in [(flatten, "flatten"), (skip, "skip")] {
+                if conflict {
+                    errors.push(syn::Error::new_spanned(
+                        range,
+                        format!("{desc}: #[rapira(range)] and #[rapira({key})] cannot be combined"),
+                    ));
+                }
+            }
         }
+    }
 
-        let Ok(nv) = attr.parse_args::<MetaNameValue>() else {
-            return None;
-        };
+    if flatten {
+        if let Some(with_attr) = &with_attr {
+            errors.push(syn::Error::new_spanned(
+                with_attr,
+                format!("{desc}: #[rapira(flatten)] and #[rapira(with)] cannot be combined"),
+            ));
+        }
+        if skip && let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("rapira")) {
+            errors.push(syn::Error::new_spanned(
+                attr,
+                format!("{desc}: #[rapira(flatten)] and #[rapira(skip)] cannot be combined"),
+            ));
+        }
+    }
 
-        if !nv.path.is_ident("since") {
-            return None;
+    let bits = errors.check(bits_attr(attrs)).flatten().is_some();
+    if let Some(true) = errors.check(fixed_attr(attrs))
+        && let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("rapira"))
+    {
+        for (conflict, key) in [
+            (with_attr.is_some(), "with"),
+            (skip, "skip"),
+            (flatten, "flatten"),
+            (bits, "bits"),
+        ] {
+            if conflict {
+                errors.push(syn::Error::new_spanned(
+                    attr,
+                    format!("{desc}: #[rapira(fixed)] and #[rapira({key})] cannot be combined"),
+                ));
+            }
         }
+    }
 
-        match &nv.value {
-            Expr::Lit(ExprLit {
-                lit: Lit::Int(i), ..
-            }) => Some(i.base10_parse::<u8>().expect("since value must be u8")),
-            _ => panic!("since value must be an integer literal"),
+    if bits && let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("rapira")) {
+        let has_len = constraints.as_ref().is_some_and(Constraints::has_len);
+        let conditional = condition
+            .as_ref()
+            .is_some_and(|condition| !condition.is_empty());
+        let since = matches!(since_value(attrs), Ok(Some(_)));
+        for (conflict, key) in [
+            (with_attr.is_some(), "with"),
+            (skip, "skip"),
+            (flatten, "flatten"),
+            (since, "since"),
+            (conditional, "omit_if/only_if"),
+            (has_len, "max_len/non_empty"),
+        ] {
+            if conflict {
+                errors.push(syn::Error::new_spanned(
+                    attr,
+                    format!("{desc}: #[rapira(bits)] and #[rapira({key})] cannot be combined"),
+                ));
+            }
         }
-    })
-}
+    }
 
-/// `#[rapira(skip)]` in fields
-pub fn skip_attr(attrs: &[Attribute]) -> bool {
-    attrs.iter().any(|attr| {
-        if !attr.path().is_ident("rapira") {
-            return false;
+    let pad = errors.check(pad_attr(attrs)).flatten().is_some();
+    let align = errors.check(align_attr(attrs)).flatten().is_some();
+    let count = errors.check(count_attr(attrs)).flatten().is_some();
+    let cond = errors.check(if_attr(attrs)).flatten().is_some();
+    let since = matches!(since_value(attrs), Ok(Some(_)));
+    let has_len = constraints.as_ref().is_some_and(Constraints::has_len);
+    let fixed = matches!(fixed_attr(attrs), Ok(true));
+    let endian = errors.check(endian_attr(attrs)).flatten().is_some();
+    if let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("rapira")) {
+        let conflicts = [
+            (
+                pad,
+                "pad",
+                &[
+                    (align, "align"),
+                    (skip, "skip"),
+                    (bits, "bits"),
+                    (since, "since"),
+                ][..],
+            ),
+            (
+                align,
+                "align",
+                &[(skip, "skip"), (bits, "bits"), (since, "since")][..],
+            ),
+            (
+                count,
+                "count",
+                &[
+                    (with_attr.is_some(), "with"),
+                    (skip, "skip"),
+                    (flatten, "flatten"),
+                    (bits, "bits"),
+                    (fixed, "fixed"),
+                    (since, "since"),
+                    (has_len, "max_len/non_empty"),
+                ][..],
+            ),
+            (cond, "if", &[(skip, "skip"), (bits, "bits")][..]),
+            (
+                endian,
+                "endian",
+                &[
+                    (with_attr.is_some(), "with"),
+                    (skip, "skip"),
+                    (flatten, "flatten"),
+                    (bits, "bits"),
+                    (fixed, "fixed"),
+                ][..],
+            ),
+        ];
+        for (set, key, others) in conflicts {
+            for (_, other) in others.iter().filter(|(conflict, _)| set && *conflict) {
+                errors.push(syn::Error::new_spanned(
+                    attr,
+                    format!("{desc}: #[rapira({key})] and #[rapira({other})] cannot be combined"),
+                ));
+            }
         }
+    }
 
-        match attr.parse_args::<Path>() {
-            Ok(path) => path.is_ident("skip"),
-            _ => false,
+    if let Some(Some((since, value))) = errors.check(since_value(attrs)) {
+        match version {
+            None => errors.push(syn::Error::new_spanned(
+                &value,
+                format!(
+                    "{desc} has #[rapira(since = {since})] but {container} is missing #[rapira(version = N)]"
+                ),
+            )),
+            Some(_) if since == 0 => errors.push(syn::Error::new_spanned(
+                &value,
+                format!("{desc}: #[rapira(since = 0)] is invalid, versions start at 1"),
+            )),
+            Some(version) if since > version => errors.push(syn::Error::new_spanned(
+                &value,
+                format!("{desc}: #[rapira(since = {since})] exceeds {container} version {version}"),
+            )),
+            _ => {}
         }
-    })
+        if skip {
+            errors.push(syn::Error::new_spanned(
+                &value,
+                format!("{desc}: #[rapira(since)] and #[rapira(skip)] cannot be combined"),
+            ));
+        }
+    }
+
+    errors.finish()
 }
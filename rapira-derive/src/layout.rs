@@ -0,0 +1,260 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{Expr, LitByteStr, Path};
+
+/// Statements of the binary layout attributes (`magic`, `pad`, `align`, `extensible`)
+/// for the generated methods
+pub struct LayoutCode {
+    /// for all decoding methods and `check_bytes`
+    pub read: TokenStream,
+    pub write: TokenStream,
+    pub try_write: TokenStream,
+    pub size: LayoutSize,
+}
+
+/// bytes added by a [`LayoutCode`]
+pub enum LayoutSize {
+    Fixed(usize),
+    /// padding up to a multiple of the alignment, depends on the size of the previous fields
+    Align(usize),
+}
+
+impl LayoutCode {
+    /// push the statements to `reads` (decoding and `check_bytes` methods),
+    /// `writes` (`convert_to_bytes*`) and `try_write` (`try_convert_to_bytes`)
+    pub fn push(
+        self,
+        reads: [&mut Vec<TokenStream>; 8],
+        writes: [&mut Vec<TokenStream>; 3],
+        try_write: &mut Vec<TokenStream>,
+    ) {
+        for method in reads {
+            method.push(self.read.clone());
+        }
+        for method in writes {
+            method.push(self.write.clone());
+        }
+        try_write.push(self.try_write);
+    }
+
+    /// add the bytes to `sizes` (`size`, `size_versioned`, `size_ctx` summands),
+    /// `static_sizes` and `min_size`
+    pub fn push_size(
+        &self,
+        rapira: &Path,
+        sizes: [&mut Vec<TokenStream>; 3],
+        static_sizes: &mut Vec<TokenStream>,
+        min_size: &mut Vec<TokenStream>,
+    ) {
+        match self.size {
+            LayoutSize::Fixed(0) => {}
+            LayoutSize::Fixed(bytes) => {
+                for method in sizes {
+                    method.push(quote! { + #bytes });
+                }
+                static_sizes.push(quote! { Some(#bytes), });
+                min_size.push(quote! { #bytes, });
+            }
+            LayoutSize::Align(align) => {
+                for method in sizes {
+                    let prev = std::mem::take(method);
+                    method.push(quote! { + #rapira::layout::align_up(0 #(#prev)*, #align) });
+                }
+                let prev = std::mem::take(static_sizes);
+                static_sizes.push(quote! {
+                    #rapira::layout::align_static(#rapira::static_size([#(#prev)*]), #align),
+                });
+                let prev = std::mem::take(min_size);
+                min_size.push(quote! {
+                    #rapira::layout::align_up(#rapira::min_size(&[#(#prev)*]), #align),
+                });
+            }
+        }
+    }
+}
+
+/// start of the struct: the payload of `#[rapira(extensible)]` (`Some(big)`), `__rapira_start`
+/// offset for `align` fields and the `magic` bytes, `None` when there is none of them
+pub fn struct_prelude(
+    rapira: &Path,
+    magic: Option<&LitByteStr>,
+    aligned: bool,
+    extensible: Option<bool>,
+) -> Option<LayoutCode> {
+    if magic.is_none() && !aligned && extensible.is_none() {
+        return None;
+    }
+    let (payload_read, payload_write) = match extensible {
+        Some(big) => (
+            quote! {
+                let mut __rapira_payload = #rapira::extensible::payload(__rapira_slice, #big)?;
+                let __rapira_slice = &mut __rapira_payload;
+            },
+            quote! { let __rapira_payload = #rapira::extensible::begin(__rapira_cursor); },
+        ),
+        None => (quote!(), quote!()),
+    };
+    let (start_read, start_write) = if aligned {
+        (
+            quote! { let __rapira_start = __rapira_slice.len(); },
+            quote! { let __rapira_start = *__rapira_cursor; },
+        )
+    } else {
+        (quote!(), quote!())
+    };
+    let (read, write, try_write) = match magic {
+        Some(magic) => (
+            quote! { #rapira::layout::read_magic(__rapira_slice, #magic)?; },
+            quote! { #rapira::extend(__rapira_slice, __rapira_cursor, #magic); },
+            quote! { #rapira::try_extend(__rapira_slice, __rapira_cursor, #magic)?; },
+        ),
+        None => (quote!(), quote!(), quote!()),
+    };
+    // the length prefix is added by `StructEnd`, alignment is relative to the payload
+    Some(LayoutCode {
+        read: quote! { #payload_read #start_read #read },
+        write: quote! { #payload_write #start_write #write },
+        try_write: quote! { #payload_write #start_write #try_write },
+        size: LayoutSize::Fixed(magic.map_or(0, |magic| magic.value().len())),
+    })
+}
+
+/// end of a `#[rapira(extensible)]` struct: the `#[rapira(rest)]` field takes
+/// the unknown bytes after the fields, the length prefix is written
+pub struct StructEnd {
+    /// decoding methods, `check_bytes` skips the rest
+    read: TokenStream,
+    write: TokenStream,
+    try_write: TokenStream,
+    size: TokenStream,
+    static_size: TokenStream,
+    min_size: TokenStream,
+}
+
+impl StructEnd {
+    /// `big` - the length prefix is big-endian, `rest` - the field with the unknown bytes
+    /// (`From<&[u8]> + AsRef<[u8]>`)
+    pub fn new(rapira: &Path, big: bool, rest: Option<&Ident>) -> Self {
+        let (read, write, try_write, size, static_size) = match rest {
+            Some(rest) => (
+                quote! { let #rest = From::from(core::mem::take(__rapira_slice)); },
+                quote! { #rapira::extend(__rapira_slice, __rapira_cursor, AsRef::<[u8]>::as_ref(&self.#rest)); },
+                quote! { #rapira::try_extend(__rapira_slice, __rapira_cursor, AsRef::<[u8]>::as_ref(&self.#rest))?; },
+                quote! { + #rapira::LEN_SIZE + AsRef::<[u8]>::as_ref(&self.#rest).len() },
+                quote! { None, },
+            ),
+            None => (
+                quote!(),
+                quote!(),
+                quote!(),
+                quote! { + #rapira::LEN_SIZE },
+                quote! { Some(#rapira::LEN_SIZE), },
+            ),
+        };
+        StructEnd {
+            read,
+            write: quote! {
+                #write
+                #rapira::extensible::end(__rapira_slice, __rapira_cursor, __rapira_payload, #big);
+            },
+            try_write: quote! {
+                #try_write
+                #rapira::extensible::try_end(__rapira_slice, __rapira_cursor, __rapira_payload, #big)?;
+            },
+            size,
+            static_size,
+            min_size: quote! { #rapira::LEN_SIZE, },
+        }
+    }
+
+    /// push the statements to `reads` (decoding methods), `writes` (`convert_to_bytes*`),
+    /// `try_write`, and the bytes to `sizes`, `static_sizes` and `min_size`
+    pub fn push(
+        self,
+        reads: [&mut Vec<TokenStream>; 6],
+        writes: [&mut Vec<TokenStream>; 3],
+        try_write: &mut Vec<TokenStream>,
+        sizes: [&mut Vec<TokenStream>; 3],
+        static_sizes: &mut Vec<TokenStream>,
+        min_size: &mut Vec<TokenStream>,
+    ) {
+        for method in reads {
+            method.push(self.read.clone());
+        }
+        for method in writes {
+            method.push(self.write.clone());
+        }
+        try_write.push(self.try_write);
+        for method in sizes {
+            method.push(self.size.clone());
+        }
+        static_sizes.push(self.static_size);
+        min_size.push(self.min_size);
+    }
+}
+
+/// zero bytes before a field with `#[rapira(pad = N)]` or `#[rapira(align = N)]`
+pub fn field_padding(
+    rapira: &Path,
+    pad: Option<usize>,
+    align: Option<usize>,
+) -> Option<LayoutCode> {
+    let (read_len, write_len, size) = match (pad, align) {
+        (Some(pad), _) => (quote! { #pad }, quote! { #pad }, LayoutSize::Fixed(pad)),
+        (None, Some(align)) => (
+            quote! { #rapira::layout::align_pad(__rapira_start - __rapira_slice.len(), #align) },
+            quote! { #rapira::layout::align_pad(*__rapira_cursor - __rapira_start, #align) },
+            LayoutSize::Align(align),
+        ),
+        (None, None) => return None,
+    };
+    Some(LayoutCode {
+        read: quote! { #rapira::fixed::read_padding(__rapira_slice, #read_len)?; },
+        write: quote! { #rapira::fixed::write_padding(__rapira_slice, __rapira_cursor, #write_len); },
+        try_write: quote! {
+            #rapira::fixed::try_write_padding(__rapira_slice, __rapira_cursor, #write_len)?;
+        },
+        size,
+    })
+}
+
+/// `bool` expression of `#[rapira(if = expr)]`, `earlier` fields are bound
+/// as references to `owner.field` (`self.` when writing, empty when reading)
+pub fn field_condition(cond: &Expr, earlier: &[Ident], owner: &TokenStream) -> TokenStream {
+    quote! {{
+        #(
+            #[allow(unused_variables)]
+            let #earlier = &#owner #earlier;
+        )*
+        #cond
+    }}
+}
+
+/// decoding expression of a `#[rapira(count = field)]` field, `read` decodes one item
+pub fn count_read(rapira: &Path, count: &Ident, read: TokenStream) -> TokenStream {
+    quote! { #rapira::layout::read_counted(__rapira_slice, #rapira::layout::count(&#count)?, #read) }
+}
+
+/// statements writing the items of a `#[rapira(count = field)]` field one by one by `write`
+/// (`__rapira_item` is a reference to the item), `try_write` returns an error
+/// when `field` doesn't match the length
+pub fn count_write(
+    rapira: &Path,
+    count: &Ident,
+    ident: &Ident,
+    write: TokenStream,
+    try_write: bool,
+) -> TokenStream {
+    let check = quote! { #rapira::layout::check_count(&self.#count, self.#ident.len()) };
+    let check = if try_write {
+        quote! { #check?; }
+    } else {
+        quote! { debug_assert!(#check.is_ok()); }
+    };
+    quote! {
+        #check
+        for __rapira_item in &self.#ident {
+            #write
+        }
+    }
+}
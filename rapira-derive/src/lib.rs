@@ -3,56 +3,190 @@ extern crate quote;
 extern crate syn;
 
 mod attributes;
+mod bits;
 mod enum_with_primitive;
 mod enums;
 mod field_attrs;
+mod layout;
+mod proxy;
 mod shared;
 mod simple_enum;
+mod sparse;
 mod structs;
 
 use enum_with_primitive::enum_with_primitive_serializer;
 use enums::enum_serializer;
 use proc_macro2::TokenStream;
+use proxy::{proxy_serializer, transparent_serializer};
 use quote::quote;
+use shared::{Errors, constraint_schema, endian_check};
 use simple_enum::simple_enum_serializer;
+use sparse::sparse_serializer;
 use structs::struct_serializer;
-use syn::{Data, DeriveInput, Fields, Ident, parse_macro_input};
+use syn::{Data, DataEnum, DeriveInput, Fields, parse_macro_input};
 
 /// available attributes:
 /// - `#[primitive(PrimitiveName)]` - set primitive enum for complex enum
 /// - `#[idx = 1]`
 /// - `#[rapira(static_size = expr)]`
 /// - `#[rapira(min_size = expr)]`
-/// - `#[rapira(with = path)]`
+/// - `#[rapira(with = Adapter)]` - on field: serialize by `Adapter: rapira::RapiraWith<FieldType>`
+///   (`rapira::byte_rapira`, `rapira::str_rapira`, `rapira::zero`, ..), all methods go through it
 /// - `#[rapira(skip)]`
+/// - `#[rapira(flatten)]` - on field: the nested struct's fields are written inline, bytes are
+///   identical to declaring them in the parent at the field's position (`#[idx]` moves them as
+///   a group), `since` fields of the nested struct follow the parent version; can't be combined
+///   with `with` or `skip`; the field type must be a derived struct without own framing (`magic`,
+///   `align` fields, `extensible`, `sparse`, `transparent`, `from`/`into`), other types are
+///   compile errors
+/// - `#[rapira(omit_if = FLAG)]`, `#[rapira(only_if = FLAG)]` - on field: written only when the
+///   `RapiraFlags` of `*_ctx` methods lack / contain `FLAG: u64` (other methods use `RapiraFlags::NONE`),
+///   absent fields decode as `Default::default()`
+/// - `#[rapira(default = expr)]`, `#[rapira(default_with = path)]` - on field with `skip`, `since`,
+///   `omit_if` or `only_if`: value of the absent field instead of `Default::default()`
 /// - `#[rapira(debug)]`
-/// - `#[rapira(version = N)]` - on struct: enable versioned deserialization
-/// - `#[rapira(since = N)]` - on field: field added in version N (requires version on struct)
+/// - `#[rapira(validate = path)]` - on container or field: `fn(&T) -> rapira::Result<()>` called
+///   after decoding in `from_slice`, `from_slice_versioned`, `from_slice_ctx` and `check_bytes`
+///   (not in `from_slice_unchecked`/`from_slice_unsafe`), return `RapiraError::validation(msg)`;
+///   field names are added to the path of `RapiraError::Validation`
+/// - `#[rapira(max_len = N)]`, `#[rapira(non_empty)]` - on `rapira::LenPrefixed` field (`String`,
+///   `Vec`, maps, `Bytes`, ..): the length prefix is checked before the field is decoded, `max_len`
+///   replaces the `Rapira::MAX_CAP` limit of the items for the field in all decoding methods
+/// - `#[rapira(range = a..=b)]` - on field: the decoded value must be in the range, checked like
+///   `validate` (violations of constraints are `RapiraError::Validation` too); constraints of all
+///   fields are exported by `rapira::constraints::ConstraintSchema` (implemented for every type,
+///   `flatten` fields carry the constraints of their type)
+/// - `#[rapira(bits = N)]` - on struct field of a `rapira::BitField` type (`bool`, `u8`..`u64`,
+///   simple enums): consecutive `bits` fields are packed into one little-endian bitfield of
+///   the fewest bytes (a new one starts past 64 bits), the first field in the lowest bits;
+///   decoding rejects values without a variant and set unused bits (`RapiraError::BitField`),
+///   values must fit in `N` bits (an error in `try_convert_to_bytes`, a panic in
+///   `convert_to_bytes`)
+/// - `#[rapira(version = N)]` - on struct or enum: enable versioned deserialization (`N: u16`)
+/// - `#[rapira(since = N)]` - on field or enum variant: added in version N (requires version on container)
+/// - `#[rapira(retired(name = x, ty = T, since = N, until = M, after = field))]` - on struct:
+///   field removed in version M, read and discarded for data of versions `N..M`
+/// - `#[rapira(migrate(from = N, via = LegacyType, with = path))]` - on struct or enum:
+///   data of versions `<= N` is decoded as `LegacyType` and converted by `path`
+///   (`try_with = path` for a fallible conversion returning `rapira::Result<Self>`)
+///   and `downgrade = path` (`fn(&Self) -> LegacyType`) for writing at versions `<= N`
+/// - `#[rapira(transparent)]` - on struct: written as its only field (other fields must be `skip`)
+/// - `#[rapira(fixed)]` - on `Option<T>` field: written as the tag and `T` zero-padded to
+///   `T::STATIC_SIZE` (`rapira::fixed_option`), so the field has a static size; on struct:
+///   for all its `Option` fields; on enum with data: also every variant is zero-padded to the
+///   largest one, the enum is static-size (compile error for payloads without a static size);
+///   non-zero padding is `RapiraError::Padding`
+/// - `#[rapira(sparse)]` - on struct: a presence bitmap (a bit per field, field `i` in bit `i % 8`
+///   of byte `i / 8`) followed by the present fields only; `Option<T>` fields are present when
+///   `Some` and written as `T`, other fields when not equal to `Default::default()`
+///   (`Default + PartialEq`), absent fields decode as `None`/`Default::default()`; `since` fields
+///   take a bit only at versions `>= since`; no `omit_if`/`only_if`, `flatten` or `bits` fields
+/// - `#[rapira(magic = b"RPR1")]` - on struct: bytes written before the fields, other bytes
+///   are `RapiraError::Magic`
+/// - `#[rapira(pad = N)]`, `#[rapira(align = N)]` - on struct field: `N` zero bytes before
+///   the field, or zero bytes up to an offset (from the start of the struct, `magic` included)
+///   multiple of `N`; written whether the field is present or not, non-zero is `RapiraError::Padding`
+/// - `#[rapira(count = field)]` - on `Vec` struct field: written without a length prefix,
+///   the length is the value of an earlier integer `field` (`try_convert_to_bytes` checks
+///   they match, `RapiraError::Count`)
+/// - `#[rapira(if = expr)]` - on struct field: present when `expr` is true, earlier fields are
+///   in scope as references (`if = *kind == 2`), absent fields decode as their default;
+///   with `count` or `if` fields `check_bytes` decodes the struct
+/// - `#[rapira(extensible)]` - on struct: the fields are written after a `u32` length prefix
+///   (`rapira::extensible`), bytes after the known fields (written by a newer peer) are skipped,
+///   or kept by the `#[rapira(rest)]` field (`Vec<u8>`) and written back after the fields;
+///   on enum with data: every variant payload is written after the tag with a `u32` length
+///   prefix, a variant is decoded from its payload only (bytes a newer writer appended are
+///   skipped); `#[rapira(unknown)]` on the last variant with two fields, the tag (an unsigned
+///   integer at least as wide as the enum tag type) and the raw payload (`Vec<u8>`):
+///   `Unknown { tag: u32, raw: Vec<u8> }` captures unknown tags and is written back unchanged
+///   (a tag too wide for the enum tag is `RapiraError::EnumVariant` in `try_convert_to_bytes`
+///   and a panic in `convert_to_bytes`), without it they are `RapiraError::EnumVariant`
+/// - `#[rapira(endian = "big")]` - on struct, enum or field: integers, floats, `NonZeroU32`,
+///   `NonZeroU64`, the length prefix of `String` and `Vec<T>`, `Vec` items, `count` items and
+///   `u16`/`u32` enum tags are big-endian (`rapira::be` adapters), fields of other types keep
+///   their own layout; `endian = "little"` on a field overrides the container; on container,
+///   fields with little-endian numbers and no adapter (`Option<u32>`, `[u16; N]`, tuples,
+///   aliases of integers) are compile errors, fields of generic types are not checked
+/// - `#[rapira(from = "T")]`, `#[rapira(try_from = "T")]`, `#[rapira(into = "U")]` - on container:
+///   read as `T` and converted by `From<T>`/`TryFrom<T>` (errors are `RapiraError::Conversion`,
+///   `check_bytes` converts too), written as `U` by `Into<U>` of a clone (`Self: Clone`);
+///   without `into` the `from` type is written and vice versa
+/// - `#[rapira(max_cap = N)]` - on container: `Rapira::MAX_CAP`, max items of decoded
+///   `Vec`s, `BTreeMap`s and `IndexMap`s of this type (by default the global `rapira::max_cap`
+///   limits)
+/// - `#[rapira(crate = "path::to::rapira")]` - path of rapira in generated code,
+///   by default the (possibly renamed) `rapira` dependency from Cargo.toml
+/// - `#[rapira(bound = "T: Trait")]` - on container: where predicates instead of the inferred ones,
+///   on field: predicates instead of the inferred ones of the field
+///   (inferred: `T: Rapira` for fields of a parameter type `T`, `FieldType: Rapira` for other
+///   field types using parameters, `T: Rapira` for the parameters of recursive field types,
+///   `Adapter: RapiraWith<FieldType>` for `with` fields using parameters, `skip` fields and
+///   `PhantomData<T>` need none)
+/// - `#[rapira(tag = u16)]` - on enum: variant tag type, `u8` (default), `u16` or `u32`;
+///   `#[idx = N]` or variant positions must fit the tag and be unique
+/// - simple (fieldless) enums are written as their discriminant (or `#[idx = N]`),
+///   the tag type defaults to `#[repr(u8 | u16 | u32)]`, other integer reprs need `tag`;
+///   non-literal discriminants (`A = -1`, `B = BASE + 1`) are checked at compile time
+///   to fit the tag and to be unique;
+///   `#[rapira(other)]` on a variant: decode unknown tags as this variant;
+///   they implement `rapira::BitField` by the tag too
+/// - several items can be combined: `#[rapira(version = 3, migrate(..), debug)]`
+///
+/// unknown or misplaced `#[rapira(..)]` keys and invalid values are compile errors
 #[proc_macro_derive(Rapira, attributes(rapira, idx, primitive))]
 pub fn serializer_trait(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(stream as DeriveInput);
+    rapira_derive(ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn rapira_derive(ast: DeriveInput) -> syn::Result<TokenStream> {
+    check_attrs(&ast)?;
+
+    let schema = constraint_schema(&ast)?;
+    let endian = endian_check(&ast)?;
+    let serializer = serializer(ast)?;
+    Ok(quote! {
+        #serializer
+        #schema
+        #endian
+    })
+}
+
+fn serializer(ast: DeriveInput) -> syn::Result<TokenStream> {
     let name = &ast.ident;
     let data = &ast.data;
-    let is_debug = attributes::debug_attr(&ast.attrs);
+    let is_debug = attributes::debug_attr(&ast.attrs)?;
+
+    if let Some(proxy) = attributes::proxy_attr(&ast.attrs)? {
+        return proxy_serializer(name, ast.generics, &ast.attrs, proxy);
+    }
 
     match data {
+        Data::Struct(data_struct) if attributes::transparent_attr(&ast.attrs)? => {
+            transparent_serializer(data_struct, name, ast.generics, &ast.attrs)
+        }
+        Data::Struct(data_struct) if attributes::sparse_attr(&ast.attrs)? => {
+            sparse_serializer(data_struct, name, ast.generics, &ast.attrs)
+        }
         Data::Struct(data_struct) => {
             struct_serializer(data_struct, name, ast.generics, is_debug, &ast.attrs)
         }
         Data::Enum(data_enum) => {
-            let is_simple_enum = data_enum.variants.iter().all(|item| item.fields.is_empty());
-            if is_simple_enum {
-                simple_enum_serializer(name)
+            if is_simple_enum(data_enum) {
+                simple_enum_serializer(data_enum, name, &ast.attrs)
             } else {
-                let primitive_name = attributes::get_primitive_name(&ast.attrs);
+                let primitive_name = attributes::get_primitive_name(&ast.attrs)?;
 
                 match primitive_name {
                     Some(primitive_name) => {
-                        enum_with_primitive_serializer(data_enum, name, primitive_name)
+                        enum_with_primitive_serializer(data_enum, name, primitive_name, &ast.attrs)
                     }
                     None => {
-                        let enum_static_size = attributes::enum_static_size(&ast.attrs);
-                        let min_size = attributes::min_size(&ast.attrs);
+                        let enum_static_size = attributes::enum_static_size(&ast.attrs)?;
+                        let min_size = attributes::min_size(&ast.attrs)?;
                         enum_serializer(
                             data_enum,
                             name,
@@ -60,17 +194,120 @@ pub fn serializer_trait(stream: proc_macro::TokenStream) -> proc_macro::TokenStr
                             min_size,
                             ast.generics,
                             is_debug,
+                            &ast.attrs,
                         )
                     }
                 }
             }
         }
-        Data::Union(_) => {
-            panic!(
-                "unions not supported, but Rust enums is implemented Rapira trait (use Enums instead)"
+        Data::Union(data_union) => Err(syn::Error::new_spanned(
+            data_union.union_token,
+            "unions not supported, but Rust enums is implemented Rapira trait (use Enums instead)",
+        )),
+    }
+}
+
+fn is_simple_enum(data_enum: &DataEnum) -> bool {
+    data_enum.variants.iter().all(|item| item.fields.is_empty())
+}
+
+/// Check all container, variant and field attributes before generating code,
+/// so every attribute error is reported at once
+fn check_attrs(ast: &DeriveInput) -> syn::Result<()> {
+    let name = &ast.ident;
+    let mut errors = Errors::default();
+
+    let check_fields = |errors: &mut Errors,
+                        fields: &Fields,
+                        keys: &[&str],
+                        prefix: &str,
+                        version: Option<u16>,
+                        container: &str| {
+        for (idx, field) in fields.iter().enumerate() {
+            let desc = match &field.ident {
+                Some(ident) => format!("{prefix}field `{ident}`"),
+                None => format!("{prefix}field {idx}"),
+            };
+            errors.check(field_attrs::check_field(
+                &field.attrs,
+                keys,
+                &desc,
+                version,
+                container,
+            ));
+        }
+    };
+
+    match &ast.data {
+        Data::Struct(data_struct) => {
+            errors.check(attributes::check_container(
+                &ast.attrs,
+                attributes::STRUCT_KEYS,
+                "struct",
+            ));
+            let version = attributes::version_attr(&ast.attrs).unwrap_or_default();
+            let container = format!("struct `{name}`");
+            let field_keys = match &data_struct.fields {
+                Fields::Named(_) => field_attrs::FIELD_KEYS,
+                _ => field_attrs::UNNAMED_FIELD_KEYS,
+            };
+            check_fields(
+                &mut errors,
+                &data_struct.fields,
+                field_keys,
+                "",
+                version,
+                &container,
             );
         }
+        Data::Enum(data_enum) => {
+            let simple = is_simple_enum(data_enum);
+            let primitive = matches!(attributes::get_primitive_name(&ast.attrs), Ok(Some(_)));
+            let (keys, variant_keys, field_keys) = if simple {
+                (
+                    attributes::SIMPLE_ENUM_KEYS,
+                    field_attrs::SIMPLE_VARIANT_KEYS,
+                    &[][..],
+                )
+            } else if primitive {
+                (
+                    attributes::PRIMITIVE_ENUM_KEYS,
+                    field_attrs::VARIANT_KEYS,
+                    field_attrs::PRIMITIVE_VARIANT_FIELD_KEYS,
+                )
+            } else {
+                (
+                    attributes::ENUM_KEYS,
+                    field_attrs::DATA_VARIANT_KEYS,
+                    field_attrs::VARIANT_FIELD_KEYS,
+                )
+            };
+            errors.check(attributes::check_container(&ast.attrs, keys, "enum"));
+            let version = attributes::version_attr(&ast.attrs).unwrap_or_default();
+            let container = format!("enum `{name}`");
+            for variant in &data_enum.variants {
+                let variant_name = &variant.ident;
+                errors.check(field_attrs::check_field(
+                    &variant.attrs,
+                    variant_keys,
+                    &format!("variant `{variant_name}`"),
+                    version,
+                    &container,
+                ));
+                check_fields(
+                    &mut errors,
+                    &variant.fields,
+                    field_keys,
+                    &format!("variant `{variant_name}` "),
+                    version,
+                    &container,
+                );
+            }
+        }
+        Data::Union(_) => {}
     }
+
+    errors.finish()
 }
 
 /// #[primitive(PrimitiveName)]
@@ -86,21 +323,22 @@ pub fn derive_primitive_from_enum(stream: proc_macro::TokenStream) -> proc_macro
             let is_simple_enum = data_enum.variants.iter().all(|item| item.fields.is_empty());
 
             if is_simple_enum {
-                panic!("PrimitiveFromEnum only for non simple enum allow");
+                syn::Error::new_spanned(name, "PrimitiveFromEnum only for non simple enum allow")
+                    .into_compile_error()
+                    .into()
             } else {
-                let primitive_name = ast
-                    .attrs
-                    .iter()
-                    .find_map(|attr| {
-                        if !attr.path().is_ident("primitive") {
-                            return None;
-                        }
-
-                        let ident: Ident = attr.parse_args().unwrap();
-
-                        Some(ident)
-                    })
-                    .expect("complex enums must include primitive type name");
+                let primitive_name = match attributes::get_primitive_name(&ast.attrs) {
+                    Ok(Some(primitive_name)) => primitive_name,
+                    Ok(None) => {
+                        return syn::Error::new_spanned(
+                            name,
+                            "complex enums must include primitive type name: #[primitive(PrimitiveName)]",
+                        )
+                        .into_compile_error()
+                        .into();
+                    }
+                    Err(err) => return err.into_compile_error().into(),
+                };
 
                 let len = data_enum.variants.len();
 
@@ -158,13 +396,13 @@ pub fn derive_primitive_from_enum(stream: proc_macro::TokenStream) -> proc_macro
                 proc_macro::TokenStream::from(res)
             }
         }
-        _ => {
-            panic!("PrimitiveFromEnum only for enum allow");
-        }
+        _ => syn::Error::new_spanned(name, "PrimitiveFromEnum only for enum allow")
+            .into_compile_error()
+            .into(),
     }
 }
 
-#[proc_macro_derive(FromU8, attributes(primitive))]
+#[proc_macro_derive(FromU8, attributes(primitive, rapira))]
 pub fn derive_from_u8(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(stream as DeriveInput);
 
@@ -175,6 +413,10 @@ pub fn derive_from_u8(stream: proc_macro::TokenStream) -> proc_macro::TokenStrea
         Data::Enum(data_enum) => {
             let is_simple_enum = data_enum.variants.iter().all(|item| item.fields.is_empty());
             if is_simple_enum {
+                let rapira = match attributes::crate_path(&ast.attrs) {
+                    Ok(rapira) => rapira,
+                    Err(err) => return err.into_compile_error().into(),
+                };
                 let mut variants: Vec<TokenStream> = Vec::with_capacity(data_enum.variants.len());
                 let mut try_variants: Vec<TokenStream> =
                     Vec::with_capacity(data_enum.variants.len());
@@ -201,7 +443,7 @@ pub fn derive_from_u8(stream: proc_macro::TokenStream) -> proc_macro::TokenStrea
                             e as u8
                         }
                     }
-                    impl rapira::FromU8 for #name {
+                    impl #rapira::FromU8 for #name {
                         /// # Panics
                         ///
                         /// Panics if `u` is not equal to any variant
@@ -214,22 +456,27 @@ pub fn derive_from_u8(stream: proc_macro::TokenStream) -> proc_macro::TokenStrea
                         }
                     }
                     impl core::convert::TryFrom<u8> for #name {
-                        type Error = rapira::EnumFromU8Error;
+                        type Error = #rapira::EnumFromU8Error;
                         fn try_from(value: u8) -> Result<Self, Self::Error> {
                             match value {
                                 #(#try_variants)*
-                                _ => Err(rapira::EnumFromU8Error),
+                                _ => Err(#rapira::EnumFromU8Error),
                             }
                         }
                     }
                 };
                 proc_macro::TokenStream::from(r#gen)
             } else {
-                panic!("FromU8 only for simple enum allow (without nested data)");
+                syn::Error::new_spanned(
+                    name,
+                    "FromU8 only for simple enum allow (without nested data)",
+                )
+                .into_compile_error()
+                .into()
             }
         }
-        _ => {
-            panic!("FromU8 only for enum allow");
-        }
+        _ => syn::Error::new_spanned(name, "FromU8 only for enum allow")
+            .into_compile_error()
+            .into(),
     }
 }
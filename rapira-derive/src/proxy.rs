@@ -0,0 +1,394 @@
+extern crate proc_macro2;
+extern crate quote;
+extern crate syn;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{Attribute, DataStruct, Generics, Member, WherePredicate};
+
+use crate::{
+    attributes::{self, Proxy},
+    field_attrs::{
+        align_attr, count_attr, flag_condition_attr, if_attr, pad_attr, rest_attr, skip_attr,
+    },
+    shared::{build_ident, default_value, field_codec, field_with, impl_bounds, max_cap_const},
+};
+
+/// `#[rapira(transparent)]`: struct with one serialized field is written as this field,
+/// other fields must be `#[rapira(skip)]` and are filled with their default value
+pub fn transparent_serializer(
+    data_struct: &DataStruct,
+    name: &Ident,
+    generics: Generics,
+    attrs: &[Attribute],
+) -> syn::Result<TokenStream> {
+    let rapira = &attributes::crate_path(attrs)?;
+
+    let mut inner = None;
+    let mut skipped: Vec<TokenStream> = Vec::new();
+    for (idx, field) in data_struct.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(idx.into()),
+        };
+        if skip_attr(&field.attrs)? {
+            let default = default_value(&field.attrs)?;
+            skipped.push(quote! { #member: #default, });
+            continue;
+        }
+        if inner.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                format!(
+                    "#[rapira(transparent)] struct `{name}` must have exactly one field without #[rapira(skip)]"
+                ),
+            ));
+        }
+        let unsupported = if !flag_condition_attr(&field.attrs)?.is_empty() {
+            Some("omit_if/only_if")
+        } else if pad_attr(&field.attrs)?.is_some() {
+            Some("pad")
+        } else if align_attr(&field.attrs)?.is_some() {
+            Some("align")
+        } else if count_attr(&field.attrs)?.is_some() {
+            Some("count")
+        } else if if_attr(&field.attrs)?.is_some() {
+            Some("if")
+        } else if rest_attr(&field.attrs)? {
+            Some("rest")
+        } else {
+            None
+        };
+        if let Some(key) = unsupported {
+            return Err(syn::Error::new_spanned(
+                field,
+                format!(
+                    "#[rapira(transparent)] struct `{name}`: #[rapira({key})] is not supported"
+                ),
+            ));
+        }
+        inner = Some((member, field));
+    }
+    let Some((member, field)) = inner else {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!(
+                "#[rapira(transparent)] struct `{name}` must have exactly one field without #[rapira(skip)]"
+            ),
+        ));
+    };
+
+    let typ = &field.ty;
+    let codec = field_codec(rapira, typ, field_with(rapira, field, attrs)?.as_ref());
+    let build = quote! {
+        Ok(#name {
+            #member: __rapira_value,
+            #(#skipped)*
+        })
+    };
+
+    let bounds = impl_bounds(rapira, name, &generics, attrs, core::iter::once(field))?;
+    let name_with_generics = build_ident(rapira, name, generics, bounds);
+
+    let max_cap = max_cap_const(attrs)?;
+    Ok(quote! {
+        #name_with_generics {
+            const STATIC_SIZE: Option<usize> = #codec::STATIC_SIZE;
+            const MIN_SIZE: usize = #codec::MIN_SIZE;
+            #max_cap
+
+            #[inline]
+            fn size(&self) -> usize {
+                #codec::size(&self.#member)
+            }
+
+            #[inline]
+            fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()> {
+                #codec::check_bytes(__rapira_slice)
+            }
+
+            #[inline]
+            fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = #codec::from_slice(__rapira_slice)?;
+                #build
+            }
+
+            #[inline]
+            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = unsafe { #codec::from_slice_unchecked(__rapira_slice)? };
+                #build
+            }
+
+            #[inline]
+            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = unsafe { #codec::from_slice_unsafe(__rapira_slice)? };
+                #build
+            }
+
+            #[inline]
+            fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u16) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = #codec::from_slice_versioned(__rapira_slice, __rapira_version)?;
+                #build
+            }
+
+            #[inline]
+            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
+                #codec::try_convert_to_bytes(&self.#member, __rapira_slice, __rapira_cursor)
+            }
+
+            #[inline]
+            fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {
+                #codec::convert_to_bytes(&self.#member, __rapira_slice, __rapira_cursor)
+            }
+
+            #[inline]
+            fn convert_to_bytes_versioned(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_version: u16) {
+                #codec::convert_to_bytes_versioned(&self.#member, __rapira_slice, __rapira_cursor, __rapira_version)
+            }
+
+            #[inline]
+            fn size_versioned(&self, __rapira_version: u16) -> usize {
+                #codec::size_versioned(&self.#member, __rapira_version)
+            }
+
+            #[inline]
+            fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: #rapira::RapiraFlags) {
+                #codec::convert_to_bytes_ctx(&self.#member, __rapira_slice, __rapira_cursor, __rapira_flags)
+            }
+
+            #[inline]
+            fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = #codec::from_slice_ctx(__rapira_slice, __rapira_flags)?;
+                #build
+            }
+
+            #[inline]
+            fn size_ctx(&self, __rapira_flags: #rapira::RapiraFlags) -> usize {
+                #codec::size_ctx(&self.#member, __rapira_flags)
+            }
+
+            #[inline]
+            fn check_bytes_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<()> {
+                #codec::check_bytes_ctx(__rapira_slice, __rapira_flags)
+            }
+        }
+    })
+}
+
+/// `#[rapira(from = "T")]`/`#[rapira(try_from = "T")]` and `#[rapira(into = "U")]`:
+/// read as `T` and converted by `From<T>`/`TryFrom<T>` (its error is `RapiraError::Conversion`,
+/// `check_bytes` runs the conversion too), written as `U` converted from a clone by `Into<U>`
+pub fn proxy_serializer(
+    name: &Ident,
+    generics: Generics,
+    attrs: &[Attribute],
+    proxy: Proxy,
+) -> syn::Result<TokenStream> {
+    let rapira = &attributes::crate_path(attrs)?;
+    let Proxy {
+        from,
+        try_from,
+        into,
+    } = proxy;
+    let read = quote! { <#from as #rapira::Rapira> };
+    let write = quote! { <#into as #rapira::Rapira> };
+
+    let (static_size, min_size) =
+        if from.to_token_stream().to_string() == into.to_token_stream().to_string() {
+            (quote! { #read::STATIC_SIZE }, quote! { #read::MIN_SIZE })
+        } else {
+            (
+                quote! {
+                    match (#read::STATIC_SIZE, #write::STATIC_SIZE) {
+                        (Some(r), Some(w)) if r == w => Some(r),
+                        _ => None,
+                    }
+                },
+                quote! {
+                    if #read::MIN_SIZE < #write::MIN_SIZE {
+                        #read::MIN_SIZE
+                    } else {
+                        #write::MIN_SIZE
+                    }
+                },
+            )
+        };
+
+    let convert = if try_from {
+        quote! {
+            <Self as ::core::convert::TryFrom<#from>>::try_from(__rapira_value)
+                .map_err(|_| #rapira::RapiraError::Conversion)
+        }
+    } else {
+        quote! { Ok(<Self as ::core::convert::From<#from>>::from(__rapira_value)) }
+    };
+    let (check_bytes, check_bytes_ctx) = if try_from {
+        (
+            quote! {
+                let __rapira_value = #read::from_slice(__rapira_slice)?;
+                let _: Self = #convert?;
+                Ok(())
+            },
+            quote! {
+                let __rapira_value = #read::from_slice_ctx(__rapira_slice, __rapira_flags)?;
+                let _: Self = #convert?;
+                Ok(())
+            },
+        )
+    } else {
+        (
+            quote! { #read::check_bytes(__rapira_slice) },
+            quote! { #read::check_bytes_ctx(__rapira_slice, __rapira_flags) },
+        )
+    };
+    let value = quote! {
+        let __rapira_value: #into = ::core::convert::Into::into(::core::clone::Clone::clone(self));
+    };
+
+    let bounds = match attributes::bound_attr(attrs)? {
+        Some(bound) => bound,
+        None => {
+            let (_, ty_generics, _) = generics.split_for_impl();
+            let from_bound: WherePredicate = if try_from {
+                syn::parse_quote! { #name #ty_generics: ::core::convert::TryFrom<#from> }
+            } else {
+                syn::parse_quote! { #name #ty_generics: ::core::convert::From<#from> }
+            };
+            vec![
+                syn::parse_quote! { #from: #rapira::Rapira },
+                syn::parse_quote! { #into: #rapira::Rapira },
+                from_bound,
+                syn::parse_quote! {
+                    #name #ty_generics: ::core::clone::Clone + ::core::convert::Into<#into>
+                },
+            ]
+        }
+    };
+    let name_with_generics = build_ident(rapira, name, generics, bounds);
+
+    let max_cap = max_cap_const(attrs)?;
+    Ok(quote! {
+        #name_with_generics {
+            const STATIC_SIZE: Option<usize> = #static_size;
+            const MIN_SIZE: usize = #min_size;
+            #max_cap
+
+            #[inline]
+            fn size(&self) -> usize {
+                match #write::STATIC_SIZE {
+                    Some(s) => s,
+                    None => {
+                        #value
+                        #write::size(&__rapira_value)
+                    }
+                }
+            }
+
+            #[inline]
+            fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()> {
+                #check_bytes
+            }
+
+            #[inline]
+            fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = #read::from_slice(__rapira_slice)?;
+                #convert
+            }
+
+            #[inline]
+            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = unsafe { #read::from_slice_unchecked(__rapira_slice)? };
+                #convert
+            }
+
+            #[inline]
+            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = unsafe { #read::from_slice_unsafe(__rapira_slice)? };
+                #convert
+            }
+
+            #[inline]
+            fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u16) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = #read::from_slice_versioned(__rapira_slice, __rapira_version)?;
+                #convert
+            }
+
+            #[inline]
+            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
+                #value
+                #write::try_convert_to_bytes(&__rapira_value, __rapira_slice, __rapira_cursor)
+            }
+
+            #[inline]
+            fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {
+                #value
+                #write::convert_to_bytes(&__rapira_value, __rapira_slice, __rapira_cursor)
+            }
+
+            #[inline]
+            fn convert_to_bytes_versioned(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_version: u16) {
+                #value
+                #write::convert_to_bytes_versioned(&__rapira_value, __rapira_slice, __rapira_cursor, __rapira_version)
+            }
+
+            #[inline]
+            fn size_versioned(&self, __rapira_version: u16) -> usize {
+                #value
+                #write::size_versioned(&__rapira_value, __rapira_version)
+            }
+
+            #[inline]
+            fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: #rapira::RapiraFlags) {
+                #value
+                #write::convert_to_bytes_ctx(&__rapira_value, __rapira_slice, __rapira_cursor, __rapira_flags)
+            }
+
+            #[inline]
+            fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                let __rapira_value = #read::from_slice_ctx(__rapira_slice, __rapira_flags)?;
+                #convert
+            }
+
+            #[inline]
+            fn size_ctx(&self, __rapira_flags: #rapira::RapiraFlags) -> usize {
+                #value
+                #write::size_ctx(&__rapira_value, __rapira_flags)
+            }
+
+            #[inline]
+            fn check_bytes_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<()> {
+                #check_bytes_ctx
+            }
+        }
+    })
+}
@@ -1,54 +1,1089 @@
-use proc_macro2::{Ident, TokenStream};
-use quote::quote;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
 use syn::{
-    GenericParam, Generics, Path, PathSegment, PredicateType, TraitBound, TraitBoundModifier, Type,
-    TypeParam, TypeParamBound, TypePath, WherePredicate,
+    Attribute, Data, DeriveInput, ExprPath, Field, Fields, GenericArgument, Generics, LitStr, Path,
+    PathArguments, PathSegment, PredicateType, TraitBound, TraitBoundModifier, Type,
+    TypeParamBound, TypePath, WherePredicate,
     punctuated::Punctuated,
-    token::{Colon, Comma},
+    spanned::Spanned,
+    token::Colon,
+    visit::{self, Visit},
 };
 
-pub fn build_ident(name: &Ident, mut generics: Generics) -> TokenStream {
+use crate::{
+    attributes::{self, Migration},
+    field_attrs::{
+        Constraints, FlagCondition, bits_attr, constraints_attr, count_attr, default_attr,
+        extract_with_attr, flag_condition_attr, flatten_attr, skip_attr, unknown_attr,
+    },
+};
+
+/// accumulates errors to report all of them at once
+#[derive(Default)]
+pub struct Errors(Option<syn::Error>);
+
+impl Errors {
+    pub fn push(&mut self, err: syn::Error) {
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    /// value of `res`, its error is accumulated
+    pub fn check<T>(&mut self, res: syn::Result<T>) -> Option<T> {
+        res.map_err(|err| self.push(err)).ok()
+    }
+
+    pub fn finish(self) -> syn::Result<()> {
+        match self.0 {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// `impl .. rapira::Rapira for Name ..` with `predicates` added to the where clause
+pub fn build_ident(
+    rapira: &Path,
+    name: &Ident,
+    mut generics: Generics,
+    predicates: Vec<WherePredicate>,
+) -> TokenStream {
     if generics.params.is_empty() {
-        return quote! { impl rapira::Rapira for #name };
+        return quote! { impl #rapira::Rapira for #name };
     }
 
-    let predicates: Punctuated<WherePredicate, Comma> = generics
-        .params
-        .iter()
-        .filter_map(|param| match param {
-            GenericParam::Type(TypeParam { ident, .. }) => {
-                let path_segment = PathSegment::from(ident.clone());
-                let path = Path::from(path_segment);
-                let type_path = TypePath { qself: None, path };
-                let ty = Type::from(type_path);
-
-                let rapira_path: Path = syn::parse_quote! { rapira::Rapira };
-                let trait_bound = TraitBound {
-                    paren_token: None,
-                    modifier: TraitBoundModifier::None,
-                    lifetimes: None,
-                    path: rapira_path,
+    let where_clause = generics.make_where_clause();
+    where_clause.predicates.extend(predicates);
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! { impl #impl_generics #rapira::Rapira for #name #ty_generics #where_clause }
+}
+
+/// `const MAX_CAP` of the `Rapira` impl from `#[rapira(max_cap = N)]`
+pub fn max_cap_const(attrs: &[Attribute]) -> syn::Result<Option<TokenStream>> {
+    Ok(attributes::max_cap_attr(attrs)?.map(|max_cap| quote! { const MAX_CAP: usize = #max_cap; }))
+}
+
+/// `ident: rapira::Rapira`
+fn rapira_bound(rapira: &Path, ident: &Ident) -> WherePredicate {
+    let path_segment = PathSegment::from(ident.clone());
+    let path = Path::from(path_segment);
+    let type_path = TypePath { qself: None, path };
+    let ty = Type::from(type_path);
+
+    let rapira_path: Path = syn::parse_quote! { #rapira::Rapira };
+    let trait_bound = TraitBound {
+        paren_token: None,
+        modifier: TraitBoundModifier::None,
+        lifetimes: None,
+        path: rapira_path,
+    };
+    let type_param_bound = TypeParamBound::Trait(trait_bound);
+    let mut bounds = Punctuated::new();
+    bounds.push(type_param_bound);
+
+    WherePredicate::Type(PredicateType {
+        lifetimes: None,
+        bounded_ty: ty,
+        colon_token: Colon::default(),
+        bounds,
+    })
+}
+
+/// type parameters used by a field type, except inside `PhantomData`
+struct UsedParams<'a> {
+    params: &'a [&'a Ident],
+    used: Vec<&'a Ident>,
+}
+
+impl<'ast> Visit<'ast> for UsedParams<'_> {
+    fn visit_type_path(&mut self, type_path: &'ast TypePath) {
+        if type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "PhantomData")
+        {
+            return;
+        }
+        // `T` or `T::Assoc`
+        if type_path.qself.is_none()
+            && let Some(first) = type_path.path.segments.first()
+            && let Some(param) = self.params.iter().find(|param| **param == &first.ident)
+            && !self.used.contains(param)
+        {
+            self.used.push(param);
+        }
+        visit::visit_type_path(self, type_path);
+    }
+}
+
+/// `where` predicates of the impl:
+/// container `#[rapira(bound = "..")]` replaces inference, otherwise
+/// each serialized field adds its `#[rapira(bound = "..")]`, `T: rapira::Rapira` for a
+/// field of a type parameter `T` or `Type: rapira::Rapira` for other types using type
+/// parameters (`Id<H>` with `H` only in `PhantomData` needs no `H: rapira::Rapira`;
+/// types containing the type itself add `T: rapira::Rapira` for their parameters),
+/// a field with `with` whose type uses type parameters adds
+/// `With: rapira::RapiraWith<Type>` (`fixed` and `endian` fields: the adapter of [`field_with`]),
+/// with `omit_if`/`only_if`
+/// and without `default` also `Type: Default`, with `max_len`/`non_empty` also
+/// `Type: rapira::LenPrefixed`, in `sparse` structs fields other than `Option` also
+/// `Type: Default + PartialEq`, `bits` fields add `Type: rapira::BitField` instead of
+/// `T: rapira::Rapira` (fields with `skip` add nothing)
+pub fn impl_bounds<'a>(
+    rapira: &Path,
+    name: &Ident,
+    generics: &Generics,
+    attrs: &[Attribute],
+    fields: impl Iterator<Item = &'a Field>,
+) -> syn::Result<Vec<WherePredicate>> {
+    if let Some(bound) = attributes::bound_attr(attrs)? {
+        return Ok(bound);
+    }
+
+    let params: Vec<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+    let sparse = attributes::sparse_attr(attrs)?;
+    let mut predicates = Vec::new();
+    // field types already bounded by `Type: rapira::Rapira`
+    let mut bounded: Vec<String> = Vec::new();
+    let mut visitor = UsedParams {
+        params: &params,
+        used: Vec::new(),
+    };
+
+    for field in fields {
+        if let Some(bound) = attributes::bound_attr(&field.attrs)? {
+            predicates.extend(bound);
+            continue;
+        }
+        if skip_attr(&field.attrs)? {
+            continue;
+        }
+
+        let ty = &field.ty;
+        let mut field_visitor = UsedParams {
+            params: &params,
+            used: Vec::new(),
+        };
+        field_visitor.visit_type(ty);
+        let generic = !field_visitor.used.is_empty();
+
+        if bits_attr(&field.attrs)?.is_some() {
+            if generic {
+                predicates.push(syn::parse_quote! { #ty: #rapira::BitField });
+            }
+            continue;
+        }
+        match field_with(rapira, field, attrs)? {
+            Some(with_attr) if generic => {
+                predicates.push(syn::parse_quote! { #with_attr: #rapira::RapiraWith<#ty> });
+            }
+            Some(_) => {}
+            None if generic => {
+                // the type the generated code reads: `Option<T>` of a sparse struct is
+                // written as `T`, a `count` field as its items
+                let read_ty = match sparse_option(field)? {
+                    Some(inner) if sparse => inner,
+                    _ if count_attr(&field.attrs)?.is_some() => {
+                        generic_inner(ty, "Vec").unwrap_or(ty)
+                    }
+                    _ => ty,
                 };
-                let type_param_bound = TypeParamBound::Trait(trait_bound);
-                let mut bounds = Punctuated::new();
-                bounds.push(type_param_bound);
-
-                let predicate = WherePredicate::Type(PredicateType {
-                    lifetimes: None,
-                    bounded_ty: ty,
-                    colon_token: Colon::default(),
-                    bounds,
-                });
-                Some(predicate)
+                // a recursive type (`Vec<Tree<T>>` in `Tree<T>`) can't bound itself
+                let mut recursive = UsedParams {
+                    params: &[name],
+                    used: Vec::new(),
+                };
+                recursive.visit_type(read_ty);
+                match read_ty {
+                    Type::Path(TypePath { qself: None, path })
+                        if path
+                            .get_ident()
+                            .is_some_and(|ident| params.contains(&ident)) =>
+                    {
+                        visitor.visit_type(read_ty);
+                    }
+                    _ if !recursive.used.is_empty() => visitor.visit_type(read_ty),
+                    _ => {
+                        let key = quote!(#read_ty).to_string();
+                        if !bounded.contains(&key) {
+                            bounded.push(key);
+                            predicates.push(syn::parse_quote! { #read_ty: #rapira::Rapira });
+                        }
+                    }
+                }
             }
-            _ => None,
-        })
+            None => {}
+        }
+        if generic
+            && !flag_condition_attr(&field.attrs)?.is_empty()
+            && default_attr(&field.attrs)?.is_none()
+        {
+            predicates.push(syn::parse_quote! { #ty: Default });
+        }
+        if generic && constraints_attr(&field.attrs)?.has_len() {
+            predicates.push(syn::parse_quote! { #ty: #rapira::LenPrefixed });
+        }
+        if generic && sparse && sparse_option(field)?.is_none() {
+            predicates.push(syn::parse_quote! { #ty: Default + PartialEq });
+        }
+    }
+
+    predicates.extend(visitor.used.iter().map(|ident| rapira_bound(rapira, ident)));
+    Ok(predicates)
+}
+
+/// `T` of an `Option<T>` field without `with`: in `#[rapira(sparse)]` structs it is present
+/// when `Some` and written as `T`, other fields are present when not `Default::default()`
+pub fn sparse_option(field: &Field) -> syn::Result<Option<&Type>> {
+    if extract_with_attr(&field.attrs)?.is_some() {
+        return Ok(None);
+    }
+    Ok(option_inner(&field.ty))
+}
+
+/// `T` of `Option<T>`, by the last path segment
+fn option_inner(typ: &Type) -> Option<&Type> {
+    generic_inner(typ, "Option")
+}
+
+/// `T` of `Wrapper<T>` with the last path segment `wrapper`
+fn generic_inner<'a>(typ: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(TypePath { qself: None, path }) = typ else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// adapter of a field: `#[rapira(with = ..)]`, `rapira::fixed_option` for `Option<T>` fields
+/// with `#[rapira(fixed)]` (on the field or on its container), or a `rapira::be` adapter
+/// for `#[rapira(endian = "big")]` (on the field or on its container)
+pub fn field_with(
+    rapira: &Path,
+    field: &Field,
+    container: &[Attribute],
+) -> syn::Result<Option<ExprPath>> {
+    if let Some(with_attr) = extract_with_attr(&field.attrs)? {
+        return Ok(Some(with_attr));
+    }
+    let fixed_field = attributes::fixed_attr(&field.attrs)?;
+    match option_inner(&field.ty) {
+        Some(_) if fixed_field || attributes::fixed_attr(container)? => {
+            return Ok(Some(syn::parse_quote! { #rapira::fixed_option }));
+        }
+        None if fixed_field => {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "#[rapira(fixed)] on a field needs an `Option<T>` type, enums are fixed by #[rapira(fixed)] on the enum",
+            ));
+        }
+        _ => {}
+    }
+
+    let endian_field = attributes::endian_attr(&field.attrs)?;
+    if !endian_field
+        .or(attributes::endian_attr(container)?)
+        .unwrap_or(false)
+    {
+        return Ok(None);
+    }
+    match be_adapter(rapira, &field.ty) {
+        Some(adapter) if adapter.prefixed && constraints_attr(&field.attrs)?.has_len() => {
+            Err(syn::Error::new_spanned(
+                &field.ty,
+                "#[rapira(max_len)] and #[rapira(non_empty)] need a little-endian length prefix, set #[rapira(endian = \"little\")] on the field",
+            ))
+        }
+        Some(adapter) => Ok(Some(adapter.path)),
+        None if endian_field.is_some() => Err(syn::Error::new_spanned(
+            &field.ty,
+            "#[rapira(endian = \"big\")] on a field needs an integer, float, `NonZeroU32`, `NonZeroU64`, `String` or `Vec<T>` type, other types set the endian on their own container",
+        )),
+        None => Ok(None),
+    }
+}
+
+/// codec of the items of a `#[rapira(count = field)]` field: their `rapira::be` adapter
+/// with `#[rapira(endian = "big")]` (on the field or on its container), otherwise `rapira::Rapira`
+pub fn count_codec(
+    rapira: &Path,
+    field: &Field,
+    container: &[Attribute],
+) -> syn::Result<TokenStream> {
+    let big = attributes::endian_attr(&field.attrs)?
+        .or(attributes::endian_attr(container)?)
+        .unwrap_or(false);
+    let item = big.then(|| generic_inner(&field.ty, "Vec")).flatten();
+    match item.and_then(|item| Some((item, be_adapter(rapira, item)?))) {
+        Some((item, adapter)) => Ok(field_codec(rapira, item, Some(&adapter.path))),
+        None => Ok(quote! { #rapira::Rapira }),
+    }
+}
+
+/// big-endian adapter from `rapira::be` of a field type
+struct BeAdapter {
+    path: ExprPath,
+    /// `String` and `Vec<T>`: the length prefix is big-endian
+    prefixed: bool,
+}
+
+/// `rapira::be` adapter by the last path segment of `typ`: integers, floats, `NonZeroU32`,
+/// `NonZeroU64`, `String`, `Vec<u8>` and `Vec<T>` (items by their own adapter or their own impl)
+fn be_adapter(rapira: &Path, typ: &Type) -> Option<BeAdapter> {
+    const SCALARS: &[&str] = &[
+        "u16",
+        "u32",
+        "u64",
+        "u128",
+        "usize",
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "isize",
+        "f32",
+        "f64",
+        "NonZeroU32",
+        "NonZeroU64",
+    ];
+    let Type::Path(TypePath { qself: None, path }) = typ else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    let ident = &segment.ident;
+    let (path, prefixed) = match &segment.arguments {
+        PathArguments::None if SCALARS.iter().any(|scalar| ident == scalar) => {
+            (syn::parse_quote! { #rapira::be::#ident }, false)
+        }
+        PathArguments::None if ident == "String" => {
+            (syn::parse_quote! { #rapira::be::string }, true)
+        }
+        PathArguments::AngleBracketed(_) => match generic_inner(typ, "Vec")? {
+            Type::Path(TypePath { qself: None, path }) if path.is_ident("u8") => {
+                (syn::parse_quote! { #rapira::be::bytes }, true)
+            }
+            item => {
+                let item = match be_adapter(rapira, item) {
+                    Some(item) => item.path,
+                    None => syn::parse_quote! { #rapira::be::plain },
+                };
+                (syn::parse_quote! { #rapira::be::vec::<#item> }, true)
+            }
+        },
+        _ => return None,
+    };
+    Some(BeAdapter { path, prefixed })
+}
+
+/// `<T as rapira::Rapira>` or `<With as rapira::RapiraWith<T>>` for `#[rapira(with = With)]`:
+/// both have the same associated items, the value is passed as the first argument
+pub fn field_codec(rapira: &Path, typ: &Type, with_attr: Option<&ExprPath>) -> TokenStream {
+    match with_attr {
+        Some(with_attr) => quote! { <#with_attr as #rapira::RapiraWith<#typ>> },
+        None => quote! { <#typ as #rapira::Rapira> },
+    }
+}
+
+/// `impl rapira::constraints::ConstraintSchema for Name`: constraints of the fields,
+/// labeled like `RapiraError::Validation` paths (`field`, `0`, `Variant.field`, `Variant.0`),
+/// `flatten` fields with the `CONSTRAINTS` of their type
+pub fn constraint_schema(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let rapira = attributes::crate_path(&ast.attrs)?;
+    let fields: Vec<(String, &Field)> = match &ast.data {
+        Data::Struct(data) => field_labels("", &data.fields),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| field_labels(&format!("{}.", variant.ident), &variant.fields))
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+    let mut items: Vec<TokenStream> = Vec::new();
+    let params: Vec<&Ident> = ast
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
         .collect();
+    let mut where_predicates: Vec<WherePredicate> = Vec::new();
+    for (label, field) in fields {
+        if flatten_attr(&field.attrs)? {
+            let typ = &field.ty;
+            let mut used = UsedParams {
+                params: &params,
+                used: Vec::new(),
+            };
+            used.visit_type(typ);
+            if !used.used.is_empty() {
+                where_predicates
+                    .push(syn::parse_quote! { #typ: #rapira::constraints::ConstraintSchema });
+            }
+            items.push(quote! {
+                #rapira::constraints::FieldConstraints {
+                    field: #label,
+                    max_len: None,
+                    non_empty: false,
+                    range: None,
+                    flatten: <#typ as #rapira::constraints::ConstraintSchema>::CONSTRAINTS,
+                },
+            });
+            continue;
+        }
+        let Constraints {
+            max_len,
+            non_empty,
+            range,
+        } = constraints_attr(&field.attrs)?;
+        if max_len.is_none() && !non_empty && range.is_none() {
+            continue;
+        }
+        let max_len = match max_len {
+            Some(max_len) => quote! { Some(#max_len) },
+            None => quote! { None },
+        };
+        let range = match range {
+            Some(range) => {
+                let range = quote!(#range).to_string().replace(' ', "");
+                quote! { Some(#range) }
+            }
+            None => quote! { None },
+        };
+        items.push(quote! {
+            #rapira::constraints::FieldConstraints {
+                field: #label,
+                max_len: #max_len,
+                non_empty: #non_empty,
+                range: #range,
+                flatten: &[],
+            },
+        });
+    }
+    let name = &ast.ident;
+    let mut generics = ast.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(where_predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #rapira::constraints::ConstraintSchema for #name #ty_generics #where_clause {
+            const CONSTRAINTS: &'static [#rapira::constraints::FieldConstraints] = &[#(#items)*];
+        }
+    })
+}
 
-    let where_clause = generics.make_where_clause();
-    where_clause.predicates.extend(predicates);
+/// assertions of a `#[rapira(endian = "big")]` container: a field without a `rapira::be`
+/// adapter (and `Vec` items without one) must not be `Rapira::LITTLE_ENDIAN`, so `Option<u32>`,
+/// `[u32; N]`, tuples and aliases of integers don't stay little-endian silently;
+/// fields of generic types are not checked
+pub fn endian_check(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    if !attributes::endian_attr(&ast.attrs)?.unwrap_or(false)
+        || attributes::proxy_attr(&ast.attrs)?.is_some()
+    {
+        return Ok(quote!());
+    }
+    let rapira = attributes::crate_path(&ast.attrs)?;
+    let fields: Vec<(String, &Field)> = match &ast.data {
+        Data::Struct(data) => field_labels("", &data.fields),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .filter(|variant| !unknown_attr(&variant.attrs).unwrap_or(false))
+            .flat_map(|variant| field_labels(&format!("{}.", variant.ident), &variant.fields))
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+    let params: Vec<&Ident> = ast
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    let mut checks = Vec::new();
+    for (label, field) in fields {
+        if skip_attr(&field.attrs)?
+            || bits_attr(&field.attrs)?.is_some()
+            || attributes::endian_attr(&field.attrs)? == Some(false)
+        {
+            continue;
+        }
+        let typ = match field_with(&rapira, field, &ast.attrs)? {
+            None => &field.ty,
+            Some(_) => match generic_inner(&field.ty, "Vec") {
+                // `Vec<u8>` is `rapira::be::bytes`
+                Some(Type::Path(TypePath { qself: None, path })) if path.is_ident("u8") => continue,
+                Some(item) if be_adapter(&rapira, item).is_none() => item,
+                _ => continue,
+            },
+        };
+        let mut visitor = UsedParams {
+            params: &params,
+            used: Vec::new(),
+        };
+        visitor.visit_type(typ);
+        if !visitor.used.is_empty() {
+            continue;
+        }
+        let msg = format!(
+            "field `{label}` of a #[rapira(endian = \"big\")] container has little-endian numbers and no `rapira::be` adapter, set #[rapira(endian = \"little\")] or #[rapira(with = ..)] on the field"
+        );
+        checks.push(quote_spanned! { typ.span()=>
+            const _: () = assert!(!<#typ as #rapira::Rapira>::LITTLE_ENDIAN, #msg);
+        });
+    }
+    Ok(quote! { #(#checks)* })
+}
 
+fn field_labels<'a>(prefix: &str, fields: &'a Fields) -> Vec<(String, &'a Field)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => (format!("{prefix}{ident}"), field),
+            None => (format!("{prefix}{idx}"), field),
+        })
+        .collect()
+}
+
+/// `impl rapira::layout::Flat for Name`, emitted for structs without own framing
+pub fn flat_impl(rapira: &Path, name: &Ident, generics: &Generics) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! { impl #impl_generics #rapira::layout::Flat for #name #ty_generics #where_clause {} }
+}
+
+/// a `#[rapira(flatten)]` field must be a struct without own framing: the bytes of
+/// its `Rapira` impl are then exactly its fields
+pub fn flatten_check(rapira: &Path, typ: &Type, flatten: bool) -> TokenStream {
+    if flatten {
+        quote_spanned! { typ.span()=> #rapira::layout::assert_flat::<#typ>(); }
+    } else {
+        quote!()
+    }
+}
+
+/// `debug_from_slice` read of one field: a `#[rapira(flatten)]` field logs its own fields
+/// by `debug_from_slice` of its type, as if declared in the parent, other fields use `log`
+pub fn debug_read(rapira: &Path, typ: &Type, flatten: bool, log: TokenStream) -> TokenStream {
+    if flatten {
+        quote! { <#typ as #rapira::Rapira>::debug_from_slice(__rapira_slice)? }
+    } else {
+        log
+    }
+}
+
+/// `#[rapira(validate = path)]` and constraints (`max_len`, `non_empty`, `range`) of a field
+/// and its name in `RapiraError::Validation` paths
+pub struct FieldCheck {
+    rapira: Path,
+    typ: Type,
+    field: LitStr,
+    validate: Option<ExprPath>,
+    constraints: Constraints,
+}
+
+impl FieldCheck {
+    pub fn new(rapira: &Path, field: &Field, name: String) -> syn::Result<Self> {
+        Ok(FieldCheck {
+            rapira: rapira.clone(),
+            typ: field.ty.clone(),
+            field: LitStr::new(&name, Span::call_site()),
+            validate: attributes::validate_attr(&field.attrs)?,
+            constraints: constraints_attr(&field.attrs)?,
+        })
+    }
+
+    /// `.map_err(..)` adding the field to the path of `RapiraError::Validation`
+    pub fn in_field(&self) -> TokenStream {
+        let field = &self.field;
+        quote! { .map_err(|err| err.in_field(#field)) }
+    }
+
+    /// `max_len`/`non_empty` statement checking the length prefix before decoding,
+    /// empty without them
+    pub fn len_check(&self) -> TokenStream {
+        if !self.constraints.has_len() {
+            return quote!();
+        }
+        let Self {
+            rapira,
+            typ,
+            constraints,
+            ..
+        } = self;
+        let non_empty = constraints.non_empty;
+        let max_len = match &constraints.max_len {
+            Some(max_len) => quote! { Some(#max_len) },
+            None => quote! { None },
+        };
+        let in_field = self.in_field();
+        quote! {
+            #rapira::constraints::check_len(
+                <#typ as #rapira::LenPrefixed>::peek_len(__rapira_slice)?,
+                #non_empty,
+                #max_len,
+            ) #in_field?;
+        }
+    }
+
+    /// decoding expression of the field: `read` after the length check
+    pub fn read(&self, read: TokenStream) -> TokenStream {
+        let len_check = self.len_check();
+        let in_field = self.in_field();
+        if len_check.is_empty() {
+            quote! { #read #in_field? }
+        } else {
+            quote! {{
+                #len_check
+                #read #in_field?
+            }}
+        }
+    }
+
+    /// `from_slice` expression of the field, `max_len` replaces the global capacity limits
+    pub fn read_slice(&self, codec: &TokenStream) -> TokenStream {
+        let Self { rapira, typ, .. } = self;
+        match &self.constraints.max_len {
+            Some(max_len) => self.read(
+                quote! { <#typ as #rapira::LenPrefixed>::from_slice_max_len(__rapira_slice, #max_len) },
+            ),
+            None => self.read(quote! { #codec::from_slice(__rapira_slice) }),
+        }
+    }
+
+    /// `from_slice_versioned` expression of the field, like [`Self::read_slice`]
+    pub fn read_versioned(&self, codec: &TokenStream) -> TokenStream {
+        self.read_decode(
+            quote! { Versioned(__rapira_version) },
+            quote! { #codec::from_slice_versioned(__rapira_slice, __rapira_version) },
+        )
+    }
+
+    /// `from_slice_ctx` expression of the field, like [`Self::read_slice`]
+    pub fn read_ctx(&self, codec: &TokenStream) -> TokenStream {
+        self.read_decode(
+            quote! { Ctx(__rapira_flags) },
+            quote! { #codec::from_slice_ctx(__rapira_slice, __rapira_flags) },
+        )
+    }
+
+    /// `read` of the field, with `max_len` by `LenPrefixed::decode_max_len` with `decode`
+    fn read_decode(&self, decode: TokenStream, read: TokenStream) -> TokenStream {
+        let Self { rapira, typ, .. } = self;
+        match &self.constraints.max_len {
+            Some(max_len) => self.read(quote! {
+                <#typ as #rapira::LenPrefixed>::decode_max_len(
+                    __rapira_slice,
+                    #max_len,
+                    #rapira::constraints::Decode::#decode,
+                )
+            }),
+            None => self.read(read),
+        }
+    }
+
+    /// validation statements of the decoded field `ident`, empty without `validate` and `range`
+    pub fn validate(&self, ident: &Ident) -> TokenStream {
+        let in_field = self.in_field();
+        let range = self.constraints.range.as_ref().map(|range| {
+            let rapira = &self.rapira;
+            quote! { #rapira::constraints::check_range(&#ident, #range) #in_field?; }
+        });
+        let validate = self
+            .validate
+            .as_ref()
+            .map(|validate| quote! { #validate(&#ident) #in_field?; });
+        quote! { #range #validate }
+    }
+
+    /// `check_bytes` (or `check_bytes_ctx`) statement, the field is decoded to be validated
+    pub fn check_bytes(&self, codec: &TokenStream, typ: &Type, ctx: bool) -> TokenStream {
+        if self.validate.is_some() || self.constraints.range.is_some() {
+            let read = if ctx {
+                self.read_ctx(codec)
+            } else {
+                self.read_slice(codec)
+            };
+            let validate = self.validate(&Ident::new("__rapira_field", Span::call_site()));
+            return quote! {
+                let __rapira_field: #typ = #read;
+                #validate
+            };
+        }
+        let check = if ctx {
+            quote! { check_bytes_ctx(__rapira_slice, __rapira_flags) }
+        } else {
+            quote! { check_bytes(__rapira_slice) }
+        };
+        let check = self.read(quote! { #codec::#check });
+        quote! { #check; }
+    }
+}
+
+/// body of a decoding method followed by the container `#[rapira(validate = path)]`
+pub fn validated(rapira: &Path, validate: Option<&ExprPath>, body: TokenStream) -> TokenStream {
+    match validate {
+        Some(validate) => quote! {
+            #[allow(clippy::redundant_closure_call)]
+            let __rapira_value = (|| -> #rapira::Result<Self> { #body })()?;
+            #validate(&__rapira_value)?;
+            Ok(__rapira_value)
+        },
+        None => body,
+    }
+}
+
+/// `bool` expression: field with `#[rapira(omit_if = FLAG)]`/`#[rapira(only_if = FLAG)]`
+/// is present with `flags`, `None` for unconditional fields
+pub fn flag_present(condition: &FlagCondition, flags: &TokenStream) -> Option<TokenStream> {
+    let only_if = condition
+        .only_if
+        .as_ref()
+        .map(|flag| quote! { #flags.has(#flag) });
+    let omit_if = condition
+        .omit_if
+        .as_ref()
+        .map(|flag| quote! { !#flags.has(#flag) });
+    and_conditions(only_if, omit_if)
+}
+
+/// `(a) && (b)`, either of them when the other one is `None`
+pub fn and_conditions(a: Option<TokenStream>, b: Option<TokenStream>) -> Option<TokenStream> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(quote! { (#a) && (#b) }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// value of an absent field: `#[rapira(default = expr)]`, `#[rapira(default_with = path)]`
+/// or `Default::default()`
+pub fn default_value(attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    Ok(match default_attr(attrs)? {
+        Some(default) => quote! { #default },
+        None => quote! { Default::default() },
+    })
+}
+
+/// `let ident: typ = read;`, `default` when `present` is false
+pub fn read_if(
+    present: Option<&TokenStream>,
+    ident: &Ident,
+    typ: &Type,
+    read: TokenStream,
+    default: &TokenStream,
+) -> TokenStream {
+    match present {
+        Some(present) => quote! {
+            let #ident: #typ = if #present {
+                #read
+            } else {
+                #default
+            };
+        },
+        None => quote! {
+            let #ident: #typ = #read;
+        },
+    }
+}
+
+/// `write` statements, skipped when `present` is false
+pub fn write_if(present: Option<&TokenStream>, write: TokenStream) -> TokenStream {
+    match present {
+        Some(present) => quote! {
+            if #present {
+                #write
+            }
+        },
+        None => write,
+    }
+}
+
+/// `+ size` summand, `0` when `present` is false
+pub fn size_if(present: Option<&TokenStream>, size: TokenStream) -> TokenStream {
+    match present {
+        Some(present) => quote! { + if #present { #size } else { 0 } },
+        None => quote! { + #size },
+    }
+}
+
+/// `from_slice_versioned` statement for one field decoded by `codec` (see [`field_codec`]):
+/// field with `since` is read only when `__rapira_version >= since` (and `present`),
+/// otherwise `default` is used
+pub fn versioned_field(
+    codec: &TokenStream,
+    ident: &Ident,
+    typ: &Type,
+    since: Option<u16>,
+    present: Option<&TokenStream>,
+    default: &TokenStream,
+    check: Option<&FieldCheck>,
+) -> TokenStream {
+    let read = match check {
+        Some(check) => check.read_versioned(codec),
+        None => quote! { #codec::from_slice_versioned(__rapira_slice, __rapira_version)? },
+    };
+    let present = and_conditions(
+        since.map(|since| quote! { __rapira_version >= #since }),
+        present.cloned(),
+    );
+    read_if(present.as_ref(), ident, typ, read, default)
+}
+
+/// prelude of `from_slice_versioned`, `size_versioned` and `convert_to_bytes_versioned`
+pub struct MigratePrelude {
+    pub read: TokenStream,
+    pub size: TokenStream,
+    pub write: TokenStream,
+}
+
+/// Versioned methods prelude: data of versions `<= from` is decoded
+/// as the legacy `via` type (which handles older versions itself, so hops chain)
+/// and converted by `with`, written after conversion by `downgrade`.
+/// Migration with the smallest `from` wins.
+pub fn migrate_prelude(
+    rapira: &Path,
+    migrations: &[Migration],
+    version: Option<u16>,
+    kind: &str,
+    name: &Ident,
+) -> syn::Result<MigratePrelude> {
+    let mut errors = Errors::default();
+    let mut read: Vec<TokenStream> = Vec::with_capacity(migrations.len());
+    let mut size: Vec<TokenStream> = Vec::with_capacity(migrations.len());
+    let mut write: Vec<TokenStream> = Vec::with_capacity(migrations.len());
+    let mut prev: Option<u16> = None;
+
+    for Migration {
+        from,
+        via,
+        with,
+        fallible,
+        downgrade,
+        span,
+    } in migrations
+    {
+        let mut error = |msg: String| errors.push(syn::Error::new(*span, msg));
+        match version {
+            None => error(format!(
+                "migrate from {from}: {kind} `{name}` is missing #[rapira(version = N)]"
+            )),
+            Some(_) if *from == 0 => {
+                error("migrate: from = 0 is invalid, versions start at 1".to_owned())
+            }
+            Some(version) if *from >= version => error(format!(
+                "migrate from {from}: must be less than {kind} version {version}"
+            )),
+            _ => {}
+        }
+        if prev == Some(*from) {
+            error(format!("migrate from {from}: declared more than once"));
+        }
+        prev = Some(*from);
+
+        let convert = if *fallible {
+            quote! { #with(__rapira_legacy) }
+        } else {
+            quote! { Ok(#with(__rapira_legacy)) }
+        };
+
+        read.push(quote! {
+            if __rapira_version <= #from {
+                let __rapira_legacy = <#via as #rapira::Rapira>::from_slice_versioned(__rapira_slice, __rapira_version)?;
+                return #convert;
+            }
+        });
+
+        match downgrade {
+            Some(downgrade) => {
+                size.push(quote! {
+                    if __rapira_version <= #from {
+                        let __rapira_legacy: #via = #downgrade(self);
+                        return <#via as #rapira::Rapira>::size_versioned(&__rapira_legacy, __rapira_version);
+                    }
+                });
+                write.push(quote! {
+                    if __rapira_version <= #from {
+                        let __rapira_legacy: #via = #downgrade(self);
+                        <#via as #rapira::Rapira>::convert_to_bytes_versioned(&__rapira_legacy, __rapira_slice, __rapira_cursor, __rapira_version);
+                        return;
+                    }
+                });
+            }
+            None => {
+                let no_downgrade = quote! {
+                    if __rapira_version <= #from {
+                        panic!(
+                            "{} can't be written at version {}: migrate(from = {}) has no downgrade",
+                            stringify!(#name),
+                            __rapira_version,
+                            #from,
+                        );
+                    }
+                };
+                size.push(no_downgrade.clone());
+                write.push(no_downgrade);
+            }
+        }
+    }
+
+    errors.finish()?;
+    Ok(MigratePrelude {
+        read: quote! { #(#read)* },
+        size: quote! { #(#size)* },
+        write: quote! { #(#write)* },
+    })
+}
+
+/// `size_versioned` summand and `convert_to_bytes_versioned` statement
+/// for one field (`value` is a reference to it):
+/// field with `since` is omitted when `__rapira_version < since` (or not `present`)
+pub fn versioned_field_write(
+    rapira: &Path,
+    value: &TokenStream,
+    typ: &Type,
+    with_attr: Option<&ExprPath>,
+    since: Option<u16>,
+    present: Option<&TokenStream>,
+) -> (TokenStream, TokenStream) {
+    let codec = field_codec(rapira, typ, with_attr);
+    let size = quote! { #codec::size_versioned(#value, __rapira_version) };
+    let write = quote! {
+        #codec::convert_to_bytes_versioned(#value, __rapira_slice, __rapira_cursor, __rapira_version);
+    };
+    let present = and_conditions(
+        since.map(|since| quote! { __rapira_version >= #since }),
+        present.cloned(),
+    );
+    (
+        size_if(present.as_ref(), size),
+        write_if(present.as_ref(), write),
+    )
+}
+
+/// `(read check, write check)` for an enum variant added in version `since`:
+/// reading older data fails, writing at an older version panics
+pub fn variant_since_checks(
+    rapira: &Path,
+    since: Option<u16>,
+    name: &Ident,
+    variant_name: &Ident,
+) -> (TokenStream, TokenStream) {
+    let Some(since) = since else {
+        return (quote!(), quote!());
+    };
+
+    (
+        quote! {
+            if __rapira_version < #since {
+                return Err(#rapira::RapiraError::EnumVariant);
+            }
+        },
+        quote! {
+            if __rapira_version < #since {
+                panic!(
+                    "{}::{} can't be written at version {}: added in version {}",
+                    stringify!(#name),
+                    stringify!(#variant_name),
+                    __rapira_version,
+                    #since,
+                );
+            }
+        },
+    )
+}
+
+/// byte width of the enum tag type (`u8`, `u16`, `u32`)
+pub fn tag_size(tag: &Ident) -> usize {
+    match tag.to_string().as_str() {
+        "u8" => 1,
+        "u16" => 2,
+        _ => 4,
+    }
+}
+
+/// expression reading the enum tag, `method` is `from_slice` or `from_slice_unsafe`,
+/// `big` for `#[rapira(endian = "big")]` enums
+pub fn tag_read(rapira: &Path, tag: &Ident, method: &str, big: bool) -> TokenStream {
+    let method = Ident::new(method, proc_macro2::Span::call_site());
+    if tag == "u8" {
+        quote! { #rapira::byte_rapira::#method(__rapira_slice)? }
+    } else if big {
+        quote! { <#rapira::be::#tag as #rapira::RapiraWith<#tag>>::#method(__rapira_slice)? }
+    } else {
+        quote! { <#tag as #rapira::Rapira>::#method(__rapira_slice)? }
+    }
+}
+
+/// statement writing the enum tag `value` (of type `tag`)
+pub fn tag_write(rapira: &Path, tag: &Ident, value: &TokenStream, big: bool) -> TokenStream {
+    if tag == "u8" {
+        quote! { #rapira::push(__rapira_slice, __rapira_cursor, #value); }
+    } else if big {
+        quote! {
+            <#rapira::be::#tag as #rapira::RapiraWith<#tag>>::convert_to_bytes(&#value, __rapira_slice, __rapira_cursor);
+        }
+    } else {
+        quote! { #rapira::Rapira::convert_to_bytes(&#value, __rapira_slice, __rapira_cursor); }
+    }
+}
+
+/// variant tags of an enum: `#[idx = N]` or the variant position,
+/// must fit the tag type and be unique
+pub fn variant_tags<'a>(
+    variants: impl Iterator<Item = (&'a Ident, Option<u32>)>,
+    tag: &Ident,
+    name: &Ident,
+) -> syn::Result<Vec<u32>> {
+    let tags: Vec<(&Ident, u64)> = variants
+        .enumerate()
+        .map(|(pos, (variant, idx))| (variant, idx.unwrap_or(pos as u32) as u64))
+        .collect();
+    check_tags(&tags, tag, name)?;
+    Ok(tags.into_iter().map(|(_, id)| id as u32).collect())
+}
+
+/// error if a tag doesn't fit the tag type or two variants share a tag
+pub fn check_tags(tags: &[(&Ident, u64)], tag: &Ident, name: &Ident) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    let max = match tag.to_string().as_str() {
+        "u8" => u8::MAX as u64,
+        "u16" => u16::MAX as u64,
+        _ => u32::MAX as u64,
+    };
+    for (i, (variant, id)) in tags.iter().enumerate() {
+        if *id > max {
+            errors.push(syn::Error::new(
+                variant.span(),
+                format!(
+                    "variant `{name}::{variant}`: tag {id} doesn't fit in `{tag}`, use #[rapira(tag = u16)] or #[rapira(tag = u32)]"
+                ),
+            ));
+        }
+        if let Some((other, _)) = tags[..i].iter().find(|(_, other_id)| other_id == id) {
+            errors.push(syn::Error::new(
+                variant.span(),
+                format!(
+                    "variants `{name}::{other}` and `{name}::{variant}` have the same tag {id}"
+                ),
+            ));
+        }
+    }
+    errors.finish()
+}
 
-    quote! { impl #impl_generics rapira::Rapira for #name #ty_generics #where_clause }
+/// tag literal with the tag type suffix: `3u16`
+pub fn tag_lit(id: impl core::fmt::Display, tag: &Ident) -> syn::LitInt {
+    syn::LitInt::new(&format!("{id}{tag}"), proc_macro2::Span::call_site())
 }
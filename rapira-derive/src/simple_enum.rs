@@ -2,64 +2,221 @@ extern crate proc_macro2;
 extern crate quote;
 extern crate syn;
 
-use proc_macro2::Ident;
-use quote::quote;
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{Attribute, DataEnum, Expr, ExprLit, Lit};
 
-pub fn simple_enum_serializer(name: &Ident) -> proc_macro::TokenStream {
-    proc_macro::TokenStream::from(quote! {
-        impl rapira::Rapira for #name {
-            const STATIC_SIZE: Option<usize> = Some(1);
-            const MIN_SIZE: usize = 1;
+use crate::{
+    attributes,
+    field_attrs::{extract_idx_attr, other_attr},
+    shared::{check_tags, max_cap_const, tag_lit, tag_read, tag_size, tag_write},
+};
+
+pub fn simple_enum_serializer(
+    data_enum: &DataEnum,
+    name: &Ident,
+    attrs: &[Attribute],
+) -> syn::Result<TokenStream> {
+    let rapira = &attributes::crate_path(attrs)?;
+    let tag = attributes::simple_enum_tag(attrs)?;
+    let tag_size = tag_size(&tag);
+    let big = attributes::endian_attr(attrs)?.unwrap_or(false);
+    let read_tag = tag_read(rapira, &tag, "from_slice", big);
+    let read_tag_unsafe = tag_read(rapira, &tag, "from_slice_unsafe", big);
+    let write_tag = tag_write(rapira, &tag, &quote! { val }, big);
+
+    let mut read_arms: Vec<TokenStream> = Vec::with_capacity(data_enum.variants.len());
+    let mut write_arms: Vec<TokenStream> = Vec::with_capacity(data_enum.variants.len());
+    // tags known at compile time, checked for range and uniqueness
+    let mut known_tags: Vec<(&Ident, u64)> = Vec::with_capacity(data_enum.variants.len());
+    // every tag as `i128`, a non-literal discriminant by the `as` cast
+    let mut tag_values: Vec<(&Ident, Option<u64>)> = Vec::with_capacity(data_enum.variants.len());
+    let mut other: Option<&Ident> = None;
+    // implicit discriminant of the next variant, `None` after a non-literal discriminant
+    let mut next_discriminant: Option<u64> = Some(0);
+
+    for variant in &data_enum.variants {
+        let ident = &variant.ident;
+
+        if other_attr(&variant.attrs)? {
+            if let Some(other) = other {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "variants `{name}::{other}` and `{name}::{ident}` are both #[rapira(other)]"
+                    ),
+                ));
+            }
+            other = Some(ident);
+        }
+
+        let discriminant = match &variant.discriminant {
+            Some((
+                _,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(i), ..
+                }),
+            )) => i.base10_parse::<u64>().ok(),
+            Some(_) => None,
+            None => next_discriminant,
+        };
+        next_discriminant = discriminant.map(|d| d + 1);
+
+        // `#[idx = N]` overrides the discriminant
+        let value = extract_idx_attr(&variant.attrs)?
+            .map(u64::from)
+            .or(discriminant);
+        tag_values.push((ident, value));
+
+        match value {
+            Some(value) => {
+                known_tags.push((ident, value));
+                let lit = tag_lit(value, &tag);
+                read_arms.push(quote! { #lit => Ok(#name::#ident), });
+                write_arms.push(quote! { #name::#ident => #lit, });
+            }
+            None => {
+                read_arms.push(quote! { v if v == #name::#ident as #tag => Ok(#name::#ident), });
+                write_arms.push(quote! { #name::#ident => #name::#ident as #tag, });
+            }
+        }
+    }
+
+    check_tags(&known_tags, &tag, name)?;
+    let tag_checks = discriminant_checks(&tag_values, &tag, name);
+
+    let unknown = match other {
+        Some(other) => quote! { Ok(#name::#other) },
+        None => quote! { Err(#rapira::RapiraError::EnumVariant) },
+    };
+    let from_tag = quote! {
+        match val {
+            #(#read_arms)*
+            _ => #unknown,
+        }
+    };
+    let to_tag = quote! {
+        let val: #tag = match self {
+            #(#write_arms)*
+        };
+    };
+
+    let max_cap = max_cap_const(attrs)?;
+    Ok(quote! {
+        impl #rapira::Rapira for #name {
+            const STATIC_SIZE: Option<usize> = Some(#tag_size);
+            const MIN_SIZE: usize = #tag_size;
+            #max_cap
 
             #[inline]
-            fn from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                <Self as core::convert::TryFrom<u8>>::try_from(val).map_err(|_| rapira::RapiraError::EnumVariant)
+                let val: #tag = #read_tag;
+                #from_tag
             }
 
             #[inline]
-            fn check_bytes(__rapira_slice: &mut &[u8]) -> rapira::Result<()>
+            fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                <Self as core::convert::TryFrom<u8>>::try_from(val).map_err(|_| rapira::RapiraError::EnumVariant)?;
+                let val: #tag = #read_tag;
+                let res: #rapira::Result<Self> = #from_tag;
+                res?;
                 Ok(())
             }
 
             #[inline]
-            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice(__rapira_slice)?;
-                <Self as core::convert::TryFrom<u8>>::try_from(val).map_err(|_| rapira::RapiraError::EnumVariant)
+                let val: #tag = #read_tag;
+                #from_tag
             }
 
             #[inline]
-            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
             where
                 Self: Sized,
             {
-                let val: u8 = rapira::byte_rapira::from_slice_unsafe(__rapira_slice)?;
-                <Self as core::convert::TryFrom<u8>>::try_from(val).map_err(|_| rapira::RapiraError::EnumVariant)
+                let val: #tag = #read_tag_unsafe;
+                #from_tag
             }
 
             #[inline]
-            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> rapira::Result<()> {
-                rapira::push(__rapira_slice, __rapira_cursor, *self as u8);
+            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
+                #to_tag
+                #write_tag
                 Ok(())
             }
 
             #[inline]
             fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {
-                rapira::push(__rapira_slice, __rapira_cursor, *self as u8);
+                #to_tag
+                #write_tag
+            }
+            #[inline]
+            fn size(&self) -> usize { #tag_size }
+        }
+
+        #tag_checks
+
+        impl #rapira::BitField for #name {
+            #[inline]
+            fn to_bits(&self) -> u64 {
+                #to_tag
+                val as u64
             }
+
             #[inline]
-            fn size(&self) -> usize { 1 }
+            fn from_bits(bits: u64) -> Option<Self> {
+                let val = <#tag>::try_from(bits).ok()?;
+                let res: #rapira::Result<Self> = #from_tag;
+                res.ok()
+            }
         }
     })
 }
+
+/// const assertions for the variants without a literal tag (`A = -1`, `B = OFFSET + 1`):
+/// the cast discriminant fits the tag type and differs from the other tags,
+/// literal tags are checked by [`check_tags`]
+fn discriminant_checks(tags: &[(&Ident, Option<u64>)], tag: &Ident, name: &Ident) -> TokenStream {
+    let value = |ident: &Ident, value: Option<u64>| match value {
+        Some(value) => quote! { #value as i128 },
+        None => quote! { #name::#ident as i128 },
+    };
+    let mut checks = Vec::new();
+    for (i, (ident, id)) in tags.iter().enumerate() {
+        if id.is_some() {
+            continue;
+        }
+        let this = value(ident, None);
+        let msg = format!("variant `{name}::{ident}`: discriminant doesn't fit in `{tag}`");
+        checks.push(quote_spanned! { ident.span()=>
+            assert!(#this >= 0 && #this <= #tag::MAX as i128, #msg);
+        });
+        for (j, (other, other_id)) in tags.iter().enumerate() {
+            // pairs of non-literal tags once
+            if i == j || (other_id.is_none() && j < i) {
+                continue;
+            }
+            let that = value(other, *other_id);
+            let msg = format!("variants `{name}::{other}` and `{name}::{ident}` have the same tag");
+            checks.push(quote_spanned! { ident.span()=>
+                assert!(#this != #that, #msg);
+            });
+        }
+    }
+    if checks.is_empty() {
+        return quote!();
+    }
+    quote! {
+        const _: () = {
+            #(#checks)*
+        };
+    }
+}
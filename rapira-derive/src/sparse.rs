@@ -0,0 +1,550 @@
+extern crate proc_macro2;
+extern crate quote;
+extern crate syn;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, DataStruct, Field, Generics, Member, Path, Type};
+
+use crate::{
+    attributes,
+    field_attrs::{
+        align_attr, bits_attr, constraints_attr, count_attr, extract_idx_attr, extract_since_attr,
+        extract_with_attr, flag_condition_attr, flatten_attr, if_attr, pad_attr, rest_attr,
+        skip_attr,
+    },
+    shared::{
+        FieldCheck, MigratePrelude, build_ident, default_value, field_codec, impl_bounds,
+        max_cap_const, migrate_prelude, sparse_option, validated,
+    },
+};
+
+/// serialized field of a sparse struct
+struct SparseField<'a> {
+    member: Member,
+    /// local binding of the decoded value
+    binding: Ident,
+    typ: &'a Type,
+    /// `T` of `Option<T>` fields, written without the `Option` tag
+    inner: Option<&'a Type>,
+    /// codec of the written value: of `T` for `Option<T>` fields
+    codec: TokenStream,
+    since: Option<u16>,
+    /// value of the field absent by version
+    default: TokenStream,
+    check: FieldCheck,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Plain,
+    Versioned,
+    Ctx,
+    Unchecked,
+    Unsafe,
+}
+
+/// `#[rapira(sparse)]`: a presence bitmap (field `i` in bit `i % 8` of byte `i / 8`, unused
+/// bits zero) followed by the present fields only: `Option<T>` fields are present when `Some`
+/// and written as `T`, other fields when not `Default::default()`; absent fields decode as
+/// `None`/`Default::default()`. Fields with `since` take a bit only at versions `>= since`
+pub fn sparse_serializer(
+    data_struct: &DataStruct,
+    name: &Ident,
+    generics: Generics,
+    attrs: &[Attribute],
+) -> syn::Result<TokenStream> {
+    let rapira = &attributes::crate_path(attrs)?;
+    let validate = attributes::validate_attr(attrs)?;
+    let MigratePrelude {
+        read: migrations_read,
+        size: migrations_size,
+        write: migrations_write,
+    } = migrate_prelude(
+        rapira,
+        &attributes::migrate_attrs(attrs)?,
+        attributes::version_attr(attrs)?,
+        "struct",
+        name,
+    )?;
+
+    for (key, unsupported) in [
+        ("debug", attributes::debug_attr(attrs)?),
+        ("retired", !attributes::retired_attrs(attrs)?.is_empty()),
+        ("fixed", attributes::fixed_attr(attrs)?),
+        ("magic", attributes::magic_attr(attrs)?.is_some()),
+        ("endian", attributes::endian_attr(attrs)?.is_some()),
+        ("extensible", attributes::extensible_attr(attrs)?),
+    ] {
+        if unsupported {
+            return Err(syn::Error::new_spanned(
+                name,
+                format!(
+                    "struct `{name}`: #[rapira({key})] cannot be combined with #[rapira(sparse)]"
+                ),
+            ));
+        }
+    }
+
+    let mut fields: Vec<(u32, SparseField)> = Vec::new();
+    // `#[rapira(skip)]` fields are built from their default value
+    let mut skipped: Vec<TokenStream> = Vec::new();
+    let mut seq = 0u32;
+    for (idx, field) in data_struct.fields.iter().enumerate() {
+        let (member, label) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+            None => (Member::Unnamed(idx.into()), idx.to_string()),
+        };
+        if skip_attr(&field.attrs)? {
+            let default = default_value(&field.attrs)?;
+            skipped.push(quote! { #member: #default, });
+            continue;
+        }
+        check_sparse_field(field, name, &label)?;
+
+        let field_idx = extract_idx_attr(&field.attrs)?.unwrap_or_else(|| {
+            let current_seq = seq;
+            seq += 1;
+            current_seq
+        });
+        let binding = match &field.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("__rapira_field_{idx}"),
+        };
+        let inner = sparse_option(field)?;
+        let codec = match inner {
+            Some(inner) => field_codec(rapira, inner, None),
+            None => field_codec(rapira, &field.ty, extract_with_attr(&field.attrs)?.as_ref()),
+        };
+        let check = FieldCheck::new(rapira, field, label)?;
+        fields.push((
+            field_idx,
+            SparseField {
+                member,
+                binding,
+                typ: &field.ty,
+                inner,
+                codec,
+                since: extract_since_attr(&field.attrs)?,
+                default: default_value(&field.attrs)?,
+                check,
+            },
+        ));
+    }
+    fields.sort_by_key(|(idx, _)| *idx);
+    let fields: Vec<SparseField> = fields.into_iter().map(|(_, field)| field).collect();
+
+    let all = fields.len();
+    let presence_bytes = all.div_ceil(8);
+    let min_fields = fields.iter().filter(|field| field.since.is_none()).count();
+    let versioned_fields = fields.iter().filter_map(|field| field.since).map(|since| {
+        quote! { + (__rapira_version >= #since) as usize }
+    });
+    let versioned_count = quote! { #min_fields #(#versioned_fields)* };
+    let count = quote! { #all };
+
+    let build = {
+        let members = fields.iter().map(
+            |SparseField {
+                 member, binding, ..
+             }| {
+                quote! { #member: #binding, }
+            },
+        );
+        quote! {
+            Ok(#name {
+                #(#members)*
+                #(#skipped)*
+            })
+        }
+    };
+    let read = |mode: Mode| {
+        let count = match mode {
+            Mode::Versioned => &versioned_count,
+            _ => &count,
+        };
+        let reads = fields.iter().map(|field| read_field(rapira, field, mode));
+        quote! {
+            let __rapira_presence = #rapira::bits::read_presence(__rapira_slice, #count)?;
+            let mut __rapira_bit = 0usize;
+            #(#reads)*
+            #build
+        }
+    };
+    let write = |mode: Mode, try_write: bool| {
+        let values = values(&fields, mode);
+        let bits = fields.iter().enumerate().map(|(idx, field)| {
+            let value = format_ident!("__rapira_value_{idx}");
+            let set = quote! {
+                #rapira::bits::set_next(&mut __rapira_presence, &mut __rapira_bit, #value.is_some());
+            };
+            match field.since {
+                Some(since) if mode == Mode::Versioned => {
+                    quote! { if __rapira_version >= #since { #set } }
+                }
+                _ => set,
+            }
+        });
+        let presence = quote! { &__rapira_presence[..#rapira::bits::presence_size(__rapira_bit)] };
+        let extend = if try_write {
+            quote! { #rapira::try_extend(__rapira_slice, __rapira_cursor, #presence)?; }
+        } else {
+            quote! { #rapira::extend(__rapira_slice, __rapira_cursor, #presence); }
+        };
+        let writes = fields.iter().enumerate().map(|(idx, SparseField { codec, .. })| {
+            let value = format_ident!("__rapira_value_{idx}");
+            let write = match mode {
+                Mode::Versioned => quote! {
+                    #codec::convert_to_bytes_versioned(__rapira_value, __rapira_slice, __rapira_cursor, __rapira_version);
+                },
+                Mode::Ctx => quote! {
+                    #codec::convert_to_bytes_ctx(__rapira_value, __rapira_slice, __rapira_cursor, __rapira_flags);
+                },
+                _ if try_write => quote! {
+                    #codec::try_convert_to_bytes(__rapira_value, __rapira_slice, __rapira_cursor)?;
+                },
+                _ => quote! {
+                    #codec::convert_to_bytes(__rapira_value, __rapira_slice, __rapira_cursor);
+                },
+            };
+            quote! {
+                if let Some(__rapira_value) = #value {
+                    #write
+                }
+            }
+        });
+        quote! {
+            #values
+            let mut __rapira_presence = [0u8; #presence_bytes];
+            let mut __rapira_bit = 0usize;
+            #(#bits)*
+            #extend
+            #(#writes)*
+        }
+    };
+    let size = |mode: Mode| {
+        let values = values(&fields, mode);
+        let count = match mode {
+            Mode::Versioned => &versioned_count,
+            _ => &count,
+        };
+        let sizes = fields
+            .iter()
+            .enumerate()
+            .map(|(idx, SparseField { codec, .. })| {
+                let value = format_ident!("__rapira_value_{idx}");
+                let size = match mode {
+                    Mode::Versioned => {
+                        quote! { #codec::size_versioned(__rapira_value, __rapira_version) }
+                    }
+                    Mode::Ctx => quote! { #codec::size_ctx(__rapira_value, __rapira_flags) },
+                    _ => quote! { #codec::size(__rapira_value) },
+                };
+                quote! {
+                    + match #value {
+                        Some(__rapira_value) => match #codec::STATIC_SIZE {
+                            Some(s) => s,
+                            None => #size,
+                        },
+                        None => 0,
+                    }
+                }
+            });
+        quote! {
+            #values
+            #rapira::bits::presence_size(#count) #(#sizes)*
+        }
+    };
+    // validated fields are checked by decoding
+    let decode_check = validate.is_some()
+        || data_struct
+            .fields
+            .iter()
+            .filter(|field| !skip_attr(&field.attrs).unwrap_or(true))
+            .any(|field| {
+                attributes::validate_attr(&field.attrs).is_ok_and(|validate| validate.is_some())
+                    || constraints_attr(&field.attrs).is_ok_and(|constraints| {
+                        constraints.has_len() || constraints.range.is_some()
+                    })
+            });
+    let check_bytes = |ctx: bool| {
+        if decode_check {
+            return if ctx {
+                quote! { Self::from_slice_ctx(__rapira_slice, __rapira_flags).map(|_| ()) }
+            } else {
+                quote! { Self::from_slice(__rapira_slice).map(|_| ()) }
+            };
+        }
+        let checks = fields.iter().map(
+            |SparseField {
+                 codec, typ, check, ..
+             }| {
+                let check = check.check_bytes(codec, typ, ctx);
+                quote! {
+                    if #rapira::bits::next_present(__rapira_presence, &mut __rapira_bit) {
+                        #check
+                    }
+                }
+            },
+        );
+        quote! {
+            let __rapira_presence = #rapira::bits::read_presence(__rapira_slice, #count)?;
+            let mut __rapira_bit = 0usize;
+            #(#checks)*
+            Ok(())
+        }
+    };
+
+    let from_slice = validated(rapira, validate.as_ref(), read(Mode::Plain));
+    let from_slice_versioned = {
+        let read_versioned = read(Mode::Versioned);
+        validated(
+            rapira,
+            validate.as_ref(),
+            quote! {
+                #migrations_read
+                #read_versioned
+            },
+        )
+    };
+    let from_slice_ctx = validated(rapira, validate.as_ref(), read(Mode::Ctx));
+    let from_slice_unchecked = read(Mode::Unchecked);
+    let from_slice_unsafe = read(Mode::Unsafe);
+    let convert_to_bytes = write(Mode::Plain, false);
+    let try_convert_to_bytes = write(Mode::Plain, true);
+    let convert_to_bytes_versioned = write(Mode::Versioned, false);
+    let convert_to_bytes_ctx = write(Mode::Ctx, false);
+    let size_plain = size(Mode::Plain);
+    let size_versioned = size(Mode::Versioned);
+    let size_ctx = size(Mode::Ctx);
+    let check_bytes_plain = check_bytes(false);
+    let check_bytes_ctx = check_bytes(true);
+
+    let bounds = impl_bounds(rapira, name, &generics, attrs, data_struct.fields.iter())?;
+    let name_with_generics = build_ident(rapira, name, generics, bounds);
+
+    let max_cap = max_cap_const(attrs)?;
+    Ok(quote! {
+        #name_with_generics {
+            const MIN_SIZE: usize = #rapira::bits::presence_size(#min_fields);
+            #max_cap
+
+            #[inline]
+            fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                #from_slice
+            }
+
+            #[inline]
+            fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u16) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                #from_slice_versioned
+            }
+
+            #[inline]
+            fn convert_to_bytes_versioned(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_version: u16) {
+                #migrations_write
+                #convert_to_bytes_versioned
+            }
+
+            #[inline]
+            fn size_versioned(&self, __rapira_version: u16) -> usize {
+                #migrations_size
+                #size_versioned
+            }
+
+            #[inline]
+            fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()>
+            where
+                Self: Sized,
+            {
+                #check_bytes_plain
+            }
+
+            #[inline]
+            unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                #from_slice_unchecked
+            }
+
+            #[inline]
+            unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                #from_slice_unsafe
+            }
+
+            #[inline]
+            fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
+                #try_convert_to_bytes
+                Ok(())
+            }
+
+            #[inline]
+            fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {
+                #convert_to_bytes
+            }
+
+            #[inline]
+            fn size(&self) -> usize {
+                #size_plain
+            }
+
+            #[inline]
+            fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: #rapira::RapiraFlags) {
+                #convert_to_bytes_ctx
+            }
+
+            #[inline]
+            fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<Self>
+            where
+                Self: Sized,
+            {
+                #from_slice_ctx
+            }
+
+            #[inline]
+            fn size_ctx(&self, __rapira_flags: #rapira::RapiraFlags) -> usize {
+                #size_ctx
+            }
+
+            #[inline]
+            fn check_bytes_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<()>
+            where
+                Self: Sized,
+            {
+                #check_bytes_ctx
+            }
+        }
+    })
+}
+
+/// field keys without meaning in sparse structs
+fn check_sparse_field(field: &Field, name: &Ident, label: &str) -> syn::Result<()> {
+    let unsupported = if !flag_condition_attr(&field.attrs)?.is_empty() {
+        Some("omit_if/only_if")
+    } else if flatten_attr(&field.attrs)? {
+        Some("flatten")
+    } else if bits_attr(&field.attrs)?.is_some() {
+        Some("bits")
+    } else if attributes::fixed_attr(&field.attrs)? {
+        Some("fixed")
+    } else if pad_attr(&field.attrs)?.is_some() {
+        Some("pad")
+    } else if align_attr(&field.attrs)?.is_some() {
+        Some("align")
+    } else if count_attr(&field.attrs)?.is_some() {
+        Some("count")
+    } else if if_attr(&field.attrs)?.is_some() {
+        Some("if")
+    } else if attributes::endian_attr(&field.attrs)?.is_some() {
+        Some("endian")
+    } else if rest_attr(&field.attrs)? {
+        Some("rest")
+    } else {
+        None
+    };
+    match unsupported {
+        Some(key) => Err(syn::Error::new_spanned(
+            field,
+            format!(
+                "struct `{name}`, field `{label}`: #[rapira({key})] cannot be combined with #[rapira(sparse)]"
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `let __rapira_value_i: Option<&T> = ..;` for each field, `Some` when it is written
+fn values(fields: &[SparseField], mode: Mode) -> TokenStream {
+    let values = fields.iter().enumerate().map(
+        |(
+            idx,
+            SparseField {
+                member,
+                typ,
+                inner,
+                since,
+                ..
+            },
+        )| {
+            let value = format_ident!("__rapira_value_{idx}");
+            let present = match inner {
+                Some(inner) => quote! { let #value: Option<&#inner> = self.#member.as_ref() },
+                None => quote! {
+                    let #value: Option<&#typ> = Some(&self.#member)
+                        .filter(|__rapira_value| **__rapira_value != <#typ as Default>::default())
+                },
+            };
+            match since {
+                Some(since) if mode == Mode::Versioned => quote! {
+                    #present.filter(|_| __rapira_version >= #since);
+                },
+                _ => quote! { #present; },
+            }
+        },
+    );
+    quote! { #(#values)* }
+}
+
+/// `let binding: typ = ..;` decoding one field after its presence bit
+fn read_field(rapira: &Path, field: &SparseField, mode: Mode) -> TokenStream {
+    let SparseField {
+        binding,
+        typ,
+        inner,
+        codec,
+        since,
+        default,
+        check,
+        ..
+    } = field;
+    let read = match mode {
+        Mode::Plain if inner.is_none() => check.read_slice(codec),
+        Mode::Versioned if inner.is_none() => check.read_versioned(codec),
+        Mode::Ctx if inner.is_none() => check.read_ctx(codec),
+        Mode::Plain => check.read(quote! { #codec::from_slice(__rapira_slice) }),
+        Mode::Versioned => {
+            check.read(quote! { #codec::from_slice_versioned(__rapira_slice, __rapira_version) })
+        }
+        Mode::Ctx => check.read(quote! { #codec::from_slice_ctx(__rapira_slice, __rapira_flags) }),
+        Mode::Unchecked => quote! { unsafe { #codec::from_slice_unchecked(__rapira_slice)? } },
+        Mode::Unsafe => quote! { unsafe { #codec::from_slice_unsafe(__rapira_slice)? } },
+    };
+    let (read, absent) = match inner {
+        Some(_) => (quote! { Some(#read) }, quote! { None }),
+        None => (read, quote! { Default::default() }),
+    };
+    let read = quote! {
+        if #rapira::bits::next_present(__rapira_presence, &mut __rapira_bit) {
+            #read
+        } else {
+            #absent
+        }
+    };
+    let read = match since {
+        Some(since) if mode == Mode::Versioned => quote! {
+            if __rapira_version >= #since {
+                #read
+            } else {
+                #default
+            }
+        },
+        _ => read,
+    };
+    let validate = match mode {
+        Mode::Unchecked | Mode::Unsafe => quote!(),
+        _ => check.validate(binding),
+    };
+    quote! {
+        let #binding: #typ = #read;
+        #validate
+    }
+}
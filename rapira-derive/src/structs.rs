@@ -4,12 +4,22 @@ extern crate syn;
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
-use syn::{Attribute, DataStruct, ExprPath, Field, Fields, Generics, LitInt};
+use syn::{Attribute, DataStruct, ExprPath, Field, Fields, Generics, LitInt, Path};
 
 use crate::{
-    attributes,
-    field_attrs::{extract_idx_attr, extract_since_attr, extract_with_attr, skip_attr},
-    shared::build_ident,
+    attributes::{self, RetiredField},
+    bits::{BitCode, BitGroup, group_ends},
+    field_attrs::{
+        align_attr, bits_attr, count_attr, extract_idx_attr, extract_since_attr,
+        flag_condition_attr, flatten_attr, if_attr, pad_attr, rest_attr, skip_attr,
+    },
+    layout,
+    shared::{
+        Errors, FieldCheck, MigratePrelude, and_conditions, build_ident, count_codec, debug_read,
+        default_value, field_codec, field_with, flag_present, flat_impl, flatten_check,
+        impl_bounds, max_cap_const, migrate_prelude, read_if, size_if, validated, versioned_field,
+        versioned_field_write, write_if,
+    },
 };
 
 pub fn struct_serializer(
@@ -18,70 +28,103 @@ pub fn struct_serializer(
     generics: Generics,
     is_debug: bool,
     attrs: &[Attribute],
-) -> proc_macro::TokenStream {
-    let struct_version = attributes::version_attr(attrs);
+) -> syn::Result<TokenStream> {
+    let rapira = &attributes::crate_path(attrs)?;
+    let validate = attributes::validate_attr(attrs)?;
+    let magic = attributes::magic_attr(attrs)?;
+    // `Some(big)`: the length prefix is big-endian
+    let extensible = attributes::extensible_attr(attrs)?
+        .then(|| attributes::endian_attr(attrs))
+        .transpose()?
+        .map(|big| big.unwrap_or(false));
+    let struct_version = attributes::version_attr(attrs)?;
+    let retired = attributes::retired_attrs(attrs)?;
+    let MigratePrelude {
+        read: migrations_read,
+        size: migrations_size,
+        write: migrations_write,
+    } = migrate_prelude(
+        rapira,
+        &attributes::migrate_attrs(attrs)?,
+        struct_version,
+        "struct",
+        name,
+    )?;
     let fields = &data_struct.fields;
+
+    if let Some(item) = retired.first()
+        && !matches!(fields, Fields::Named(_))
+    {
+        return Err(syn::Error::new(
+            item.span,
+            format!(
+                "struct `{name}`: #[rapira(retired(..))] is supported only for structs with named fields"
+            ),
+        ));
+    }
+
     match fields {
         Fields::Named(fields) => {
             let named = &fields.named;
             let named_len = named.len();
 
-            let mut fields_insert: Vec<(Field, u32, Option<ExprPath>, Option<u8>)> =
+            let mut fields_insert: Vec<(Field, u32, Option<ExprPath>, Option<u16>)> =
                 Vec::with_capacity(named_len);
             let mut seq = 0u32;
+            // `#[rapira(skip)]` fields are built from their default value
+            let mut skipped: Vec<TokenStream> = Vec::new();
+            // `#[rapira(rest)]` field, the bytes after the known fields
+            let mut rest: Option<&Ident> = None;
 
-            let iter = named.iter().filter(|field| !skip_attr(&field.attrs));
+            for field in named {
+                if rest_attr(&field.attrs)? {
+                    let ident = field.ident.as_ref().unwrap();
+                    let error = if extensible.is_none() {
+                        Some("#[rapira(rest)] needs #[rapira(extensible)] on the struct")
+                    } else if rest.is_some() {
+                        Some("only one field can be #[rapira(rest)]")
+                    } else if attributes::rapira_metas(&field.attrs)?.len() > 1 {
+                        Some("#[rapira(rest)] cannot be combined with other #[rapira(..)] keys")
+                    } else {
+                        None
+                    };
+                    if let Some(error) = error {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            format!("struct `{name}`, field `{ident}`: {error}"),
+                        ));
+                    }
+                    rest = Some(ident);
+                    continue;
+                }
+                if skip_attr(&field.attrs)? {
+                    let ident = &field.ident;
+                    let default = default_value(&field.attrs)?;
+                    skipped.push(quote! { #ident: #default, });
+                    continue;
+                }
 
-            for field in iter {
-                let field_idx = extract_idx_attr(&field.attrs).unwrap_or_else(|| {
+                let field_idx = extract_idx_attr(&field.attrs)?.unwrap_or_else(|| {
                     let current_seq = seq;
                     seq += 1;
                     current_seq
                 });
 
-                let field_with_attr = extract_with_attr(&field.attrs);
-                let field_since = extract_since_attr(&field.attrs);
-
-                // Validate since attr
-                if let Some(since) = field_since {
-                    if struct_version.is_none() {
-                        panic!(
-                            "field `{}` has #[rapira(since = {})] but struct `{}` is missing #[rapira(version = N)]",
-                            field.ident.as_ref().unwrap(),
-                            since,
-                            name
-                        );
-                    }
-                    if since == 0 {
-                        panic!(
-                            "field `{}`: #[rapira(since = 0)] is invalid, versions start at 1",
-                            field.ident.as_ref().unwrap()
-                        );
-                    }
-                    if since > struct_version.unwrap() {
-                        panic!(
-                            "field `{}`: #[rapira(since = {})] exceeds struct version {}",
-                            field.ident.as_ref().unwrap(),
-                            since,
-                            struct_version.unwrap()
-                        );
-                    }
-                    if skip_attr(&field.attrs) {
-                        panic!(
-                            "field `{}`: #[rapira(since)] and #[rapira(skip)] cannot be combined",
-                            field.ident.as_ref().unwrap()
-                        );
-                    }
-                }
+                let field_with_attr = field_with(rapira, field, attrs)?;
+                let field_since = extract_since_attr(&field.attrs)?;
 
                 fields_insert.push((field.clone(), field_idx, field_with_attr, field_since));
             }
 
-            fields_insert.sort_by(|(_, idx_a, _, _), (_, idx_b, _, _)| idx_a.cmp(idx_b));
+            fields_insert.sort_by_key(|(_, idx, _, _)| *idx);
+
+            validate_retired(&retired, &fields_insert, struct_version, name)?;
 
             let mut field_names: Vec<TokenStream> = Vec::with_capacity(named_len);
             let mut from_slice: Vec<TokenStream> = Vec::with_capacity(named_len);
             let mut from_slice_versioned: Vec<TokenStream> = Vec::with_capacity(named_len);
+            let mut size_versioned: Vec<TokenStream> = Vec::with_capacity(named_len);
+            let mut convert_to_bytes_versioned: Vec<TokenStream> = Vec::with_capacity(named_len);
             let mut debug_from_slice: Vec<TokenStream> = Vec::with_capacity(named_len);
             let mut check_bytes: Vec<TokenStream> = Vec::with_capacity(named_len);
             let mut from_slice_unchecked: Vec<TokenStream> = Vec::with_capacity(named_len);
@@ -94,159 +137,535 @@ pub fn struct_serializer(
             let mut convert_to_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(named_len);
             let mut from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(named_len);
             let mut size_ctx: Vec<TokenStream> = Vec::with_capacity(named_len);
+            let mut check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(named_len);
+
+            let mut aligned = false;
+            for (field, ..) in &fields_insert {
+                aligned |= align_attr(&field.attrs)?.is_some();
+            }
+            if let Some(prelude) =
+                layout::struct_prelude(rapira, magic.as_ref(), aligned, extensible)
+            {
+                prelude.push_size(
+                    rapira,
+                    [&mut size, &mut size_versioned, &mut size_ctx],
+                    &mut static_sizes,
+                    &mut min_size,
+                );
+                prelude.push(
+                    [
+                        &mut from_slice,
+                        &mut from_slice_versioned,
+                        &mut debug_from_slice,
+                        &mut from_slice_unchecked,
+                        &mut from_slice_unsafe,
+                        &mut from_slice_ctx,
+                        &mut check_bytes,
+                        &mut check_bytes_ctx,
+                    ],
+                    [
+                        &mut convert_to_bytes,
+                        &mut convert_to_bytes_versioned,
+                        &mut convert_to_bytes_ctx,
+                    ],
+                    &mut try_convert_to_bytes,
+                );
+            }
+
+            retired_fields(
+                rapira,
+                &retired,
+                None,
+                &mut from_slice_versioned,
+                &mut size_versioned,
+                &mut convert_to_bytes_versioned,
+            );
+
+            // `#[rapira(bits = N)]` fields are written by groups, at the last field of a group
+            let widths = fields_insert
+                .iter()
+                .map(|(field, ..)| bits_attr(&field.attrs))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let group_ends = group_ends(&widths);
+            let mut bit_group = BitGroup::new(rapira);
+            // fields already decoded, in scope of `if` conditions and `count`
+            let mut earlier: Vec<Ident> = Vec::with_capacity(named_len);
+            // `if` and `count` depend on decoded fields, `check_bytes` decodes the struct
+            let mut decoded_check = false;
 
-            for (field, _, with_attr, since) in fields_insert.iter() {
+            for (idx, (field, _, with_attr, since)) in fields_insert.iter().enumerate() {
                 let ident = field.ident.as_ref().unwrap();
                 let typ = &field.ty;
 
-                field_names.push(quote! { #ident, });
-
-                // Generate from_slice_versioned entry
-                if let Some(since_val) = since {
-                    // Field with since attr — conditionally read based on version
-                    match with_attr {
-                        Some(with_attr) => {
-                            from_slice_versioned.push(quote! {
-                                let #ident: #typ = if __rapira_version >= #since_val {
-                                    #with_attr::from_slice(__rapira_slice)?
-                                } else {
-                                    Default::default()
-                                };
-                            });
-                        }
-                        None => {
-                            from_slice_versioned.push(quote! {
-                                let #ident: #typ = if __rapira_version >= #since_val {
-                                    <#typ as rapira::Rapira>::from_slice_versioned(__rapira_slice, __rapira_version)?
-                                } else {
-                                    Default::default()
-                                };
-                            });
+                if let Some(width) = widths[idx] {
+                    field_names.push(quote! { #ident, });
+                    let check = FieldCheck::new(rapira, field, ident.to_string())?;
+                    bit_group.push(ident, quote! { &self.#ident }, typ, width, check);
+                    if group_ends[idx] {
+                        let BitCode {
+                            size: bytes,
+                            read,
+                            read_unchecked,
+                            check,
+                            write,
+                            try_write,
+                        } = bit_group.finish();
+                        for method in [
+                            &mut from_slice,
+                            &mut from_slice_versioned,
+                            &mut debug_from_slice,
+                            &mut from_slice_ctx,
+                        ] {
+                            method.push(read.clone());
                         }
-                    }
-                } else {
-                    // Field without since — always read, propagate version
-                    match with_attr {
-                        Some(with_attr) => {
-                            from_slice_versioned.push(quote! {
-                                let #ident: #typ = #with_attr::from_slice(__rapira_slice)?;
-                            });
+                        from_slice_unchecked.push(read_unchecked.clone());
+                        from_slice_unsafe.push(read_unchecked);
+                        check_bytes.push(check.clone());
+                        check_bytes_ctx.push(check);
+                        for method in [
+                            &mut convert_to_bytes,
+                            &mut convert_to_bytes_versioned,
+                            &mut convert_to_bytes_ctx,
+                        ] {
+                            method.push(write.clone());
                         }
-                        None => {
-                            from_slice_versioned.push(quote! {
-                                let #ident = <#typ as rapira::Rapira>::from_slice_versioned(__rapira_slice, __rapira_version)?;
-                            });
+                        try_convert_to_bytes.push(try_write);
+                        for method in [&mut size, &mut size_versioned, &mut size_ctx] {
+                            method.push(quote! { + #bytes });
                         }
+                        static_sizes.push(quote! { Some(#bytes), });
+                        min_size.push(quote! { #bytes, });
                     }
+                    retired_fields(
+                        rapira,
+                        &retired,
+                        Some(ident),
+                        &mut from_slice_versioned,
+                        &mut size_versioned,
+                        &mut convert_to_bytes_versioned,
+                    );
+                    earlier.push(ident.clone());
+                    continue;
                 }
 
-                match with_attr {
-                    Some(with_attr) => {
-                        static_sizes.push(quote! {
-                            #with_attr::static_size(core::marker::PhantomData::<#typ>),
-                        });
-                        min_size.push(quote! {
-                            #with_attr::min_size(core::marker::PhantomData::<#typ>),
-                        });
-                        size.push(
-                            quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                                Some(s) => s,
-                                None => #with_attr::size(&self.#ident)
-                            }) },
-                        );
-                        check_bytes.push(quote! {
-                            #with_attr::check_bytes(core::marker::PhantomData::<#typ>, __rapira_slice)?;
-                        });
-                        from_slice.push(quote! {
-                            let #ident: #typ = #with_attr::from_slice(__rapira_slice)?;
-                        });
-                        debug_from_slice.push(quote! {
-                            let len = __rapira_slice.len();
-                            println!("Field: {}, Type: {}", stringify!(#ident), stringify!(#typ));
-                            let res = #with_attr::from_slice(__rapira_slice).inspect(|v| {
-                                println!("len: {len}, {}: {v:?}", stringify!(#ident));
-                            }).inspect_err(|err| {
-                                println!("len: {len}, err: {err:?}");
-                            });
-                            let #ident: #typ = res?;
-                        });
-                        from_slice_unchecked.push(quote! {
-                            let #ident: #typ = #with_attr::from_slice_unchecked(__rapira_slice)?;
-                        });
-                        from_slice_unsafe.push(quote! {
-                            let #ident: #typ = #with_attr::from_slice_unsafe(__rapira_slice)?;
-                        });
-                        try_convert_to_bytes.push(quote! {
-                            #with_attr::try_convert_to_bytes(&self.#ident, __rapira_slice, __rapira_cursor)?;
-                        });
-                        convert_to_bytes.push(quote! {
-                            #with_attr::convert_to_bytes(&self.#ident, __rapira_slice, __rapira_cursor);
-                        });
-                        convert_to_bytes_ctx.push(quote! {
-                            #with_attr::convert_to_bytes(&self.#ident, __rapira_slice, __rapira_cursor);
-                        });
-                        from_slice_ctx.push(quote! {
-                            let #ident: #typ = #with_attr::from_slice(__rapira_slice)?;
-                        });
-                        size_ctx.push(quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                            Some(s) => s,
-                            None => #with_attr::size(&self.#ident)
-                        }) });
+                if let Some(padding) = layout::field_padding(
+                    rapira,
+                    pad_attr(&field.attrs)?,
+                    align_attr(&field.attrs)?,
+                ) {
+                    padding.push_size(
+                        rapira,
+                        [&mut size, &mut size_versioned, &mut size_ctx],
+                        &mut static_sizes,
+                        &mut min_size,
+                    );
+                    padding.push(
+                        [
+                            &mut from_slice,
+                            &mut from_slice_versioned,
+                            &mut debug_from_slice,
+                            &mut from_slice_unchecked,
+                            &mut from_slice_unsafe,
+                            &mut from_slice_ctx,
+                            &mut check_bytes,
+                            &mut check_bytes_ctx,
+                        ],
+                        [
+                            &mut convert_to_bytes,
+                            &mut convert_to_bytes_versioned,
+                            &mut convert_to_bytes_ctx,
+                        ],
+                        &mut try_convert_to_bytes,
+                    );
+                }
+
+                // `omit_if`/`only_if` fields: plain methods behave as `RapiraFlags::NONE`,
+                // `if` fields: the condition is evaluated on earlier fields
+                let condition = flag_condition_attr(&field.attrs)?;
+                let cond = if_attr(&field.attrs)?;
+                let (if_read, if_write) = match &cond {
+                    Some(cond) => (
+                        Some(layout::field_condition(cond, &earlier, &quote!())),
+                        Some(layout::field_condition(cond, &earlier, &quote! { self. })),
+                    ),
+                    None => (None, None),
+                };
+                let present = flag_present(&condition, &quote! { #rapira::RapiraFlags::NONE });
+                let present_ctx = flag_present(&condition, &quote! { __rapira_flags });
+                let present_read = and_conditions(present.clone(), if_read.clone());
+                let present_ctx_read = and_conditions(present_ctx.clone(), if_read);
+                let present = and_conditions(present, if_write.clone());
+                let present_ctx = and_conditions(present_ctx, if_write);
+                let count = count_attr(&field.attrs)?;
+                if let Some(count) = &count
+                    && !earlier.contains(count)
+                {
+                    return Err(syn::Error::new_spanned(
+                        count,
+                        format!(
+                            "struct `{name}`, field `{ident}`: `count = {count}` must name an earlier field"
+                        ),
+                    ));
+                }
+                decoded_check |= cond.is_some() || count.is_some();
+                let flatten = flatten_attr(&field.attrs)?;
+                from_slice.push(flatten_check(rapira, typ, flatten));
+                let default = default_value(&field.attrs)?;
+                let check = FieldCheck::new(rapira, field, ident.to_string())?;
+                let len_check = check.len_check();
+
+                field_names.push(quote! { #ident, });
+
+                let item = count_codec(rapira, field, attrs)?;
+                match &count {
+                    Some(count) => {
+                        from_slice_versioned.push(read_if(
+                            present_read.as_ref(),
+                            ident,
+                            typ,
+                            check.read(layout::count_read(
+                                rapira,
+                                count,
+                                quote! { |__rapira_slice| #item::from_slice_versioned(__rapira_slice, __rapira_version) },
+                            )),
+                            &default,
+                        ));
+                        size_versioned.push(size_if(
+                            present.as_ref(),
+                            quote! { self.#ident.iter().map(|__rapira_item| #item::size_versioned(__rapira_item, __rapira_version)).sum::<usize>() },
+                        ));
+                        convert_to_bytes_versioned.push(write_if(
+                            present.as_ref(),
+                            layout::count_write(
+                                rapira,
+                                count,
+                                ident,
+                                quote! { #item::convert_to_bytes_versioned(__rapira_item, __rapira_slice, __rapira_cursor, __rapira_version); },
+                                false,
+                            ),
+                        ));
                     }
                     None => {
-                        from_slice.push(quote! {
-                            let #ident = <#typ as rapira::Rapira>::from_slice(__rapira_slice)?;
-                        });
-                        debug_from_slice.push(quote! {
+                        from_slice_versioned.push(versioned_field(
+                            &field_codec(rapira, typ, with_attr.as_ref()),
+                            ident,
+                            typ,
+                            *since,
+                            present_read.as_ref(),
+                            &default,
+                            Some(&check),
+                        ));
+                        let (field_size, field_write) = versioned_field_write(
+                            rapira,
+                            &quote! { &self.#ident },
+                            typ,
+                            with_attr.as_ref(),
+                            *since,
+                            present.as_ref(),
+                        );
+                        size_versioned.push(field_size);
+                        convert_to_bytes_versioned.push(field_write);
+                    }
+                }
+                from_slice_versioned.push(check.validate(ident));
+                retired_fields(
+                    rapira,
+                    &retired,
+                    Some(ident),
+                    &mut from_slice_versioned,
+                    &mut size_versioned,
+                    &mut convert_to_bytes_versioned,
+                );
+                earlier.push(ident.clone());
+
+                if let Some(count) = &count {
+                    static_sizes.push(quote! { None, });
+                    min_size.push(quote! { 0, });
+                    let read = check.read(layout::count_read(
+                        rapira,
+                        count,
+                        quote! { #item::from_slice },
+                    ));
+                    from_slice.push(read_if(
+                        present_read.as_ref(),
+                        ident,
+                        typ,
+                        read.clone(),
+                        &default,
+                    ));
+                    from_slice.push(check.validate(ident));
+                    debug_from_slice.push(read_if(
+                        present_read.as_ref(),
+                        ident,
+                        typ,
+                        read,
+                        &default,
+                    ));
+                    debug_from_slice.push(check.validate(ident));
+                    let read = layout::count_read(
+                        rapira,
+                        count,
+                        quote! { |__rapira_slice| unsafe { #item::from_slice_unchecked(__rapira_slice) } },
+                    );
+                    from_slice_unchecked.push(read_if(
+                        present_read.as_ref(),
+                        ident,
+                        typ,
+                        quote! { #read? },
+                        &default,
+                    ));
+                    let read = layout::count_read(
+                        rapira,
+                        count,
+                        quote! { |__rapira_slice| unsafe { #item::from_slice_unsafe(__rapira_slice) } },
+                    );
+                    from_slice_unsafe.push(read_if(
+                        present_read.as_ref(),
+                        ident,
+                        typ,
+                        quote! { #read? },
+                        &default,
+                    ));
+                    from_slice_ctx.push(read_if(
+                        present_ctx_read.as_ref(),
+                        ident,
+                        typ,
+                        check.read(layout::count_read(
+                            rapira,
+                            count,
+                            quote! { |__rapira_slice| #item::from_slice_ctx(__rapira_slice, __rapira_flags) },
+                        )),
+                        &default,
+                    ));
+                    from_slice_ctx.push(check.validate(ident));
+                    size.push(size_if(
+                        present.as_ref(),
+                        quote! { self.#ident.iter().map(#item::size).sum::<usize>() },
+                    ));
+                    size_ctx.push(size_if(
+                        present_ctx.as_ref(),
+                        quote! { self.#ident.iter().map(|__rapira_item| #item::size_ctx(__rapira_item, __rapira_flags)).sum::<usize>() },
+                    ));
+                    try_convert_to_bytes.push(write_if(
+                        present.as_ref(),
+                        layout::count_write(
+                            rapira,
+                            count,
+                            ident,
+                            quote! { #item::try_convert_to_bytes(__rapira_item, __rapira_slice, __rapira_cursor)?; },
+                            true,
+                        ),
+                    ));
+                    convert_to_bytes.push(write_if(
+                        present.as_ref(),
+                        layout::count_write(
+                            rapira,
+                            count,
+                            ident,
+                            quote! { #item::convert_to_bytes(__rapira_item, __rapira_slice, __rapira_cursor); },
+                            false,
+                        ),
+                    ));
+                    convert_to_bytes_ctx.push(write_if(
+                        present_ctx.as_ref(),
+                        layout::count_write(
+                            rapira,
+                            count,
+                            ident,
+                            quote! { #item::convert_to_bytes_ctx(__rapira_item, __rapira_slice, __rapira_cursor, __rapira_flags); },
+                            false,
+                        ),
+                    ));
+                    continue;
+                }
+
+                let codec = field_codec(rapira, typ, with_attr.as_ref());
+                if present.is_some() {
+                    static_sizes.push(quote! { None, });
+                    min_size.push(quote! { 0, });
+                } else {
+                    static_sizes.push(quote! { #codec::STATIC_SIZE, });
+                    min_size.push(quote! { #codec::MIN_SIZE, });
+                }
+                size.push(size_if(
+                    present.as_ref(),
+                    quote! { (match #codec::STATIC_SIZE {
+                        Some(s) => s,
+                        None => #codec::size(&self.#ident)
+                    }) },
+                ));
+                check_bytes.push(write_if(
+                    present_read.as_ref(),
+                    check.check_bytes(&codec, typ, false),
+                ));
+                from_slice.push(read_if(
+                    present_read.as_ref(),
+                    ident,
+                    typ,
+                    check.read_slice(&codec),
+                    &default,
+                ));
+                from_slice.push(check.validate(ident));
+                debug_from_slice.push(read_if(
+                    present_read.as_ref(),
+                    ident,
+                    typ,
+                    debug_read(
+                        rapira,
+                        typ,
+                        flatten,
+                        quote! {{
+                            #len_check
                             let len = __rapira_slice.len();
                             println!("Field: {}, Type: {}", stringify!(#ident), stringify!(#typ));
-                            let res = <#typ as rapira::Rapira>::from_slice(__rapira_slice).inspect(|v| {
+                            let res = #codec::from_slice(__rapira_slice).inspect(|v| {
                                 println!("len: {len}, {}: {v:?}", stringify!(#ident));
                             }).inspect_err(|err| {
                                 println!("len: {len}, err: {err:?}");
                             });
-                            let #ident = res?;
-                        });
-                        check_bytes.push(quote! {
-                            <#typ as rapira::Rapira>::check_bytes(__rapira_slice)?;
-                        });
-                        from_slice_unchecked.push(quote! {
-                            let #ident = <#typ as rapira::Rapira>::from_slice_unchecked(__rapira_slice)?;
-                        });
-                        from_slice_unsafe.push(quote! {
-                            let #ident = <#typ as rapira::Rapira>::from_slice_unsafe(__rapira_slice)?;
-                        });
-                        try_convert_to_bytes.push(quote! {
-                            self.#ident.try_convert_to_bytes(__rapira_slice, __rapira_cursor)?;
-                        });
-                        convert_to_bytes.push(quote! {
-                            self.#ident.convert_to_bytes(__rapira_slice, __rapira_cursor);
-                        });
-                        size.push(quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                            Some(s) => s,
-                            None => self.#ident.size()
-                        }) });
-                        static_sizes.push(quote! {
-                            <#typ as rapira::Rapira>::STATIC_SIZE,
-                        });
-                        min_size.push(quote! {
-                            <#typ as rapira::Rapira>::MIN_SIZE,
-                        });
-                        convert_to_bytes_ctx.push(quote! {
-                            self.#ident.convert_to_bytes_ctx(__rapira_slice, __rapira_cursor, __rapira_flags);
-                        });
-                        from_slice_ctx.push(quote! {
-                            let #ident = <#typ as rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
-                        });
-                        size_ctx.push(quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                            Some(s) => s,
-                            None => self.#ident.size_ctx(__rapira_flags)
-                        }) });
-                    }
-                }
+                            res?
+                        }},
+                    ),
+                    &default,
+                ));
+                debug_from_slice.push(check.validate(ident));
+                from_slice_unchecked.push(read_if(
+                    present_read.as_ref(),
+                    ident,
+                    typ,
+                    quote! { #codec::from_slice_unchecked(__rapira_slice)? },
+                    &default,
+                ));
+                from_slice_unsafe.push(read_if(
+                    present_read.as_ref(),
+                    ident,
+                    typ,
+                    quote! { #codec::from_slice_unsafe(__rapira_slice)? },
+                    &default,
+                ));
+                try_convert_to_bytes.push(write_if(
+                    present.as_ref(),
+                    quote! { #codec::try_convert_to_bytes(&self.#ident, __rapira_slice, __rapira_cursor)?; },
+                ));
+                convert_to_bytes.push(write_if(
+                    present.as_ref(),
+                    quote! { #codec::convert_to_bytes(&self.#ident, __rapira_slice, __rapira_cursor); },
+                ));
+                convert_to_bytes_ctx.push(write_if(
+                    present_ctx.as_ref(),
+                    quote! { #codec::convert_to_bytes_ctx(&self.#ident, __rapira_slice, __rapira_cursor, __rapira_flags); },
+                ));
+                from_slice_ctx.push(read_if(
+                    present_ctx_read.as_ref(),
+                    ident,
+                    typ,
+                    check.read_ctx(&codec),
+                    &default,
+                ));
+                from_slice_ctx.push(check.validate(ident));
+                check_bytes_ctx.push(write_if(
+                    present_ctx_read.as_ref(),
+                    check.check_bytes(&codec, typ, true),
+                ));
+                size_ctx.push(size_if(
+                    present_ctx.as_ref(),
+                    quote! { (match #codec::STATIC_SIZE {
+                        Some(s) => s,
+                        None => #codec::size_ctx(&self.#ident, __rapira_flags)
+                    }) },
+                ));
             }
 
-            let name_with_generics = build_ident(name, generics);
+            if let Some(big) = extensible {
+                layout::StructEnd::new(rapira, big, rest).push(
+                    [
+                        &mut from_slice,
+                        &mut from_slice_versioned,
+                        &mut debug_from_slice,
+                        &mut from_slice_unchecked,
+                        &mut from_slice_unsafe,
+                        &mut from_slice_ctx,
+                    ],
+                    [
+                        &mut convert_to_bytes,
+                        &mut convert_to_bytes_versioned,
+                        &mut convert_to_bytes_ctx,
+                    ],
+                    &mut try_convert_to_bytes,
+                    [&mut size, &mut size_versioned, &mut size_ctx],
+                    &mut static_sizes,
+                    &mut min_size,
+                );
+            }
+            if let Some(rest) = rest {
+                field_names.push(quote! { #rest, });
+            }
+            field_names.extend(skipped);
+
+            // framed structs can't be flattened into others
+            let flat = (magic.is_none() && extensible.is_none() && !aligned)
+                .then(|| flat_impl(rapira, name, &generics));
+            let bounds = impl_bounds(rapira, name, &generics, attrs, named.iter())?;
+            let name_with_generics = build_ident(rapira, name, generics, bounds);
+
+            let from_slice_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    #(#from_slice)*
+                    Ok(#name {
+                        #(#field_names)*
+                    })
+                },
+            );
+            let from_slice_versioned_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    #migrations_read
+                    #(#from_slice_versioned)*
+                    Ok(#name {
+                        #(#field_names)*
+                    })
+                },
+            );
+            let debug_from_slice_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    println!("Struct: {}", stringify!(#name));
+                    #(#debug_from_slice)*
+                    Ok(#name {
+                        #(#field_names)*
+                    })
+                },
+            );
+            let from_slice_ctx_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    #(#from_slice_ctx)*
+                    Ok(#name {
+                        #(#field_names)*
+                    })
+                },
+            );
+            let check_bytes_body = if validate.is_some() || decoded_check {
+                quote! { Self::from_slice(__rapira_slice).map(|_| ()) }
+            } else {
+                quote! {
+                    #(#check_bytes)*
+                    Ok(())
+                }
+            };
+            let check_bytes_ctx_body = if validate.is_some() || decoded_check {
+                quote! { Self::from_slice_ctx(__rapira_slice, __rapira_flags).map(|_| ()) }
+            } else {
+                quote! {
+                    #(#check_bytes_ctx)*
+                    Ok(())
+                }
+            };
 
             let debug_parse = if is_debug {
                 quote! {
@@ -254,53 +673,52 @@ pub fn struct_serializer(
                     /// This method logs the struct name, field names, types, and values during deserialization.
                     /// Useful for debugging serialization/deserialization issues.
                     #[inline]
-                    fn debug_from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    fn debug_from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized + std::fmt::Debug,
                     {
-                        println!("Struct: {}", stringify!(#name));
-                        #(#debug_from_slice)*
-                        Ok(#name {
-                            #(#field_names)*
-                        })
+                        #debug_from_slice_body
                     }
                 }
             } else {
                 quote!()
             };
 
-            // Only generate from_slice_versioned override if struct has version attr
-            let versioned_method = if struct_version.is_some() {
-                quote! {
-                    #[inline]
-                    fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u8) -> rapira::Result<Self>
-                    where
-                        Self: Sized,
-                    {
-                        #(#from_slice_versioned)*
-                        Ok(#name {
-                            #(#field_names)*
-                        })
-                    }
+            let versioned_method = quote! {
+                #[inline]
+                fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u16) -> #rapira::Result<Self>
+                where
+                    Self: Sized,
+                {
+                    #from_slice_versioned_body
+                }
+
+                #[inline]
+                fn convert_to_bytes_versioned(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_version: u16) {
+                    #migrations_write
+                    #(#convert_to_bytes_versioned)*
+                }
+
+                #[inline]
+                fn size_versioned(&self, __rapira_version: u16) -> usize {
+                    #migrations_size
+                    0 #(#size_versioned)*
                 }
-            } else {
-                quote!()
             };
 
+            let max_cap = max_cap_const(attrs)?;
             let res = quote! {
                 #name_with_generics {
-                    const STATIC_SIZE: Option<usize> = rapira::static_size([#(#static_sizes)*]);
-                    const MIN_SIZE: usize = rapira::min_size(&[#(#min_size)*]);
+                    const STATIC_SIZE: Option<usize> = #rapira::static_size([#(#static_sizes)*]);
+                    const MIN_SIZE: usize = #rapira::min_size(&[#(#min_size)*]);
+                    #max_cap
 
                     #[inline]
-                    fn from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
-                        #(#from_slice)*
-                        Ok(#name {
-                            #(#field_names)*
-                        })
+                        #from_slice_body
                     }
 
                     #versioned_method
@@ -308,16 +726,15 @@ pub fn struct_serializer(
                     #debug_parse
 
                     #[inline]
-                    fn check_bytes(__rapira_slice: &mut &[u8]) -> rapira::Result<()>
+                    fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()>
                     where
                         Self: Sized,
                     {
-                        #(#check_bytes)*
-                        Ok(())
+                        #check_bytes_body
                     }
 
                     #[inline]
-                    unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
@@ -328,7 +745,7 @@ pub fn struct_serializer(
                     }
 
                     #[inline]
-                    unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
@@ -339,7 +756,7 @@ pub fn struct_serializer(
                     }
 
                     #[inline]
-                    fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> rapira::Result<()> {
+                    fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
                         #(#try_convert_to_bytes)*
                         Ok(())
                     }
@@ -355,28 +772,35 @@ pub fn struct_serializer(
                     }
 
                     #[inline]
-                    fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: rapira::RapiraFlags) {
+                    fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: #rapira::RapiraFlags) {
                         #(#convert_to_bytes_ctx)*
                     }
 
                     #[inline]
-                    fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: rapira::RapiraFlags) -> rapira::Result<Self>
+                    fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
-                        #(#from_slice_ctx)*
-                        Ok(#name {
-                            #(#field_names)*
-                        })
+                        #from_slice_ctx_body
                     }
 
                     #[inline]
-                    fn size_ctx(&self, __rapira_flags: rapira::RapiraFlags) -> usize {
+                    fn size_ctx(&self, __rapira_flags: #rapira::RapiraFlags) -> usize {
                         0 #(#size_ctx)*
                     }
+
+                    #[inline]
+                    fn check_bytes_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<()>
+                    where
+                        Self: Sized,
+                    {
+                        #check_bytes_ctx_body
+                    }
                 }
+
+                #flat
             };
-            proc_macro::TokenStream::from(res)
+            Ok(res)
         }
         Fields::Unnamed(fields) => {
             let unnamed = &fields.unnamed;
@@ -384,6 +808,8 @@ pub fn struct_serializer(
             let mut field_names: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
             let mut from_slice: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
             let mut from_slice_versioned: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
+            let mut size_versioned: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
+            let mut convert_to_bytes_versioned: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
             let mut debug_from_slice: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
             let mut check_bytes: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
             let mut from_slice_unchecked: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
@@ -396,185 +822,339 @@ pub fn struct_serializer(
             let mut convert_to_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
             let mut from_slice_ctx: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
             let mut size_ctx: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
+            let mut check_bytes_ctx: Vec<TokenStream> = Vec::with_capacity(unnamed_len);
+
+            let mut aligned = false;
+            for field in unnamed {
+                aligned |= align_attr(&field.attrs)?.is_some();
+            }
+            if let Some(prelude) =
+                layout::struct_prelude(rapira, magic.as_ref(), aligned, extensible)
+            {
+                prelude.push_size(
+                    rapira,
+                    [&mut size, &mut size_versioned, &mut size_ctx],
+                    &mut static_sizes,
+                    &mut min_size,
+                );
+                prelude.push(
+                    [
+                        &mut from_slice,
+                        &mut from_slice_versioned,
+                        &mut debug_from_slice,
+                        &mut from_slice_unchecked,
+                        &mut from_slice_unsafe,
+                        &mut from_slice_ctx,
+                        &mut check_bytes,
+                        &mut check_bytes_ctx,
+                    ],
+                    [
+                        &mut convert_to_bytes,
+                        &mut convert_to_bytes_versioned,
+                        &mut convert_to_bytes_ctx,
+                    ],
+                    &mut try_convert_to_bytes,
+                );
+            }
+
+            // `#[rapira(bits = N)]` fields are written by groups, at the last field of a group
+            let widths = unnamed
+                .iter()
+                .map(|field| bits_attr(&field.attrs))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let group_ends = group_ends(&widths);
+            let mut bit_group = BitGroup::new(rapira);
 
             for (idx, field) in unnamed.iter().enumerate() {
                 let id = syn::Lit::Int(LitInt::new(&idx.to_string(), Span::call_site()));
                 let typ = &field.ty;
-                let field_name = syn::Ident::new(&format!("arg{idx}"), Span::call_site());
-                let field_name_into = quote! { #field_name, };
-                let with_attr = extract_with_attr(&field.attrs);
-                let since = extract_since_attr(&field.attrs);
-
-                // Validate since attr for unnamed fields
-                if let Some(since_val) = since {
-                    if struct_version.is_none() {
-                        panic!(
-                            "unnamed field {} has #[rapira(since = {})] but struct `{}` is missing #[rapira(version = N)]",
-                            idx, since_val, name
-                        );
-                    }
-                    if since_val == 0 {
-                        panic!(
-                            "unnamed field {}: #[rapira(since = 0)] is invalid, versions start at 1",
-                            idx
-                        );
-                    }
-                    if since_val > struct_version.unwrap() {
-                        panic!(
-                            "unnamed field {}: #[rapira(since = {})] exceeds struct version {}",
-                            idx,
-                            since_val,
-                            struct_version.unwrap()
-                        );
-                    }
-                }
 
-                field_names.push(field_name_into);
-
-                // Generate from_slice_versioned entry
-                if let Some(since_val) = since {
-                    match &with_attr {
-                        Some(with_attr) => {
-                            from_slice_versioned.push(quote! {
-                                let #field_name: #typ = if __rapira_version >= #since_val {
-                                    #with_attr::from_slice(__rapira_slice)?
-                                } else {
-                                    Default::default()
-                                };
-                            });
+                if let Some(width) = widths[idx] {
+                    let field_name = syn::Ident::new(&format!("arg{idx}"), Span::call_site());
+                    field_names.push(quote! { #field_name, });
+                    let check = FieldCheck::new(rapira, field, idx.to_string())?;
+                    bit_group.push(&field_name, quote! { &self.#id }, typ, width, check);
+                    if group_ends[idx] {
+                        let BitCode {
+                            size: bytes,
+                            read,
+                            read_unchecked,
+                            check,
+                            write,
+                            try_write,
+                        } = bit_group.finish();
+                        for method in [
+                            &mut from_slice,
+                            &mut from_slice_versioned,
+                            &mut debug_from_slice,
+                            &mut from_slice_ctx,
+                        ] {
+                            method.push(read.clone());
                         }
-                        None => {
-                            from_slice_versioned.push(quote! {
-                                let #field_name: #typ = if __rapira_version >= #since_val {
-                                    <#typ as rapira::Rapira>::from_slice_versioned(__rapira_slice, __rapira_version)?
-                                } else {
-                                    Default::default()
-                                };
-                            });
+                        from_slice_unchecked.push(read_unchecked.clone());
+                        from_slice_unsafe.push(read_unchecked);
+                        check_bytes.push(check.clone());
+                        check_bytes_ctx.push(check);
+                        for method in [
+                            &mut convert_to_bytes,
+                            &mut convert_to_bytes_versioned,
+                            &mut convert_to_bytes_ctx,
+                        ] {
+                            method.push(write.clone());
                         }
-                    }
-                } else {
-                    match &with_attr {
-                        Some(with_attr) => {
-                            from_slice_versioned.push(quote! {
-                                let #field_name: #typ = #with_attr::from_slice(__rapira_slice)?;
-                            });
-                        }
-                        None => {
-                            from_slice_versioned.push(quote! {
-                                let #field_name = <#typ as rapira::Rapira>::from_slice_versioned(__rapira_slice, __rapira_version)?;
-                            });
+                        try_convert_to_bytes.push(try_write);
+                        for method in [&mut size, &mut size_versioned, &mut size_ctx] {
+                            method.push(quote! { + #bytes });
                         }
+                        static_sizes.push(quote! { Some(#bytes), });
+                        min_size.push(quote! { #bytes, });
                     }
+                    continue;
                 }
 
-                match with_attr {
-                    Some(with_attr) => {
-                        static_sizes.push(quote! {
-                            #with_attr::static_size(core::marker::PhantomData::<#typ>),
-                        });
-                        min_size.push(quote! {
-                            #with_attr::min_size(core::marker::PhantomData::<#typ>),
-                        });
-                        size.push(
-                            quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                                Some(s) => s,
-                                None => #with_attr::size(&self.#id)
-                            }) },
-                        );
-                        check_bytes.push(quote! {
-                            #with_attr::check_bytes(core::marker::PhantomData::<#typ>, __rapira_slice)?;
-                        });
-                        from_slice.push(quote! {
-                            let #field_name: #typ = #with_attr::from_slice(__rapira_slice)?;
-                        });
-                        debug_from_slice.push(quote! {
-                            let len = __rapira_slice.len();
-                            println!("Field: unnamed (index {}), Type: {}", #idx, stringify!(#typ));
-                            let res = #with_attr::from_slice(__rapira_slice).inspect(|v| {
-                                println!("len: {len}, unnamed (index {}): {v:?}", #idx);
-                            }).inspect_err(|err| {
-                                println!("len: {len}, err: {err:?}");
-                            });
-                            let #field_name: #typ = res?;
-                        });
-                        from_slice_unchecked.push(quote! {
-                            let #field_name: #typ = #with_attr::from_slice_unchecked(__rapira_slice)?;
-                        });
-                        from_slice_unsafe.push(quote! {
-                            let #field_name: #typ = #with_attr::from_slice_unsafe(__rapira_slice)?;
-                        });
-                        try_convert_to_bytes.push(quote! {
-                            #with_attr::try_convert_to_bytes(&self.#id, __rapira_slice, __rapira_cursor)?;
-                        });
-                        convert_to_bytes.push(quote! {
-                            #with_attr::convert_to_bytes(&self.#id, __rapira_slice, __rapira_cursor);
-                        });
-                        convert_to_bytes_ctx.push(quote! {
-                            #with_attr::convert_to_bytes(&self.#id, __rapira_slice, __rapira_cursor);
-                        });
-                        from_slice_ctx.push(quote! {
-                            let #field_name: #typ = #with_attr::from_slice(__rapira_slice)?;
-                        });
-                        size_ctx.push(quote! { + (match #with_attr::static_size(core::marker::PhantomData::<#typ>) {
-                            Some(s) => s,
-                            None => #with_attr::size(&self.#id)
-                        }) });
-                    }
-                    None => {
-                        from_slice.push(quote! {
-                            let #field_name = <#typ as rapira::Rapira>::from_slice(__rapira_slice)?;
-                        });
-                        debug_from_slice.push(quote! {
+                if let Some(padding) = layout::field_padding(
+                    rapira,
+                    pad_attr(&field.attrs)?,
+                    align_attr(&field.attrs)?,
+                ) {
+                    padding.push_size(
+                        rapira,
+                        [&mut size, &mut size_versioned, &mut size_ctx],
+                        &mut static_sizes,
+                        &mut min_size,
+                    );
+                    padding.push(
+                        [
+                            &mut from_slice,
+                            &mut from_slice_versioned,
+                            &mut debug_from_slice,
+                            &mut from_slice_unchecked,
+                            &mut from_slice_unsafe,
+                            &mut from_slice_ctx,
+                            &mut check_bytes,
+                            &mut check_bytes_ctx,
+                        ],
+                        [
+                            &mut convert_to_bytes,
+                            &mut convert_to_bytes_versioned,
+                            &mut convert_to_bytes_ctx,
+                        ],
+                        &mut try_convert_to_bytes,
+                    );
+                }
+
+                // `omit_if`/`only_if` fields: plain methods behave as `RapiraFlags::NONE`
+                let condition = flag_condition_attr(&field.attrs)?;
+                let present = flag_present(&condition, &quote! { #rapira::RapiraFlags::NONE });
+                let present_ctx = flag_present(&condition, &quote! { __rapira_flags });
+                let flatten = flatten_attr(&field.attrs)?;
+                from_slice.push(flatten_check(rapira, typ, flatten));
+                let default = default_value(&field.attrs)?;
+                let check = FieldCheck::new(rapira, field, idx.to_string())?;
+                let len_check = check.len_check();
+                let field_name = syn::Ident::new(&format!("arg{idx}"), Span::call_site());
+                let field_name_into = quote! { #field_name, };
+                let with_attr = field_with(rapira, field, attrs)?;
+                let since = extract_since_attr(&field.attrs)?;
+
+                field_names.push(field_name_into);
+
+                from_slice_versioned.push(versioned_field(
+                    &field_codec(rapira, typ, with_attr.as_ref()),
+                    &field_name,
+                    typ,
+                    since,
+                    present.as_ref(),
+                    &default,
+                    Some(&check),
+                ));
+                from_slice_versioned.push(check.validate(&field_name));
+                let (field_size, field_write) = versioned_field_write(
+                    rapira,
+                    &quote! { &self.#id },
+                    typ,
+                    with_attr.as_ref(),
+                    since,
+                    present.as_ref(),
+                );
+                size_versioned.push(field_size);
+                convert_to_bytes_versioned.push(field_write);
+
+                let codec = field_codec(rapira, typ, with_attr.as_ref());
+                if present.is_some() {
+                    static_sizes.push(quote! { None, });
+                    min_size.push(quote! { 0, });
+                } else {
+                    static_sizes.push(quote! { #codec::STATIC_SIZE, });
+                    min_size.push(quote! { #codec::MIN_SIZE, });
+                }
+                size.push(size_if(
+                    present.as_ref(),
+                    quote! { (match #codec::STATIC_SIZE {
+                        Some(s) => s,
+                        None => #codec::size(&self.#id)
+                    }) },
+                ));
+                check_bytes.push(write_if(
+                    present.as_ref(),
+                    check.check_bytes(&codec, typ, false),
+                ));
+                from_slice.push(read_if(
+                    present.as_ref(),
+                    &field_name,
+                    typ,
+                    check.read_slice(&codec),
+                    &default,
+                ));
+                from_slice.push(check.validate(&field_name));
+                debug_from_slice.push(read_if(
+                    present.as_ref(),
+                    &field_name,
+                    typ,
+                    debug_read(
+                        rapira,
+                        typ,
+                        flatten,
+                        quote! {{
+                            #len_check
                             let len = __rapira_slice.len();
                             println!("Field: unnamed (index {}), Type: {}", #idx, stringify!(#typ));
-                            let res = <#typ as rapira::Rapira>::from_slice(__rapira_slice).inspect(|v| {
+                            let res = #codec::from_slice(__rapira_slice).inspect(|v| {
                                 println!("len: {len}, unnamed (index {}): {v:?}", #idx);
                             }).inspect_err(|err| {
                                 println!("len: {len}, err: {err:?}");
                             });
-                            let #field_name = res?;
-                        });
-                        check_bytes.push(quote! {
-                            <#typ as rapira::Rapira>::check_bytes(__rapira_slice)?;
-                        });
-                        from_slice_unchecked.push(quote! {
-                            let #field_name = <#typ as rapira::Rapira>::from_slice_unchecked(__rapira_slice)?;
-                        });
-                        from_slice_unsafe.push(quote! {
-                            let #field_name = <#typ as rapira::Rapira>::from_slice_unsafe(__rapira_slice)?;
-                        });
-                        try_convert_to_bytes.push(quote! {
-                            self.#id.try_convert_to_bytes(__rapira_slice, __rapira_cursor)?;
-                        });
-                        convert_to_bytes.push(quote! {
-                            self.#id.convert_to_bytes(__rapira_slice, __rapira_cursor);
-                        });
-                        size.push(quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                            Some(s) => s,
-                            None => self.#id.size()
-                        }) });
-                        static_sizes.push(quote! {
-                            <#typ as rapira::Rapira>::STATIC_SIZE,
-                        });
-                        min_size.push(quote! {
-                            <#typ as rapira::Rapira>::MIN_SIZE,
-                        });
-                        convert_to_bytes_ctx.push(quote! {
-                            self.#id.convert_to_bytes_ctx(__rapira_slice, __rapira_cursor, __rapira_flags);
-                        });
-                        from_slice_ctx.push(quote! {
-                            let #field_name = <#typ as rapira::Rapira>::from_slice_ctx(__rapira_slice, __rapira_flags)?;
-                        });
-                        size_ctx.push(quote! { + (match <#typ as rapira::Rapira>::STATIC_SIZE {
-                            Some(s) => s,
-                            None => self.#id.size_ctx(__rapira_flags)
-                        }) });
-                    }
-                }
+                            res?
+                        }},
+                    ),
+                    &default,
+                ));
+                debug_from_slice.push(check.validate(&field_name));
+                from_slice_unchecked.push(read_if(
+                    present.as_ref(),
+                    &field_name,
+                    typ,
+                    quote! { #codec::from_slice_unchecked(__rapira_slice)? },
+                    &default,
+                ));
+                from_slice_unsafe.push(read_if(
+                    present.as_ref(),
+                    &field_name,
+                    typ,
+                    quote! { #codec::from_slice_unsafe(__rapira_slice)? },
+                    &default,
+                ));
+                try_convert_to_bytes.push(write_if(
+                    present.as_ref(),
+                    quote! { #codec::try_convert_to_bytes(&self.#id, __rapira_slice, __rapira_cursor)?; },
+                ));
+                convert_to_bytes.push(write_if(
+                    present.as_ref(),
+                    quote! { #codec::convert_to_bytes(&self.#id, __rapira_slice, __rapira_cursor); },
+                ));
+                convert_to_bytes_ctx.push(write_if(
+                    present_ctx.as_ref(),
+                    quote! { #codec::convert_to_bytes_ctx(&self.#id, __rapira_slice, __rapira_cursor, __rapira_flags); },
+                ));
+                from_slice_ctx.push(read_if(
+                    present_ctx.as_ref(),
+                    &field_name,
+                    typ,
+                    check.read_ctx(&codec),
+                    &default,
+                ));
+                from_slice_ctx.push(check.validate(&field_name));
+                check_bytes_ctx.push(write_if(
+                    present_ctx.as_ref(),
+                    check.check_bytes(&codec, typ, true),
+                ));
+                size_ctx.push(size_if(
+                    present_ctx.as_ref(),
+                    quote! { (match #codec::STATIC_SIZE {
+                        Some(s) => s,
+                        None => #codec::size_ctx(&self.#id, __rapira_flags)
+                    }) },
+                ));
+            }
+
+            if let Some(big) = extensible {
+                layout::StructEnd::new(rapira, big, None).push(
+                    [
+                        &mut from_slice,
+                        &mut from_slice_versioned,
+                        &mut debug_from_slice,
+                        &mut from_slice_unchecked,
+                        &mut from_slice_unsafe,
+                        &mut from_slice_ctx,
+                    ],
+                    [
+                        &mut convert_to_bytes,
+                        &mut convert_to_bytes_versioned,
+                        &mut convert_to_bytes_ctx,
+                    ],
+                    &mut try_convert_to_bytes,
+                    [&mut size, &mut size_versioned, &mut size_ctx],
+                    &mut static_sizes,
+                    &mut min_size,
+                );
             }
 
-            let name_with_generics = build_ident(name, generics);
+            // framed structs can't be flattened into others
+            let flat = (magic.is_none() && extensible.is_none() && !aligned)
+                .then(|| flat_impl(rapira, name, &generics));
+            let bounds = impl_bounds(rapira, name, &generics, attrs, unnamed.iter())?;
+            let name_with_generics = build_ident(rapira, name, generics, bounds);
+
+            let from_slice_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    #(#from_slice)*
+                    Ok(#name(#(#field_names)*))
+                },
+            );
+            let from_slice_versioned_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    #migrations_read
+                    #(#from_slice_versioned)*
+                    Ok(#name(#(#field_names)*))
+                },
+            );
+            let debug_from_slice_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    println!("Struct: {}", stringify!(#name));
+                    #(#debug_from_slice)*
+                    Ok(#name(#(#field_names)*))
+                },
+            );
+            let from_slice_ctx_body = validated(
+                rapira,
+                validate.as_ref(),
+                quote! {
+                    #(#from_slice_ctx)*
+                    Ok(#name(#(#field_names)*))
+                },
+            );
+            let check_bytes_body = if validate.is_some() {
+                quote! { Self::from_slice(__rapira_slice).map(|_| ()) }
+            } else {
+                quote! {
+                    #(#check_bytes)*
+                    Ok(())
+                }
+            };
+            let check_bytes_ctx_body = if validate.is_some() {
+                quote! { Self::from_slice_ctx(__rapira_slice, __rapira_flags).map(|_| ()) }
+            } else {
+                quote! {
+                    #(#check_bytes_ctx)*
+                    Ok(())
+                }
+            };
 
             let debug_parse = if is_debug {
                 quote! {
@@ -582,46 +1162,52 @@ pub fn struct_serializer(
                     /// This method logs the struct name, field indices, types, and values during deserialization.
                     /// Useful for debugging serialization/deserialization issues.
                     #[inline]
-                    fn debug_from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    fn debug_from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized + std::fmt::Debug,
                     {
-                        println!("Struct: {}", stringify!(#name));
-                        #(#debug_from_slice)*
-                        Ok(#name(#(#field_names)*))
+                        #debug_from_slice_body
                     }
                 }
             } else {
                 quote!()
             };
 
-            let versioned_method = if struct_version.is_some() {
-                quote! {
-                    #[inline]
-                    fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u8) -> rapira::Result<Self>
-                    where
-                        Self: Sized,
-                    {
-                        #(#from_slice_versioned)*
-                        Ok(#name(#(#field_names)*))
-                    }
+            let versioned_method = quote! {
+                #[inline]
+                fn from_slice_versioned(__rapira_slice: &mut &[u8], __rapira_version: u16) -> #rapira::Result<Self>
+                where
+                    Self: Sized,
+                {
+                    #from_slice_versioned_body
+                }
+
+                #[inline]
+                fn convert_to_bytes_versioned(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_version: u16) {
+                    #migrations_write
+                    #(#convert_to_bytes_versioned)*
+                }
+
+                #[inline]
+                fn size_versioned(&self, __rapira_version: u16) -> usize {
+                    #migrations_size
+                    0 #(#size_versioned)*
                 }
-            } else {
-                quote!()
             };
 
+            let max_cap = max_cap_const(attrs)?;
             let res = quote! {
                 #name_with_generics {
-                    const STATIC_SIZE: Option<usize> = rapira::static_size([#(#static_sizes)*]);
-                    const MIN_SIZE: usize = rapira::min_size(&[#(#min_size)*]);
+                    const STATIC_SIZE: Option<usize> = #rapira::static_size([#(#static_sizes)*]);
+                    const MIN_SIZE: usize = #rapira::min_size(&[#(#min_size)*]);
+                    #max_cap
 
                     #[inline]
-                    fn from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
-                        #(#from_slice)*
-                        Ok(#name(#(#field_names)*))
+                        #from_slice_body
                     }
 
                     #versioned_method
@@ -629,16 +1215,15 @@ pub fn struct_serializer(
                     #debug_parse
 
                     #[inline]
-                    fn check_bytes(__rapira_slice: &mut &[u8]) -> rapira::Result<()>
+                    fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()>
                     where
                         Self: Sized,
                     {
-                        #(#check_bytes)*
-                        Ok(())
+                        #check_bytes_body
                     }
 
                     #[inline]
-                    unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
@@ -647,7 +1232,7 @@ pub fn struct_serializer(
                     }
 
                     #[inline]
-                    unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
+                    unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
@@ -656,7 +1241,7 @@ pub fn struct_serializer(
                     }
 
                     #[inline]
-                    fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> rapira::Result<()> {
+                    fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
                         #(#try_convert_to_bytes)*
                         Ok(())
                     }
@@ -670,76 +1255,211 @@ pub fn struct_serializer(
                     fn size(&self) -> usize { 0 #(#size)* }
 
                     #[inline]
-                    fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: rapira::RapiraFlags) {
+                    fn convert_to_bytes_ctx(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize, __rapira_flags: #rapira::RapiraFlags) {
                         #(#convert_to_bytes_ctx)*
                     }
 
                     #[inline]
-                    fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: rapira::RapiraFlags) -> rapira::Result<Self>
+                    fn from_slice_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<Self>
                     where
                         Self: Sized,
                     {
-                        #(#from_slice_ctx)*
-                        Ok(#name(#(#field_names)*))
+                        #from_slice_ctx_body
                     }
 
                     #[inline]
-                    fn size_ctx(&self, __rapira_flags: rapira::RapiraFlags) -> usize {
+                    fn size_ctx(&self, __rapira_flags: #rapira::RapiraFlags) -> usize {
                         0 #(#size_ctx)*
                     }
+
+                    #[inline]
+                    fn check_bytes_ctx(__rapira_slice: &mut &[u8], __rapira_flags: #rapira::RapiraFlags) -> #rapira::Result<()>
+                    where
+                        Self: Sized,
+                    {
+                        #check_bytes_ctx_body
+                    }
                 }
-            };
 
-            proc_macro::TokenStream::from(res)
+                #flat
+            };
+            Ok(res)
         }
-        Fields::Unit => proc_macro::TokenStream::from(quote! {
-            impl rapira::Rapira for #name {
-                const STATIC_SIZE: Option<usize> = Some(0);
-                const MIN_SIZE: usize = 0;
+        Fields::Unit => {
+            let max_cap = max_cap_const(attrs)?;
+            let flat = flat_impl(rapira, name, &generics);
+            Ok(quote! {
+                impl #rapira::Rapira for #name {
+                    const STATIC_SIZE: Option<usize> = Some(0);
+                    const MIN_SIZE: usize = 0;
+                    #max_cap
 
-                #[inline]
-                fn from_slice(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
-                where
-                    Self: Sized,
-                {
-                    Ok(#name)
-                }
+                    #[inline]
+                    fn from_slice(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+                    where
+                        Self: Sized,
+                    {
+                        Ok(#name)
+                    }
 
-                #[inline]
-                fn check_bytes(__rapira_slice: &mut &[u8]) -> rapira::Result<()>
-                where
-                    Self: Sized,
-                {
-                    Ok(())
-                }
+                    #[inline]
+                    fn check_bytes(__rapira_slice: &mut &[u8]) -> #rapira::Result<()>
+                    where
+                        Self: Sized,
+                    {
+                        Ok(())
+                    }
 
-                #[inline]
-                unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
-                where
-                    Self: Sized,
-                {
-                    Ok(#name)
-                }
+                    #[inline]
+                    unsafe fn from_slice_unchecked(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+                    where
+                        Self: Sized,
+                    {
+                        Ok(#name)
+                    }
 
-                #[inline]
-                unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> rapira::Result<Self>
-                where
-                    Self: Sized,
-                {
-                    Ok(#name)
-                }
+                    #[inline]
+                    unsafe fn from_slice_unsafe(__rapira_slice: &mut &[u8]) -> #rapira::Result<Self>
+                    where
+                        Self: Sized,
+                    {
+                        Ok(#name)
+                    }
 
-                #[inline]
-                fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> rapira::Result<()> {
-                    Ok(())
+                    #[inline]
+                    fn try_convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) -> #rapira::Result<()> {
+                        Ok(())
+                    }
+
+                    #[inline]
+                    fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {}
+
+                    #[inline]
+                    fn size(&self) -> usize { 0 }
                 }
 
-                #[inline]
-                fn convert_to_bytes(&self, __rapira_slice: &mut [u8], __rapira_cursor: &mut usize) {}
+                #flat
+            })
+        }
+    }
+}
 
-                #[inline]
-                fn size(&self) -> usize { 0 }
+/// Check `#[rapira(retired(..))]` declarations: version ranges, positions
+/// and overlapping with other retired or current fields of the same name
+fn validate_retired(
+    retired: &[RetiredField],
+    fields: &[(Field, u32, Option<ExprPath>, Option<u16>)],
+    struct_version: Option<u16>,
+    name: &Ident,
+) -> syn::Result<()> {
+    let mut errors = Errors::default();
+
+    for (i, item) in retired.iter().enumerate() {
+        let field_name = &item.name;
+        let mut error = |msg: String| errors.push(syn::Error::new(item.span, msg));
+
+        let Some(version) = struct_version else {
+            error(format!(
+                "retired field `{field_name}` requires #[rapira(version = N)] on struct `{name}`"
+            ));
+            continue;
+        };
+        if item.since == 0 {
+            error(format!(
+                "retired field `{field_name}`: since = 0 is invalid, versions start at 1"
+            ));
+        }
+        if item.since >= item.until {
+            error(format!(
+                "retired field `{field_name}`: since = {} must be less than until = {}",
+                item.since, item.until
+            ));
+        }
+        if item.until > version {
+            error(format!(
+                "retired field `{field_name}`: until = {} exceeds struct version {version}",
+                item.until
+            ));
+        }
+
+        if let Some(after) = &item.after {
+            let fields_count = fields
+                .iter()
+                .filter(|(f, ..)| f.ident.as_ref() == Some(after))
+                .count();
+            let retired_count = retired[..i].iter().filter(|r| &r.name == after).count();
+            match (fields_count, retired_count) {
+                (0, 0) => error(format!(
+                    "retired field `{field_name}`: `after = {after}` must name a field or a retired field declared before it"
+                )),
+                (1, 0) | (0, 1) => {}
+                _ => error(format!(
+                    "retired field `{field_name}`: `after = {after}` is ambiguous, several fields have this name"
+                )),
             }
-        }),
+        }
+
+        for other in &retired[..i] {
+            if &other.name == field_name && item.since < other.until && other.since < item.until {
+                error(format!(
+                    "retired field `{field_name}`: versions {}..{} overlap with {}..{}",
+                    item.since, item.until, other.since, other.until
+                ));
+            }
+        }
+
+        for (f, _, _, since) in fields {
+            if f.ident.as_ref() == Some(field_name) && since.unwrap_or(1) < item.until {
+                error(format!(
+                    "retired field `{field_name}`: versions {}..{} overlap with the current field",
+                    item.since, item.until
+                ));
+            }
+        }
+    }
+
+    errors.finish()
+}
+
+/// versioned methods statements for retired fields placed after `after`
+/// (and, recursively, for retired fields placed after them),
+/// active when the version is in `since..until`:
+/// the value is read and discarded, `Default::default()` is written
+fn retired_fields(
+    rapira: &Path,
+    retired: &[RetiredField],
+    after: Option<&Ident>,
+    read: &mut Vec<TokenStream>,
+    size: &mut Vec<TokenStream>,
+    write: &mut Vec<TokenStream>,
+) {
+    for item in retired.iter().filter(|r| r.after.as_ref() == after) {
+        let RetiredField {
+            name: field_name,
+            ty,
+            since,
+            until,
+            ..
+        } = item;
+
+        read.push(quote! {
+            if (#since..#until).contains(&__rapira_version) {
+                let _: #ty = <#ty as #rapira::Rapira>::from_slice_versioned(__rapira_slice, __rapira_version)?;
+            }
+        });
+        size.push(quote! {
+            + if (#since..#until).contains(&__rapira_version) {
+                <#ty as #rapira::Rapira>::size_versioned(&<#ty as Default>::default(), __rapira_version)
+            } else {
+                0
+            }
+        });
+        write.push(quote! {
+            if (#since..#until).contains(&__rapira_version) {
+                <#ty as #rapira::Rapira>::convert_to_bytes_versioned(&<#ty as Default>::default(), __rapira_slice, __rapira_cursor, __rapira_version);
+            }
+        });
+
+        retired_fields(rapira, retired, Some(field_name), read, size, write);
     }
 }
@@ -1,7 +1,5 @@
 use crate::{
-    LEN_SIZE, Rapira, RapiraError, Result,
-    max_cap::{VEC_MAX_CAP, VEC_MAX_SIZE_OF},
-    primitive::bytes_rapira,
+    LEN_SIZE, LenPrefixed, Rapira, RapiraError, RapiraFlags, Result, primitive::bytes_rapira,
     str_rapira,
 };
 
@@ -16,6 +14,7 @@ use std::net::{IpAddr, Ipv6Addr, SocketAddrV6};
 #[cfg(feature = "alloc")]
 impl Rapira for String {
     const MIN_SIZE: usize = LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -72,6 +71,7 @@ impl Rapira for String {
 #[cfg(feature = "alloc")]
 impl Rapira for Vec<u8> {
     const MIN_SIZE: usize = LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -117,6 +117,7 @@ impl Rapira for Vec<u8> {
 #[cfg(feature = "alloc")]
 impl<T: Rapira> Rapira for Vec<T> {
     const MIN_SIZE: usize = LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -147,13 +148,13 @@ impl<T: Rapira> Rapira for Vec<T> {
     {
         let len = u32::from_slice(slice)? as usize;
 
-        if len > VEC_MAX_CAP {
+        if len > T::MAX_CAP {
             return Err(RapiraError::MaxCapacity);
         }
 
         let size = core::mem::size_of::<Vec<T>>() * len;
 
-        if size > VEC_MAX_SIZE_OF {
+        if size > T::MAX_SIZE_OF {
             return Err(RapiraError::MaxSize);
         }
 
@@ -168,19 +169,19 @@ impl<T: Rapira> Rapira for Vec<T> {
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
         let len = u32::from_slice(slice)? as usize;
 
-        if len > VEC_MAX_CAP {
+        if len > T::MAX_CAP {
             return Err(RapiraError::MaxCapacity);
         }
 
         let size = core::mem::size_of::<Vec<T>>() * len;
 
-        if size > VEC_MAX_SIZE_OF {
+        if size > T::MAX_SIZE_OF {
             return Err(RapiraError::MaxSize);
         }
 
@@ -247,12 +248,122 @@ impl<T: Rapira> Rapira for Vec<T> {
             val.convert_to_bytes(slice, cursor);
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_versioned(slice, cursor, version);
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_versioned(version))
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        for _ in 0..len {
+            T::check_bytes_ctx(slice, flags)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        if len > T::MAX_CAP {
+            return Err(RapiraError::MaxCapacity);
+        }
+
+        let size = core::mem::size_of::<Vec<T>>() * len;
+
+        if size > T::MAX_SIZE_OF {
+            return Err(RapiraError::MaxSize);
+        }
+
+        let mut vec: Vec<T> = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            let val = T::from_slice_ctx(slice, flags)?;
+            vec.push(val);
+        }
+
+        Ok(vec)
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_ctx(flags))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl LenPrefixed for String {}
+
+#[cfg(feature = "alloc")]
+impl LenPrefixed for Vec<u8> {}
+
+#[cfg(feature = "alloc")]
+impl<T: Rapira> LenPrefixed for Vec<T> {
+    #[inline]
+    fn decode_max_len(
+        slice: &mut &[u8],
+        max_len: usize,
+        decode: crate::constraints::Decode,
+    ) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        crate::constraints::check_len(len, false, Some(max_len))?;
+
+        let size = core::mem::size_of::<Vec<T>>() * len;
+
+        if size > T::MAX_SIZE_OF {
+            return Err(RapiraError::MaxSize);
+        }
+
+        let mut vec: Vec<T> = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            let val = decode.read::<T>(slice)?;
+            vec.push(val);
+        }
+
+        Ok(vec)
+    }
 }
 
 #[cfg(feature = "alloc")]
 impl<T: Rapira> Rapira for Box<T> {
     const STATIC_SIZE: Option<usize> = T::STATIC_SIZE;
     const MIN_SIZE: usize = T::MIN_SIZE;
+    const LITTLE_ENDIAN: bool = T::LITTLE_ENDIAN;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -264,7 +375,7 @@ impl<T: Rapira> Rapira for Box<T> {
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -316,6 +427,44 @@ impl<T: Rapira> Rapira for Box<T> {
     fn size(&self) -> usize {
         self.as_ref().size()
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        self.as_ref()
+            .convert_to_bytes_versioned(slice, cursor, version)
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        self.as_ref().size_versioned(version)
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        T::check_bytes_ctx(slice, flags)
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let t = T::from_slice_ctx(slice, flags)?;
+        Ok(Box::new(t))
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        self.as_ref().convert_to_bytes_ctx(slice, cursor, flags)
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        self.as_ref().size_ctx(flags)
+    }
 }
 
 #[cfg(feature = "alloc")]
@@ -324,6 +473,7 @@ where
     K: Ord,
 {
     const MIN_SIZE: usize = LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -358,6 +508,9 @@ where
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self> {
         let len = u32::from_slice(slice)? as usize;
+        if len > K::MAX_CAP.min(V::MAX_CAP) {
+            return Err(RapiraError::MaxCapacity);
+        }
         let mut map = BTreeMap::<K, V>::new();
         for _ in 0..len {
             let key = K::from_slice(slice)?;
@@ -368,8 +521,11 @@ where
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self> {
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self> {
         let len = u32::from_slice(slice)? as usize;
+        if len > K::MAX_CAP.min(V::MAX_CAP) {
+            return Err(RapiraError::MaxCapacity);
+        }
         let mut map = BTreeMap::<K, V>::new();
         for _ in 0..len {
             let key = K::from_slice_versioned(slice, version)?;
@@ -428,11 +584,79 @@ where
             value.convert_to_bytes(slice, cursor);
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+        for (key, value) in self {
+            key.convert_to_bytes_versioned(slice, cursor, version);
+            value.convert_to_bytes_versioned(slice, cursor, version);
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        4 + self.iter().fold(0, |b, (k, v)| {
+            b + k.size_versioned(version) + v.size_versioned(version)
+        })
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        for _ in 0..len {
+            K::check_bytes_ctx(slice, flags)?;
+            V::check_bytes_ctx(slice, flags)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self> {
+        let len = u32::from_slice(slice)? as usize;
+        if len > K::MAX_CAP.min(V::MAX_CAP) {
+            return Err(RapiraError::MaxCapacity);
+        }
+        let mut map = BTreeMap::<K, V>::new();
+        for _ in 0..len {
+            let key = K::from_slice_ctx(slice, flags)?;
+            let value = V::from_slice_ctx(slice, flags)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+        for (key, value) in self {
+            key.convert_to_bytes_ctx(slice, cursor, flags);
+            value.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        4 + self
+            .iter()
+            .fold(0, |b, (k, v)| b + k.size_ctx(flags) + v.size_ctx(flags))
+    }
 }
 
+#[cfg(feature = "alloc")]
+impl<K: Rapira, V: Rapira> LenPrefixed for BTreeMap<K, V> where K: Ord {}
+
 #[cfg(feature = "alloc")]
 impl Rapira for Cow<'_, str> {
     const MIN_SIZE: usize = LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -490,6 +714,9 @@ impl Rapira for Cow<'_, str> {
     }
 }
 
+#[cfg(feature = "alloc")]
+impl LenPrefixed for Cow<'_, str> {}
+
 #[cfg(feature = "std")]
 impl Rapira for IpAddr {
     const MIN_SIZE: usize = 1 + 4;
@@ -0,0 +1,601 @@
+//! Big-endian `with` adapters: `#[rapira(with = rapira::be::u32)]` for one field,
+//! `#[rapira(endian = "big")]` on a container or field picks them by the field type.
+//!
+//! Every integer, float and `NonZero` type has an adapter of the same name
+//! (`usize` as `u32`, `isize` as `i64`, like their `Rapira` impls);
+//! [`string`], [`bytes`] and [`vec`] write the `u32` length prefix big-endian.
+//! A container field of another type with little-endian numbers (`Option<u32>`, arrays,
+//! tuples, aliases) has no adapter and fails to compile, it needs `endian = "little"`
+//! or a `with` adapter of its own.
+
+#[cfg(feature = "alloc")]
+pub use self::imp::vec;
+pub use self::imp::{
+    F32 as f32, F64 as f64, I8 as i8, I16 as i16, I32 as i32, I64 as i64, I128 as i128,
+    Isize as isize, NonZeroU32, NonZeroU64, U16 as u16, U32 as u32, U64 as u64, U128 as u128,
+    Usize as usize, bytes, plain, string,
+};
+
+// adapters are defined under other names: inside `be` the re-exports shadow the primitive types
+mod imp {
+    #[cfg(feature = "alloc")]
+    use alloc::vec::Vec;
+    use core::{marker::PhantomData, mem::size_of};
+
+    use simdutf8::basic::from_utf8;
+
+    use crate::{
+        LEN_SIZE, Rapira, RapiraError, RapiraFlags, RapiraWith, Result, extend,
+        primitive::into_arr, try_extend,
+    };
+
+    /// read `N` bytes
+    #[inline]
+    fn read_arr<const N: usize>(slice: &mut &[u8]) -> Result<[u8; N]> {
+        let arr = into_arr(slice)?;
+        *slice = &slice[N..];
+        Ok(arr)
+    }
+
+    macro_rules! be_integer {
+        ($adapter: ident, $name: ident as $repr: ident) => {
+            #[doc = concat!("big-endian `", stringify!($name), "`, as `", stringify!($repr), "`")]
+            pub enum $adapter {}
+
+            impl RapiraWith<$name> for $adapter {
+                const STATIC_SIZE: Option<usize> = Some(size_of::<$repr>());
+                const MIN_SIZE: usize = size_of::<$repr>();
+
+                #[inline]
+                fn size(_: &$name) -> usize {
+                    size_of::<$repr>()
+                }
+
+                #[inline]
+                fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+                    <$repr as Rapira>::check_bytes(slice)
+                }
+
+                #[inline]
+                fn from_slice(slice: &mut &[u8]) -> Result<$name> {
+                    let bytes = read_arr(slice)?;
+                    Ok($repr::from_be_bytes(bytes) as $name)
+                }
+
+                #[inline]
+                fn convert_to_bytes(item: &$name, slice: &mut [u8], cursor: &mut usize) {
+                    extend(slice, cursor, &(*item as $repr).to_be_bytes());
+                }
+
+                #[inline]
+                fn try_convert_to_bytes(
+                    item: &$name,
+                    slice: &mut [u8],
+                    cursor: &mut usize,
+                ) -> Result<()> {
+                    try_extend(slice, cursor, &(*item as $repr).to_be_bytes())
+                }
+            }
+        };
+        ($($adapter: ident: $name: ident),*) => {
+            $(be_integer!($adapter, $name as $name);)*
+        };
+    }
+
+    be_integer!(
+        I8: i8,
+        I16: i16,
+        I32: i32,
+        I64: i64,
+        I128: i128,
+        U16: u16,
+        U32: u32,
+        U64: u64,
+        U128: u128
+    );
+    be_integer!(Usize, usize as u32);
+    be_integer!(Isize, isize as i64);
+
+    macro_rules! be_float {
+        ($adapter: ident, $name: ident) => {
+            #[doc = concat!("big-endian `", stringify!($name), "`, must be finite")]
+            pub enum $adapter {}
+
+            impl $adapter {
+                #[inline]
+                fn read(slice: &mut &[u8]) -> Result<$name> {
+                    let value = $name::from_be_bytes(into_arr(slice)?);
+                    if !value.is_finite() {
+                        return Err(RapiraError::FloatIsNaN);
+                    }
+                    *slice = &slice[size_of::<$name>()..];
+                    Ok(value)
+                }
+            }
+
+            impl RapiraWith<$name> for $adapter {
+                const STATIC_SIZE: Option<usize> = Some(size_of::<$name>());
+                const MIN_SIZE: usize = size_of::<$name>();
+
+                #[inline]
+                fn size(_: &$name) -> usize {
+                    size_of::<$name>()
+                }
+
+                #[inline]
+                fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+                    Self::read(slice).map(|_| ())
+                }
+
+                #[inline]
+                fn from_slice(slice: &mut &[u8]) -> Result<$name> {
+                    Self::read(slice)
+                }
+
+                #[inline]
+                unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<$name> {
+                    read_arr(slice).map($name::from_be_bytes)
+                }
+
+                #[inline]
+                unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<$name> {
+                    read_arr(slice).map($name::from_be_bytes)
+                }
+
+                #[inline]
+                fn convert_to_bytes(item: &$name, slice: &mut [u8], cursor: &mut usize) {
+                    assert!(
+                        item.is_finite(),
+                        concat!(stringify!($name), " is not finite")
+                    );
+                    extend(slice, cursor, &item.to_be_bytes());
+                }
+
+                #[inline]
+                fn try_convert_to_bytes(
+                    item: &$name,
+                    slice: &mut [u8],
+                    cursor: &mut usize,
+                ) -> Result<()> {
+                    if !item.is_finite() {
+                        return Err(RapiraError::FloatIsNaN);
+                    }
+                    try_extend(slice, cursor, &item.to_be_bytes())
+                }
+            }
+        };
+    }
+
+    be_float!(F32, f32);
+    be_float!(F64, f64);
+
+    macro_rules! be_non_zero {
+        ($name: ident, $int: ident) => {
+            #[doc = concat!("big-endian `", stringify!($name), "`")]
+            pub enum $name {}
+
+            impl RapiraWith<core::num::$name> for $name {
+                const STATIC_SIZE: Option<usize> = Some(size_of::<core::num::$name>());
+                const MIN_SIZE: usize = size_of::<core::num::$name>();
+
+                #[inline]
+                fn size(_: &core::num::$name) -> usize {
+                    size_of::<core::num::$name>()
+                }
+
+                #[inline]
+                fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+                    <core::num::$name as Rapira>::check_bytes(slice)
+                }
+
+                #[inline]
+                fn from_slice(slice: &mut &[u8]) -> Result<core::num::$name> {
+                    let value = <$int>::from_be_bytes(read_arr(slice)?);
+                    core::num::$name::new(value).ok_or(RapiraError::NonZero)
+                }
+
+                #[inline]
+                unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<core::num::$name> {
+                    let value = <$int>::from_be_bytes(read_arr(slice)?);
+                    Ok(unsafe { core::num::$name::new_unchecked(value) })
+                }
+
+                #[inline]
+                unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<core::num::$name> {
+                    unsafe { Self::from_slice_unchecked(slice) }
+                }
+
+                #[inline]
+                fn convert_to_bytes(item: &core::num::$name, slice: &mut [u8], cursor: &mut usize) {
+                    extend(slice, cursor, &item.get().to_be_bytes());
+                }
+
+                #[inline]
+                fn try_convert_to_bytes(
+                    item: &core::num::$name,
+                    slice: &mut [u8],
+                    cursor: &mut usize,
+                ) -> Result<()> {
+                    try_extend(slice, cursor, &item.get().to_be_bytes())
+                }
+            }
+        };
+    }
+
+    be_non_zero!(NonZeroU32, u32);
+    be_non_zero!(NonZeroU64, u64);
+
+    /// big-endian length prefix
+    #[inline]
+    fn read_len(slice: &mut &[u8]) -> Result<usize> {
+        read_arr(slice).map(|len| u32::from_be_bytes(len) as usize)
+    }
+
+    /// `len` bytes after a big-endian length prefix
+    #[inline]
+    fn read_prefixed<'a>(slice: &mut &'a [u8]) -> Result<&'a [u8]> {
+        let len = read_len(slice)?;
+        let Some((bytes, tail)) = slice.split_at_checked(len) else {
+            return Err(RapiraError::SliceLen);
+        };
+        *slice = tail;
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn write_prefixed(bytes: &[u8], slice: &mut [u8], cursor: &mut usize) {
+        extend(slice, cursor, &(bytes.len() as u32).to_be_bytes());
+        extend(slice, cursor, bytes);
+    }
+
+    #[inline]
+    fn try_write_prefixed(bytes: &[u8], slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        try_extend(slice, cursor, &(bytes.len() as u32).to_be_bytes())?;
+        try_extend(slice, cursor, bytes)
+    }
+
+    /// `String`-like fields (`AsRef<str>` + `From<&str>`) with a big-endian length,
+    /// as [`str_rapira`](crate::str_rapira)
+    #[allow(non_camel_case_types)]
+    pub enum string {}
+
+    impl string {
+        #[inline]
+        fn read<'a>(slice: &mut &'a [u8]) -> Result<&'a str> {
+            from_utf8(read_prefixed(slice)?).map_err(|_| RapiraError::StringType)
+        }
+    }
+
+    impl<T> RapiraWith<T> for string
+    where
+        T: AsRef<str> + for<'a> From<&'a str>,
+    {
+        const MIN_SIZE: usize = LEN_SIZE;
+
+        #[inline]
+        fn size(item: &T) -> usize {
+            LEN_SIZE + item.as_ref().len()
+        }
+
+        #[inline]
+        fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+            Self::read(slice).map(|_| ())
+        }
+
+        #[inline]
+        fn from_slice(slice: &mut &[u8]) -> Result<T> {
+            Self::read(slice).map(T::from)
+        }
+
+        #[inline]
+        unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<T> {
+            let bytes = read_prefixed(slice)?;
+            Ok(T::from(unsafe { core::str::from_utf8_unchecked(bytes) }))
+        }
+
+        #[inline]
+        unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<T> {
+            unsafe { Self::from_slice_unchecked(slice) }
+        }
+
+        #[inline]
+        fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+            write_prefixed(item.as_ref().as_bytes(), slice, cursor);
+        }
+
+        #[inline]
+        fn try_convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+            try_write_prefixed(item.as_ref().as_bytes(), slice, cursor)
+        }
+    }
+
+    /// `Vec<u8>`-like fields (`AsRef<[u8]>` + `From<&[u8]>`) with a big-endian length,
+    /// as [`bytes_rapira`](crate::bytes_rapira)
+    #[allow(non_camel_case_types)]
+    pub enum bytes {}
+
+    impl<T> RapiraWith<T> for bytes
+    where
+        T: AsRef<[u8]> + for<'a> From<&'a [u8]>,
+    {
+        const MIN_SIZE: usize = LEN_SIZE;
+
+        #[inline]
+        fn size(item: &T) -> usize {
+            LEN_SIZE + item.as_ref().len()
+        }
+
+        #[inline]
+        fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+            read_prefixed(slice).map(|_| ())
+        }
+
+        #[inline]
+        fn from_slice(slice: &mut &[u8]) -> Result<T> {
+            read_prefixed(slice).map(T::from)
+        }
+
+        #[inline]
+        fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+            write_prefixed(item.as_ref(), slice, cursor);
+        }
+
+        #[inline]
+        fn try_convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+            try_write_prefixed(item.as_ref(), slice, cursor)
+        }
+    }
+
+    /// `T` by its own `Rapira` impl, items of [`vec`] without a big-endian adapter
+    #[allow(non_camel_case_types)]
+    pub enum plain {}
+
+    impl<T: Rapira> RapiraWith<T> for plain {
+        const STATIC_SIZE: Option<usize> = T::STATIC_SIZE;
+        const MIN_SIZE: usize = T::MIN_SIZE;
+
+        #[inline]
+        fn size(item: &T) -> usize {
+            item.size()
+        }
+
+        #[inline]
+        fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+            T::check_bytes(slice)
+        }
+
+        #[inline]
+        fn from_slice(slice: &mut &[u8]) -> Result<T> {
+            T::from_slice(slice)
+        }
+
+        #[inline]
+        unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<T> {
+            unsafe { T::from_slice_unchecked(slice) }
+        }
+
+        #[inline]
+        unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<T> {
+            unsafe { T::from_slice_unsafe(slice) }
+        }
+
+        #[inline]
+        fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<T> {
+            T::from_slice_versioned(slice, version)
+        }
+
+        #[inline]
+        fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<T> {
+            T::from_slice_ctx(slice, flags)
+        }
+
+        #[inline]
+        fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()> {
+            T::check_bytes_ctx(slice, flags)
+        }
+
+        #[inline]
+        fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+            item.convert_to_bytes(slice, cursor);
+        }
+
+        #[inline]
+        fn try_convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+            item.try_convert_to_bytes(slice, cursor)
+        }
+
+        #[inline]
+        fn convert_to_bytes_versioned(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+            version: u16,
+        ) {
+            item.convert_to_bytes_versioned(slice, cursor, version);
+        }
+
+        #[inline]
+        fn convert_to_bytes_ctx(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+            flags: RapiraFlags,
+        ) {
+            item.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+
+        #[inline]
+        fn size_versioned(item: &T, version: u16) -> usize {
+            item.size_versioned(version)
+        }
+
+        #[inline]
+        fn size_ctx(item: &T, flags: RapiraFlags) -> usize {
+            item.size_ctx(flags)
+        }
+    }
+
+    /// `Vec<T>` with a big-endian length, items by the adapter `A`:
+    /// `be::vec<be::u16>` for `Vec<u16>`, `be::vec` (`be::vec<be::plain>`) for other items
+    #[cfg(feature = "alloc")]
+    #[allow(non_camel_case_types)]
+    pub struct vec<A = plain>(PhantomData<A>);
+
+    #[cfg(feature = "alloc")]
+    impl<A> vec<A> {
+        /// decoded length, limited as `Vec<T>`
+        #[inline]
+        fn read_len<T: Rapira>(slice: &mut &[u8]) -> Result<usize> {
+            let len = read_len(slice)?;
+            if len > T::MAX_CAP {
+                return Err(RapiraError::MaxCapacity);
+            }
+            if size_of::<Vec<T>>() * len > T::MAX_SIZE_OF {
+                return Err(RapiraError::MaxSize);
+            }
+            Ok(len)
+        }
+
+        #[inline]
+        fn read<T: Rapira>(
+            slice: &mut &[u8],
+            mut read: impl FnMut(&mut &[u8]) -> Result<T>,
+        ) -> Result<Vec<T>> {
+            let len = Self::read_len::<T>(slice)?;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                items.push(read(slice)?);
+            }
+            Ok(items)
+        }
+
+        #[inline]
+        fn write<T>(
+            items: &[T],
+            slice: &mut [u8],
+            cursor: &mut usize,
+            mut write: impl FnMut(&T, &mut [u8], &mut usize),
+        ) {
+            extend(slice, cursor, &(items.len() as u32).to_be_bytes());
+            for item in items {
+                write(item, slice, cursor);
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<T: Rapira, A: RapiraWith<T>> RapiraWith<Vec<T>> for vec<A> {
+        const MIN_SIZE: usize = LEN_SIZE;
+
+        #[inline]
+        fn size(items: &Vec<T>) -> usize {
+            match A::STATIC_SIZE {
+                Some(size) => LEN_SIZE + size * items.len(),
+                None => LEN_SIZE + items.iter().map(A::size).sum::<usize>(),
+            }
+        }
+
+        #[inline]
+        fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+            let len = read_len(slice)?;
+            for _ in 0..len {
+                A::check_bytes(slice)?;
+            }
+            Ok(())
+        }
+
+        #[inline]
+        fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()> {
+            let len = read_len(slice)?;
+            for _ in 0..len {
+                A::check_bytes_ctx(slice, flags)?;
+            }
+            Ok(())
+        }
+
+        #[inline]
+        fn from_slice(slice: &mut &[u8]) -> Result<Vec<T>> {
+            Self::read(slice, A::from_slice)
+        }
+
+        #[inline]
+        unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<Vec<T>> {
+            Self::read(slice, |slice| unsafe { A::from_slice_unchecked(slice) })
+        }
+
+        #[inline]
+        unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<Vec<T>> {
+            Self::read(slice, |slice| unsafe { A::from_slice_unsafe(slice) })
+        }
+
+        #[inline]
+        fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Vec<T>> {
+            Self::read(slice, |slice| A::from_slice_versioned(slice, version))
+        }
+
+        #[inline]
+        fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Vec<T>> {
+            Self::read(slice, |slice| A::from_slice_ctx(slice, flags))
+        }
+
+        #[inline]
+        fn convert_to_bytes(items: &Vec<T>, slice: &mut [u8], cursor: &mut usize) {
+            Self::write(items, slice, cursor, A::convert_to_bytes);
+        }
+
+        #[inline]
+        fn try_convert_to_bytes(
+            items: &Vec<T>,
+            slice: &mut [u8],
+            cursor: &mut usize,
+        ) -> Result<()> {
+            try_extend(slice, cursor, &(items.len() as u32).to_be_bytes())?;
+            for item in items {
+                A::try_convert_to_bytes(item, slice, cursor)?;
+            }
+            Ok(())
+        }
+
+        #[inline]
+        fn convert_to_bytes_versioned(
+            items: &Vec<T>,
+            slice: &mut [u8],
+            cursor: &mut usize,
+            version: u16,
+        ) {
+            Self::write(items, slice, cursor, |item, slice, cursor| {
+                A::convert_to_bytes_versioned(item, slice, cursor, version)
+            });
+        }
+
+        #[inline]
+        fn convert_to_bytes_ctx(
+            items: &Vec<T>,
+            slice: &mut [u8],
+            cursor: &mut usize,
+            flags: RapiraFlags,
+        ) {
+            Self::write(items, slice, cursor, |item, slice, cursor| {
+                A::convert_to_bytes_ctx(item, slice, cursor, flags)
+            });
+        }
+
+        #[inline]
+        fn size_versioned(items: &Vec<T>, version: u16) -> usize {
+            LEN_SIZE
+                + items
+                    .iter()
+                    .map(|item| A::size_versioned(item, version))
+                    .sum::<usize>()
+        }
+
+        #[inline]
+        fn size_ctx(items: &Vec<T>, flags: RapiraFlags) -> usize {
+            LEN_SIZE
+                + items
+                    .iter()
+                    .map(|item| A::size_ctx(item, flags))
+                    .sum::<usize>()
+        }
+    }
+}
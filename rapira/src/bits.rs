@@ -0,0 +1,158 @@
+//! Runtime of `#[rapira(bits = N)]` fields: consecutive annotated fields are packed
+//! into one little-endian bitfield of the fewest bytes (at most 64 bits), the first
+//! field in the lowest bits. Unused high bits must be zero.
+//!
+//! Also the presence bitmap of `#[rapira(sparse)]` structs.
+
+use crate::{RapiraError, Result, extend, try_extend};
+
+/// Values stored in `#[rapira(bits = N)]` fields: `bool`, unsigned integers
+/// and simple enums deriving `Rapira` (by their tag)
+pub trait BitField: Sized {
+    fn to_bits(&self) -> u64;
+
+    /// `None` for bit patterns without a value
+    fn from_bits(bits: u64) -> Option<Self>;
+}
+
+impl BitField for bool {
+    #[inline]
+    fn to_bits(&self) -> u64 {
+        *self as u64
+    }
+
+    #[inline]
+    fn from_bits(bits: u64) -> Option<Self> {
+        match bits {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_for_unsigned {
+    ($($type: ident),*) => {
+        $(impl BitField for $type {
+            #[inline]
+            fn to_bits(&self) -> u64 {
+                *self as u64
+            }
+
+            #[inline]
+            fn from_bits(bits: u64) -> Option<Self> {
+                $type::try_from(bits).ok()
+            }
+        })*
+    };
+}
+
+impl_for_unsigned!(u8, u16, u32, u64);
+
+#[inline]
+const fn mask(width: u32) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// read a bitfield of `bytes` bytes with `bits` used bits
+#[inline]
+pub fn read_bits(slice: &mut &[u8], bytes: usize, bits: u32) -> Result<u64> {
+    let Some((head, tail)) = slice.split_at_checked(bytes) else {
+        return Err(RapiraError::SliceLen);
+    };
+    let mut buf = [0u8; 8];
+    buf[..bytes].copy_from_slice(head);
+    let packed = u64::from_le_bytes(buf);
+    if packed & !mask(bits) != 0 {
+        return Err(RapiraError::BitField);
+    }
+    *slice = tail;
+    Ok(packed)
+}
+
+/// value of the `width` bits at `offset`
+#[inline]
+pub fn get<T: BitField>(packed: u64, offset: u32, width: u32) -> Result<T> {
+    T::from_bits((packed >> offset) & mask(width)).ok_or(RapiraError::BitField)
+}
+
+/// `value` at `offset`
+///
+/// # Panics
+///
+/// when it does not fit in `width` bits, [`try_put`] returns an error instead
+#[inline]
+pub fn put<T: BitField>(value: &T, offset: u32, width: u32) -> u64 {
+    let bits = value.to_bits();
+    assert!(bits <= mask(width), "value does not fit in {width} bits");
+    bits << offset
+}
+
+/// `value` at `offset`, error when it does not fit in `width` bits
+#[inline]
+pub fn try_put<T: BitField>(value: &T, offset: u32, width: u32) -> Result<u64> {
+    let bits = value.to_bits();
+    if bits > mask(width) {
+        return Err(RapiraError::BitField);
+    }
+    Ok(bits << offset)
+}
+
+#[inline]
+pub fn write_bits(packed: u64, bytes: usize, slice: &mut [u8], cursor: &mut usize) {
+    extend(slice, cursor, &packed.to_le_bytes()[..bytes]);
+}
+
+#[inline]
+pub fn try_write_bits(
+    packed: u64,
+    bytes: usize,
+    slice: &mut [u8],
+    cursor: &mut usize,
+) -> Result<()> {
+    try_extend(slice, cursor, &packed.to_le_bytes()[..bytes])
+}
+
+/// bytes of the presence bitmap of a `#[rapira(sparse)]` struct with `fields` fields
+#[inline]
+pub const fn presence_size(fields: usize) -> usize {
+    fields.div_ceil(8)
+}
+
+/// presence bitmap of a `#[rapira(sparse)]` struct with `fields` fields,
+/// field `i` is bit `i % 8` of byte `i / 8`, unused bits must be zero
+#[inline]
+pub fn read_presence<'a>(slice: &mut &'a [u8], fields: usize) -> Result<&'a [u8]> {
+    let Some((presence, tail)) = slice.split_at_checked(presence_size(fields)) else {
+        return Err(RapiraError::SliceLen);
+    };
+    // bits of the last byte past the fields
+    if let Some(last) = presence.last()
+        && u32::from(*last) >> (fields - (presence.len() - 1) * 8) != 0
+    {
+        return Err(RapiraError::BitField);
+    }
+    *slice = tail;
+    Ok(presence)
+}
+
+/// presence of the next field of the bitmap, `bit` is advanced
+#[inline]
+pub fn next_present(presence: &[u8], bit: &mut usize) -> bool {
+    let present = presence[*bit / 8] & (1 << (*bit % 8)) != 0;
+    *bit += 1;
+    present
+}
+
+/// set presence of the next field of the bitmap, `bit` is advanced
+#[inline]
+pub fn set_next(presence: &mut [u8], bit: &mut usize, present: bool) {
+    if present {
+        presence[*bit / 8] |= 1 << (*bit % 8);
+    }
+    *bit += 1;
+}
@@ -0,0 +1,176 @@
+//! Runtime of the `#[rapira(max_len = N)]`, `#[rapira(non_empty)]` and
+//! `#[rapira(range = ..)]` field constraints.
+//!
+//! Length constraints are checked against the `u32` length prefix before the field is decoded,
+//! so an oversized string or collection is rejected without allocating it.
+//! The derive exports them by [`ConstraintSchema`].
+
+use core::ops::RangeBounds;
+
+use crate::{Rapira, RapiraError, RapiraFlags, Result};
+
+/// Strings, bytes and collections serialized with a `u32` length prefix.
+///
+/// `#[rapira(max_len = N)]` fields decode by [`LenPrefixed::from_slice_max_len`]:
+/// `N` replaces the [`MAX_CAP`](crate::Rapira::MAX_CAP) limit of the items for that field.
+pub trait LenPrefixed: Rapira {
+    /// `u32` length prefix of the next value, the slice is not advanced
+    #[inline]
+    fn peek_len(slice: &[u8]) -> Result<usize> {
+        let mut peek = slice;
+        Ok(u32::from_slice(&mut peek)? as usize)
+    }
+
+    /// `from_slice` with at most `max_len` items (bytes for strings and bytes)
+    #[inline]
+    fn from_slice_max_len(slice: &mut &[u8], max_len: usize) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Self::decode_max_len(slice, max_len, Decode::Plain)
+    }
+
+    /// decoding by `decode` with at most `max_len` items, collections read their items
+    /// by `decode` too
+    #[inline]
+    fn decode_max_len(slice: &mut &[u8], max_len: usize, decode: Decode) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        check_len(Self::peek_len(slice)?, false, Some(max_len))?;
+        decode.read(slice)
+    }
+}
+
+/// Decoding method of a `max_len` field: `from_slice`, `from_slice_versioned`
+/// or `from_slice_ctx` of the container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decode {
+    Plain,
+    Versioned(u16),
+    Ctx(RapiraFlags),
+}
+
+impl Decode {
+    #[inline]
+    pub fn read<T: Rapira>(self, slice: &mut &[u8]) -> Result<T> {
+        match self {
+            Decode::Plain => T::from_slice(slice),
+            Decode::Versioned(version) => T::from_slice_versioned(slice, version),
+            Decode::Ctx(flags) => T::from_slice_ctx(slice, flags),
+        }
+    }
+}
+
+/// Constraints of one field, for decoders in other languages to apply the same limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldConstraints {
+    /// path of the field as in `RapiraError::Validation`: `name`, `0`, `Variant.name`, `Variant.0`
+    pub field: &'static str,
+    pub max_len: Option<usize>,
+    pub non_empty: bool,
+    /// `range` as written in the attribute, without whitespace: `1..=100`
+    pub range: Option<&'static str>,
+    /// `CONSTRAINTS` of the type of a `#[rapira(flatten)]` field, its fields are on the wire
+    /// in place of this one; empty for other fields
+    pub flatten: &'static [FieldConstraints],
+}
+
+/// Constraint schema of a derived type: its fields with `#[rapira(max_len = N)]`,
+/// `#[rapira(non_empty)]` or `#[rapira(range = ..)]` and its `#[rapira(flatten)]` fields,
+/// in declaration order
+///
+/// ```
+/// use rapira::{Rapira, constraints::{ConstraintSchema, FieldConstraints}};
+///
+/// #[derive(Rapira)]
+/// struct Upload {
+///     #[rapira(max_len = 8, non_empty)]
+///     name: String,
+///     size: u64,
+///     #[rapira(range = 1..=100)]
+///     priority: u32,
+/// }
+///
+/// assert_eq!(
+///     Upload::CONSTRAINTS,
+///     [
+///         FieldConstraints { field: "name", max_len: Some(8), non_empty: true, range: None, flatten: &[] },
+///         FieldConstraints { field: "priority", max_len: None, non_empty: false, range: Some("1..=100"), flatten: &[] },
+///     ]
+/// );
+///
+/// #[derive(Rapira)]
+/// struct Job {
+///     id: u32,
+///     #[rapira(flatten)]
+///     upload: Upload,
+/// }
+///
+/// let flat = Job::flat_constraints();
+/// assert_eq!(flat[0].0, "upload.name");
+/// assert_eq!(flat[1].0, "upload.priority");
+/// ```
+pub trait ConstraintSchema {
+    const CONSTRAINTS: &'static [FieldConstraints];
+
+    /// `CONSTRAINTS` with the fields of `flatten` fields spliced in,
+    /// by their paths in `RapiraError::Validation`: `upload.name`
+    #[cfg(feature = "alloc")]
+    fn flat_constraints() -> alloc::vec::Vec<(alloc::string::String, FieldConstraints)> {
+        let mut flat = alloc::vec::Vec::new();
+        flatten_into(&mut flat, "", Self::CONSTRAINTS);
+        flat
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn flatten_into(
+    flat: &mut alloc::vec::Vec<(alloc::string::String, FieldConstraints)>,
+    prefix: &str,
+    constraints: &[FieldConstraints],
+) {
+    for constraint in constraints {
+        let path = alloc::format!("{prefix}{}", constraint.field);
+        // a `flatten` field has no constraints of its own
+        if constraint.flatten.is_empty() {
+            if constraint.max_len.is_some() || constraint.non_empty || constraint.range.is_some() {
+                flat.push((path, *constraint));
+            }
+        } else {
+            flatten_into(flat, &alloc::format!("{path}."), constraint.flatten);
+        }
+    }
+}
+
+/// check `len` of a `#[rapira(non_empty)]` and/or `#[rapira(max_len = N)]` field
+#[inline]
+pub fn check_len(len: usize, non_empty: bool, max_len: Option<usize>) -> Result<()> {
+    if non_empty && len == 0 {
+        #[cfg(feature = "alloc")]
+        return Err(RapiraError::validation("must not be empty"));
+        #[cfg(not(feature = "alloc"))]
+        return Err(RapiraError::SliceLen);
+    }
+    match max_len {
+        #[cfg(feature = "alloc")]
+        Some(max_len) if len > max_len => Err(RapiraError::validation(alloc::format!(
+            "len {len} exceeds max_len {max_len}"
+        ))),
+        #[cfg(not(feature = "alloc"))]
+        Some(max_len) if len > max_len => Err(RapiraError::MaxCapacity),
+        _ => Ok(()),
+    }
+}
+
+/// check `value` of a `#[rapira(range = ..)]` field
+#[inline]
+pub fn check_range<T: PartialOrd, R: RangeBounds<T>>(value: &T, range: R) -> Result<()> {
+    if range.contains(value) {
+        return Ok(());
+    }
+    #[cfg(feature = "alloc")]
+    return Err(RapiraError::validation("out of range"));
+    #[cfg(not(feature = "alloc"))]
+    return Err(RapiraError::Other("out of range"));
+}
@@ -0,0 +1,240 @@
+//! Self-describing envelope for versioned data.
+//!
+//! [`Rapira::from_slice_versioned`] expects the schema version to be stored
+//! **externally** (e.g. in DB metadata). Blobs written to files, caches or
+//! message queues have no such place, so the envelope stores the version
+//! in-band, in a compact header in front of the value:
+//!
+//! ```text
+//! magic: [u8; 2] = b"RV"
+//! flags: u8              bit 0 — schema hash is present, other bits must be 0
+//! version: u16 (LE)
+//! schema_hash: u64 (LE)  only if flags bit 0 is set
+//! value: T
+//! ```
+//!
+//! The header costs 5 bytes (13 with a schema hash). The value is decoded with
+//! [`Rapira::from_slice_versioned`] using the version from the header.
+//!
+//! ```rust,ignore
+//! use rapira::envelope::{self, Versioned};
+//!
+//! let bytes = rapira::serialize(&Versioned::new(user, 2));
+//! // ... later, possibly by a newer build:
+//! let user: User = envelope::deserialize(&bytes)?;
+//! ```
+
+use crate::{Rapira, RapiraError, Result, byte_rapira, extend, push, try_extend, try_push};
+
+/// First two bytes of every envelope.
+pub const MAGIC: [u8; 2] = *b"RV";
+
+/// Header size without schema hash: magic + flags + version.
+pub const HEADER_MIN_SIZE: usize = 2 + 1 + 2;
+
+const FLAG_SCHEMA_HASH: u8 = 1;
+
+/// Envelope header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// schema version the value is written with
+    pub version: u16,
+    /// optional hash of the schema, checked by [`deserialize_with_hash`]
+    pub schema_hash: Option<u64>,
+}
+
+impl Header {
+    #[inline]
+    pub const fn size(&self) -> usize {
+        match self.schema_hash {
+            Some(_) => HEADER_MIN_SIZE + 8,
+            None => HEADER_MIN_SIZE,
+        }
+    }
+
+    /// read header and advance slice to the value
+    pub fn from_slice(slice: &mut &[u8]) -> Result<Self> {
+        let magic = <[u8; 2]>::from_slice(slice)?;
+        if magic != MAGIC {
+            return Err(RapiraError::EnvelopeHeader);
+        }
+
+        let flags = byte_rapira::from_slice(slice)?;
+        if flags & !FLAG_SCHEMA_HASH != 0 {
+            return Err(RapiraError::EnvelopeHeader);
+        }
+
+        let version = u16::from_slice(slice)?;
+        let schema_hash = if flags & FLAG_SCHEMA_HASH != 0 {
+            Some(u64::from_slice(slice)?)
+        } else {
+            None
+        };
+
+        Ok(Header {
+            version,
+            schema_hash,
+        })
+    }
+
+    #[inline]
+    fn flags(&self) -> u8 {
+        match self.schema_hash {
+            Some(_) => FLAG_SCHEMA_HASH,
+            None => 0,
+        }
+    }
+
+    pub fn convert_to_bytes(&self, slice: &mut [u8], cursor: &mut usize) {
+        extend(slice, cursor, &MAGIC);
+        push(slice, cursor, self.flags());
+        self.version.convert_to_bytes(slice, cursor);
+        if let Some(hash) = self.schema_hash {
+            hash.convert_to_bytes(slice, cursor);
+        }
+    }
+
+    pub fn try_convert_to_bytes(&self, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        try_extend(slice, cursor, &MAGIC)?;
+        try_push(slice, cursor, self.flags())?;
+        self.version.try_convert_to_bytes(slice, cursor)?;
+        if let Some(hash) = self.schema_hash {
+            hash.try_convert_to_bytes(slice, cursor)?;
+        }
+        Ok(())
+    }
+}
+
+/// Value with an in-band version header.
+///
+/// Serialization writes the header and then the value with the layout of
+/// `version` ([`Rapira::convert_to_bytes_versioned`]), so an older version
+/// can be targeted during rolling upgrades. Deserialization reads the header and decodes the value with
+/// [`Rapira::from_slice_versioned`]; `version` and `schema_hash` of the
+/// result are the ones found in the header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Versioned<T> {
+    pub version: u16,
+    pub schema_hash: Option<u64>,
+    pub value: T,
+}
+
+impl<T> Versioned<T> {
+    #[inline]
+    pub const fn new(value: T, version: u16) -> Self {
+        Versioned {
+            version,
+            schema_hash: None,
+            value,
+        }
+    }
+
+    #[inline]
+    pub const fn with_schema_hash(mut self, schema_hash: u64) -> Self {
+        self.schema_hash = Some(schema_hash);
+        self
+    }
+
+    #[inline]
+    pub const fn header(&self) -> Header {
+        Header {
+            version: self.version,
+            schema_hash: self.schema_hash,
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Rapira> Rapira for Versioned<T> {
+    const MIN_SIZE: usize = HEADER_MIN_SIZE + T::MIN_SIZE;
+
+    #[inline]
+    fn size(&self) -> usize {
+        self.header().size() + self.value.size_versioned(self.version)
+    }
+
+    /// check_bytes is not version-aware, so the value is checked
+    /// by decoding it with the header version
+    #[inline]
+    fn check_bytes(slice: &mut &[u8]) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let header = Header::from_slice(slice)?;
+        T::from_slice_versioned(slice, header.version)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice(slice: &mut &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let header = Header::from_slice(slice)?;
+        let value = T::from_slice_versioned(slice, header.version)?;
+        Ok(Versioned {
+            version: header.version,
+            schema_hash: header.schema_hash,
+            value,
+        })
+    }
+
+    #[inline]
+    fn try_convert_to_bytes(&self, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        self.header().try_convert_to_bytes(slice, cursor)?;
+        let size = self.value.size_versioned(self.version);
+        if slice.len() < *cursor + size {
+            return Err(RapiraError::SliceLen);
+        }
+        self.value
+            .convert_to_bytes_versioned(slice, cursor, self.version);
+        Ok(())
+    }
+
+    #[inline]
+    fn convert_to_bytes(&self, slice: &mut [u8], cursor: &mut usize) {
+        self.header().convert_to_bytes(slice, cursor);
+        self.value
+            .convert_to_bytes_versioned(slice, cursor, self.version);
+    }
+}
+
+/// read envelope header without decoding the value
+pub fn read_header(mut bytes: &[u8]) -> Result<Header> {
+    Header::from_slice(&mut bytes)
+}
+
+/// serialize value with envelope header, using the layout of `version`
+#[cfg(feature = "alloc")]
+pub fn serialize<T: Rapira>(item: &T, version: u16) -> Vec<u8> {
+    let header = Header {
+        version,
+        schema_hash: None,
+    };
+    let mut bytes = vec![0u8; header.size() + item.size_versioned(version)];
+    let mut cursor = 0;
+    header.convert_to_bytes(&mut bytes, &mut cursor);
+    item.convert_to_bytes_versioned(&mut bytes, &mut cursor, version);
+    bytes
+}
+
+/// deserialize value from envelope, using version from header
+pub fn deserialize<T: Rapira>(mut bytes: &[u8]) -> Result<T> {
+    let header = Header::from_slice(&mut bytes)?;
+    T::from_slice_versioned(&mut bytes, header.version)
+}
+
+/// deserialize value from envelope and check schema hash, if header has it
+pub fn deserialize_with_hash<T: Rapira>(mut bytes: &[u8], schema_hash: u64) -> Result<T> {
+    let header = Header::from_slice(&mut bytes)?;
+    if let Some(hash) = header.schema_hash
+        && hash != schema_hash
+    {
+        return Err(RapiraError::SchemaHash);
+    }
+    T::from_slice_versioned(&mut bytes, header.version)
+}
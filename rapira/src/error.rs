@@ -1,3 +1,5 @@
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, boxed::Box, vec::Vec};
 use core::array::TryFromSliceError;
 #[cfg(feature = "std")]
 use std::io::Error as IoError;
@@ -31,6 +33,26 @@ pub enum RapiraError {
     MaxSize,
     #[cfg_attr(feature = "std", error("max capacity error"))]
     MaxCapacity,
+    #[cfg_attr(feature = "std", error("envelope header error"))]
+    EnvelopeHeader,
+    #[cfg_attr(feature = "std", error("envelope schema hash mismatch"))]
+    SchemaHash,
+    #[cfg_attr(feature = "std", error("try_from conversion error"))]
+    Conversion,
+    #[cfg_attr(feature = "std", error("bit field value out of range"))]
+    BitField,
+    #[cfg_attr(feature = "std", error("non-zero padding"))]
+    Padding,
+    #[cfg_attr(feature = "std", error("magic bytes mismatch"))]
+    Magic,
+    #[cfg_attr(
+        feature = "std",
+        error("count field does not match the collection length")
+    )]
+    Count,
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(feature = "std", error("validation error: {0}"))]
+    Validation(Box<ValidationError>),
     #[cfg_attr(feature = "postcard", error(transparent))]
     #[cfg(feature = "postcard")]
     Postcard(#[from] postcard::Error),
@@ -44,3 +66,51 @@ pub enum RapiraError {
 }
 
 pub type Result<T, E = RapiraError> = core::result::Result<T, E>;
+
+impl RapiraError {
+    /// [`RapiraError::Validation`] with `message`, for `#[rapira(validate = path)]` hooks
+    #[cfg(feature = "alloc")]
+    pub fn validation(message: impl Into<Cow<'static, str>>) -> Self {
+        RapiraError::Validation(Box::new(ValidationError {
+            path: Vec::new(),
+            message: message.into(),
+        }))
+    }
+
+    /// prepend `field` to the path of [`RapiraError::Validation`], other errors are unchanged
+    #[inline]
+    pub fn in_field(self, field: &'static str) -> Self {
+        #[cfg(feature = "alloc")]
+        if let RapiraError::Validation(mut err) = self {
+            err.path.insert(0, field);
+            return RapiraError::Validation(err);
+        }
+        let _ = field;
+        self
+    }
+}
+
+/// Failed `#[rapira(validate = path)]` hook or field constraint
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// fields from the outermost value to the invalid one, empty for the value itself
+    pub path: Vec<&'static str>,
+    pub message: Cow<'static, str>,
+}
+
+#[cfg(feature = "alloc")]
+impl core::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (idx, field) in self.path.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(".")?;
+            }
+            f.write_str(field)?;
+        }
+        if !self.path.is_empty() {
+            f.write_str(": ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
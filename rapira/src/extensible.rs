@@ -0,0 +1,63 @@
+//! Runtime of `#[rapira(extensible)]`: the encoded struct, or every variant payload
+//! of an enum after its tag, follows a `u32` length prefix (big-endian with
+//! `#[rapira(endian = "big")]`), so a reader skips what it doesn't know:
+//!
+//! ```text
+//! tag: u8 | u16 | u32    enums only
+//! len: u32
+//! payload: [u8; len]
+//! ```
+//!
+//! A struct or a variant is decoded from its payload only, bytes left after its fields
+//! (appended by a newer writer) are ignored, or kept by the `#[rapira(rest)]` field of
+//! a struct. Unknown tags go to the `#[rapira(unknown)]` variant with the raw payload.
+//! Both are written back unchanged.
+
+use crate::{LEN_SIZE, RapiraError, Result, extend, try_extend};
+
+/// the payload after the length prefix, `slice` is advanced past it
+#[inline]
+pub fn payload<'a>(slice: &mut &'a [u8], big: bool) -> Result<&'a [u8]> {
+    let Some((len, tail)) = slice.split_first_chunk::<LEN_SIZE>() else {
+        return Err(RapiraError::SliceLen);
+    };
+    let len = if big {
+        u32::from_be_bytes(*len)
+    } else {
+        u32::from_le_bytes(*len)
+    };
+    let Some((payload, tail)) = tail.split_at_checked(len as usize) else {
+        return Err(RapiraError::SliceLen);
+    };
+    *slice = tail;
+    Ok(payload)
+}
+
+/// reserve the length prefix, returns its offset for [`end`]
+#[inline]
+pub fn begin(cursor: &mut usize) -> usize {
+    let start = *cursor;
+    *cursor += LEN_SIZE;
+    start
+}
+
+/// write the length prefix at `start` for the payload written since [`begin`]
+#[inline]
+pub fn end(slice: &mut [u8], cursor: &usize, start: usize, big: bool) {
+    extend(slice, &mut { start }, &prefix(*cursor, start, big));
+}
+
+#[inline]
+pub fn try_end(slice: &mut [u8], cursor: &usize, start: usize, big: bool) -> Result<()> {
+    try_extend(slice, &mut { start }, &prefix(*cursor, start, big))
+}
+
+#[inline]
+fn prefix(cursor: usize, start: usize, big: bool) -> [u8; LEN_SIZE] {
+    let len = (cursor - start - LEN_SIZE) as u32;
+    if big {
+        len.to_be_bytes()
+    } else {
+        len.to_le_bytes()
+    }
+}
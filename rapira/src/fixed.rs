@@ -0,0 +1,197 @@
+//! Runtime of `#[rapira(fixed)]`: `Option<T>` fields are written as the tag and
+//! the payload zero-padded to `T::STATIC_SIZE`, data enums pad every variant to
+//! the largest one, so both keep a `STATIC_SIZE`. Padding must be zero.
+
+use crate::{Rapira, RapiraError, RapiraFlags, RapiraWith, Result, byte_rapira, push, try_push};
+
+/// `STATIC_SIZE` of `#[rapira(fixed)]` values, compile error for a payload without one
+#[inline]
+pub const fn size(static_size: Option<usize>) -> usize {
+    match static_size {
+        Some(size) => size,
+        None => panic!("#[rapira(fixed)] needs payloads with a static size"),
+    }
+}
+
+/// `STATIC_SIZE` of a `#[rapira(fixed)]` enum: the tag and the largest variant,
+/// `arr` - static sizes of the variants
+pub const fn enum_size<const N: usize>(arr: [Option<usize>; N], tag_size: usize) -> Option<usize> {
+    let mut i = 0;
+    let mut max = 0;
+    while i < arr.len() {
+        let item = size(arr[i]);
+        if item > max {
+            max = item;
+        }
+        i += 1;
+    }
+    Some(max + tag_size)
+}
+
+/// skip `len` padding bytes, error when any of them is not zero
+#[inline]
+pub fn read_padding(slice: &mut &[u8], len: usize) -> Result<()> {
+    let Some((padding, tail)) = slice.split_at_checked(len) else {
+        return Err(RapiraError::SliceLen);
+    };
+    if padding.iter().any(|byte| *byte != 0) {
+        return Err(RapiraError::Padding);
+    }
+    *slice = tail;
+    Ok(())
+}
+
+#[inline]
+pub fn write_padding(slice: &mut [u8], cursor: &mut usize, len: usize) {
+    let end = *cursor + len;
+    slice[*cursor..end].fill(0);
+    *cursor = end;
+}
+
+#[inline]
+pub fn try_write_padding(slice: &mut [u8], cursor: &mut usize, len: usize) -> Result<()> {
+    let end = *cursor + len;
+    slice
+        .get_mut(*cursor..end)
+        .ok_or(RapiraError::SliceLen)?
+        .fill(0);
+    *cursor = end;
+    Ok(())
+}
+
+/// `Option<T>` as the tag and `T::STATIC_SIZE` bytes, zeros for `None`:
+/// `with` adapter of `#[rapira(fixed)]` fields
+#[allow(non_camel_case_types)]
+pub enum fixed_option {}
+
+impl fixed_option {
+    #[inline]
+    const fn payload<T: Rapira>() -> usize {
+        size(T::STATIC_SIZE)
+    }
+
+    /// `read` the payload of `Some`, padding of `None`
+    #[inline]
+    fn read<T: Rapira, R>(
+        slice: &mut &[u8],
+        read: impl FnOnce(&mut &[u8]) -> Result<R>,
+    ) -> Result<Option<R>> {
+        if byte_rapira::from_slice(slice)? != 0 {
+            read(slice).map(Some)
+        } else {
+            read_padding(slice, const { Self::payload::<T>() })?;
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Rapira> RapiraWith<Option<T>> for fixed_option {
+    const STATIC_SIZE: Option<usize> = Some(1 + Self::payload::<T>());
+    const MIN_SIZE: usize = 1 + Self::payload::<T>();
+
+    #[inline]
+    fn size(_: &Option<T>) -> usize {
+        const { 1 + Self::payload::<T>() }
+    }
+
+    #[inline]
+    fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+        Self::read::<T, _>(slice, T::check_bytes).map(|_| ())
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()> {
+        Self::read::<T, _>(slice, |slice| T::check_bytes_ctx(slice, flags)).map(|_| ())
+    }
+
+    #[inline]
+    fn from_slice(slice: &mut &[u8]) -> Result<Option<T>> {
+        Self::read::<T, _>(slice, T::from_slice)
+    }
+
+    #[inline]
+    unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<Option<T>> {
+        Self::read::<T, _>(slice, |slice| unsafe { T::from_slice_unchecked(slice) })
+    }
+
+    #[inline]
+    unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<Option<T>> {
+        Self::read::<T, _>(slice, |slice| unsafe { T::from_slice_unsafe(slice) })
+    }
+
+    #[inline]
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Option<T>> {
+        Self::read::<T, _>(slice, |slice| T::from_slice_versioned(slice, version))
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Option<T>> {
+        Self::read::<T, _>(slice, |slice| T::from_slice_ctx(slice, flags))
+    }
+
+    #[inline]
+    fn convert_to_bytes(item: &Option<T>, slice: &mut [u8], cursor: &mut usize) {
+        match item {
+            Some(item) => {
+                push(slice, cursor, 1);
+                item.convert_to_bytes(slice, cursor);
+            }
+            None => {
+                push(slice, cursor, 0);
+                write_padding(slice, cursor, const { Self::payload::<T>() });
+            }
+        }
+    }
+
+    #[inline]
+    fn try_convert_to_bytes(item: &Option<T>, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        match item {
+            Some(item) => {
+                try_push(slice, cursor, 1)?;
+                item.try_convert_to_bytes(slice, cursor)
+            }
+            None => {
+                try_push(slice, cursor, 0)?;
+                try_write_padding(slice, cursor, const { Self::payload::<T>() })
+            }
+        }
+    }
+
+    #[inline]
+    fn convert_to_bytes_versioned(
+        item: &Option<T>,
+        slice: &mut [u8],
+        cursor: &mut usize,
+        version: u16,
+    ) {
+        match item {
+            Some(item) => {
+                push(slice, cursor, 1);
+                item.convert_to_bytes_versioned(slice, cursor, version);
+            }
+            None => {
+                push(slice, cursor, 0);
+                write_padding(slice, cursor, const { Self::payload::<T>() });
+            }
+        }
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(
+        item: &Option<T>,
+        slice: &mut [u8],
+        cursor: &mut usize,
+        flags: RapiraFlags,
+    ) {
+        match item {
+            Some(item) => {
+                push(slice, cursor, 1);
+                item.convert_to_bytes_ctx(slice, cursor, flags);
+            }
+            None => {
+                push(slice, cursor, 0);
+                write_padding(slice, cursor, const { Self::payload::<T>() });
+            }
+        }
+    }
+}
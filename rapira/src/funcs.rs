@@ -82,7 +82,7 @@ pub fn read_to<T: Rapira>(mut bytes: &[u8], mut iter: impl Extend<T>) -> Result<
 // }
 
 /// Check oversize vec and other items with capacity initialization
-/// with MaxCapacity trait
+/// with [`MAX_CAP`](crate::Rapira::MAX_CAP) limits of the item types
 /// (max memory limit attack...)
 ///
 /// Check cursor oveflow,
@@ -96,13 +96,30 @@ where
 
 /// Deserialize with schema version awareness.
 /// Version is stored externally (e.g. in DB metadata), not in the serialized data.
-pub fn deserialize_versioned<T>(mut bytes: &[u8], version: u8) -> Result<T>
+pub fn deserialize_versioned<T>(mut bytes: &[u8], version: u16) -> Result<T>
 where
     T: Rapira + Sized,
 {
     T::from_slice_versioned(&mut bytes, version)
 }
 
+/// Size of value serialized at schema `version`.
+/// No `STATIC_SIZE` shortcut: statically sized struct can be smaller at an older version.
+#[inline]
+pub fn size_versioned<T: Rapira>(item: &T, version: u16) -> usize {
+    item.size_versioned(version)
+}
+
+/// Serialize with the layout of schema `version`,
+/// readable by `deserialize_versioned(bytes, version)`.
+#[cfg(feature = "alloc")]
+pub fn serialize_versioned<T: Rapira>(item: &T, version: u16) -> Vec<u8> {
+    let value_size = item.size_versioned(version);
+    let mut bytes: Vec<u8> = vec![0u8; value_size];
+    item.convert_to_bytes_versioned(&mut bytes, &mut 0, version);
+    bytes
+}
+
 /// # Safety
 ///
 /// NOT check oversize vec and other items with capacity initialization
@@ -156,3 +173,8 @@ pub fn serialize_ctx<T: Rapira>(item: &T, flags: RapiraFlags) -> Vec<u8> {
 pub fn deserialize_ctx<T: Rapira + Sized>(mut bytes: &[u8], flags: RapiraFlags) -> Result<T> {
     T::from_slice_ctx(&mut bytes, flags)
 }
+
+/// Check bytes with context flags.
+pub fn check_bytes_ctx<T: Rapira + Sized>(mut bytes: &[u8], flags: RapiraFlags) -> Result<()> {
+    T::check_bytes_ctx(&mut bytes, flags)
+}
@@ -1,6 +1,7 @@
 #[cfg(feature = "arrayvec")]
 impl<T: crate::Rapira, const CAP: usize> crate::Rapira for arrayvec::ArrayVec<T, CAP> {
     const MIN_SIZE: usize = crate::LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -40,7 +41,7 @@ impl<T: crate::Rapira, const CAP: usize> crate::Rapira for arrayvec::ArrayVec<T,
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> crate::Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> crate::Result<Self>
     where
         Self: Sized,
     {
@@ -113,6 +114,70 @@ impl<T: crate::Rapira, const CAP: usize> crate::Rapira for arrayvec::ArrayVec<T,
             val.convert_to_bytes(slice, cursor);
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_versioned(slice, cursor, version);
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_versioned(version))
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<()>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+        for _ in 0..len {
+            T::check_bytes_ctx(slice, flags)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = usize::from_slice(slice)?;
+        if len > CAP {
+            return Err(crate::RapiraError::SliceLen);
+        }
+        let mut vec = Self::new_const();
+        for _ in 0..len {
+            let val = T::from_slice_ctx(slice, flags)?;
+            vec.push(val);
+        }
+        Ok(vec)
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(
+        &self,
+        slice: &mut [u8],
+        cursor: &mut usize,
+        flags: crate::RapiraFlags,
+    ) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: crate::RapiraFlags) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_ctx(flags))
+    }
 }
 
 #[cfg(feature = "arrayvec")]
@@ -179,9 +244,13 @@ impl<const CAP: usize> crate::Rapira for arrayvec::ArrayString<CAP> {
     }
 }
 
+#[cfg(feature = "arrayvec")]
+impl<const CAP: usize> crate::LenPrefixed for arrayvec::ArrayString<CAP> {}
+
 #[cfg(feature = "smallvec")]
 impl<T: crate::Rapira, const CAP: usize> crate::Rapira for smallvec::SmallVec<[T; CAP]> {
     const MIN_SIZE: usize = crate::LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -210,17 +279,15 @@ impl<T: crate::Rapira, const CAP: usize> crate::Rapira for smallvec::SmallVec<[T
     where
         Self: Sized,
     {
-        use crate::max_cap::{SMALLVEC_MAX_CAP, SMALLVEC_MAX_SIZE_OF};
-
         let len = u32::from_slice(slice)? as usize;
 
-        if len > SMALLVEC_MAX_CAP {
+        if len > T::SMALL_MAX_CAP {
             return Err(crate::RapiraError::MaxCapacity);
         }
 
         let size = std::mem::size_of::<Self>() * len;
 
-        if size > SMALLVEC_MAX_SIZE_OF {
+        if size > T::SMALL_MAX_SIZE_OF {
             return Err(crate::RapiraError::MaxSize);
         }
 
@@ -235,21 +302,19 @@ impl<T: crate::Rapira, const CAP: usize> crate::Rapira for smallvec::SmallVec<[T
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> crate::Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> crate::Result<Self>
     where
         Self: Sized,
     {
-        use crate::max_cap::{SMALLVEC_MAX_CAP, SMALLVEC_MAX_SIZE_OF};
-
         let len = u32::from_slice(slice)? as usize;
 
-        if len > SMALLVEC_MAX_CAP {
+        if len > T::SMALL_MAX_CAP {
             return Err(crate::RapiraError::MaxCapacity);
         }
 
         let size = std::mem::size_of::<Self>() * len;
 
-        if size > SMALLVEC_MAX_SIZE_OF {
+        if size > T::SMALL_MAX_SIZE_OF {
             return Err(crate::RapiraError::MaxSize);
         }
 
@@ -318,6 +383,116 @@ impl<T: crate::Rapira, const CAP: usize> crate::Rapira for smallvec::SmallVec<[T
             val.convert_to_bytes(slice, cursor);
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_versioned(slice, cursor, version);
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_versioned(version))
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<()>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        for _ in 0..len {
+            T::check_bytes_ctx(slice, flags)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        if len > T::SMALL_MAX_CAP {
+            return Err(crate::RapiraError::MaxCapacity);
+        }
+
+        let size = std::mem::size_of::<Self>() * len;
+
+        if size > T::SMALL_MAX_SIZE_OF {
+            return Err(crate::RapiraError::MaxSize);
+        }
+
+        let mut vec = Self::with_capacity(len);
+
+        for _ in 0..len {
+            let val = T::from_slice_ctx(slice, flags)?;
+            vec.push(val);
+        }
+
+        Ok(vec)
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(
+        &self,
+        slice: &mut [u8],
+        cursor: &mut usize,
+        flags: crate::RapiraFlags,
+    ) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: crate::RapiraFlags) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_ctx(flags))
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<T: crate::Rapira, const CAP: usize> crate::LenPrefixed for smallvec::SmallVec<[T; CAP]> {
+    #[inline]
+    fn decode_max_len(
+        slice: &mut &[u8],
+        max_len: usize,
+        decode: crate::constraints::Decode,
+    ) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        use crate::Rapira;
+
+        let len = u32::from_slice(slice)? as usize;
+
+        crate::constraints::check_len(len, false, Some(max_len))?;
+
+        let size = std::mem::size_of::<Self>() * len;
+
+        if size > T::SMALL_MAX_SIZE_OF {
+            return Err(crate::RapiraError::MaxSize);
+        }
+
+        let mut vec = Self::with_capacity(len);
+
+        for _ in 0..len {
+            let val = decode.read::<T>(slice)?;
+            vec.push(val);
+        }
+
+        Ok(vec)
+    }
 }
 
 #[cfg(feature = "bytes")]
@@ -377,6 +552,9 @@ impl crate::Rapira for bytes::Bytes {
     }
 }
 
+#[cfg(feature = "bytes")]
+impl crate::LenPrefixed for bytes::Bytes {}
+
 #[cfg(feature = "byteview")]
 impl crate::Rapira for byteview::StrView {
     const MIN_SIZE: usize = crate::LEN_SIZE;
@@ -434,6 +612,9 @@ impl crate::Rapira for byteview::StrView {
     }
 }
 
+#[cfg(feature = "byteview")]
+impl crate::LenPrefixed for byteview::StrView {}
+
 #[cfg(feature = "byteview")]
 impl crate::Rapira for byteview::ByteView {
     const MIN_SIZE: usize = crate::LEN_SIZE;
@@ -491,6 +672,9 @@ impl crate::Rapira for byteview::ByteView {
     }
 }
 
+#[cfg(feature = "byteview")]
+impl crate::LenPrefixed for byteview::ByteView {}
+
 #[cfg(feature = "fjall")]
 impl crate::Rapira for fjall::Slice {
     const MIN_SIZE: usize = crate::LEN_SIZE;
@@ -548,302 +732,456 @@ impl crate::Rapira for fjall::Slice {
     }
 }
 
+#[cfg(feature = "fjall")]
+impl crate::LenPrefixed for fjall::Slice {}
+
 #[cfg(feature = "zerocopy")]
-pub mod zero {
+mod zero_with {
     use core::{marker::PhantomData, mem::size_of};
 
     use zerocopy::{FromBytes, Immutable, IntoBytes};
 
-    use crate::{extend, try_extend};
+    use crate::{RapiraWith, extend, try_extend};
 
-    pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize>
-    where
-        T: Sized,
-    {
-        Some(size_of::<T>())
-    }
+    /// `with` adapter for zerocopy types: bytes of the value as is
+    #[allow(non_camel_case_types)]
+    pub enum zero {}
 
-    pub const fn min_size<T>(_: PhantomData<T>) -> usize
-    where
-        T: Sized,
-    {
-        size_of::<T>()
-    }
-
-    #[inline]
-    pub fn size<T>(_: &T) -> usize
-    where
-        T: Sized,
-    {
-        size_of::<T>()
-    }
+    impl zero {
+        pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize>
+        where
+            T: Sized,
+        {
+            Some(size_of::<T>())
+        }
 
-    #[inline]
-    pub fn check_bytes<T>(_: PhantomData<T>, slice: &mut &[u8]) -> crate::Result<()>
-    where
-        T: Sized,
-    {
-        let size = size_of::<T>();
+        pub const fn min_size<T>(_: PhantomData<T>) -> usize
+        where
+            T: Sized,
+        {
+            size_of::<T>()
+        }
 
-        *slice = slice.get(size..).ok_or(crate::RapiraError::SliceLen)?;
+        #[inline]
+        pub fn size<T>(_: &T) -> usize
+        where
+            T: Sized,
+        {
+            size_of::<T>()
+        }
 
-        Ok(())
-    }
+        #[inline]
+        pub fn check_bytes<T>(_: PhantomData<T>, slice: &mut &[u8]) -> crate::Result<()>
+        where
+            T: Sized,
+        {
+            let size = size_of::<T>();
 
-    #[inline]
-    pub fn from_slice<T>(slice: &mut &[u8]) -> crate::Result<T>
-    where
-        T: FromBytes + Sized,
-    {
-        let size = size_of::<T>();
-        let bytes: &[u8] = slice.get(..size).ok_or(crate::RapiraError::SliceLen)?;
+            *slice = slice.get(size..).ok_or(crate::RapiraError::SliceLen)?;
 
-        *slice = slice.get(size..).ok_or(crate::RapiraError::SliceLen)?;
+            Ok(())
+        }
 
-        let t: T = FromBytes::read_from_bytes(bytes)
-            .map_err(|_| crate::RapiraError::Other("zerocopy error"))?;
-        Ok(t)
-    }
+        #[inline]
+        pub fn from_slice<T>(slice: &mut &[u8]) -> crate::Result<T>
+        where
+            T: FromBytes + Sized,
+        {
+            let size = size_of::<T>();
+            let bytes: &[u8] = slice.get(..size).ok_or(crate::RapiraError::SliceLen)?;
 
-    #[inline]
-    pub fn from_slice_unchecked<T>(slice: &mut &[u8]) -> crate::Result<T>
-    where
-        T: FromBytes + Sized,
-    {
-        from_slice(slice)
-    }
+            *slice = slice.get(size..).ok_or(crate::RapiraError::SliceLen)?;
 
-    /// .
-    ///
-    /// # Panics
-    ///
-    /// Panics if .
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if .
-    ///
-    /// # Safety
-    ///
-    /// .
-    #[inline]
-    pub unsafe fn from_slice_unsafe<T>(slice: &mut &[u8]) -> crate::Result<T>
-    where
-        T: FromBytes + Sized,
-    {
-        unsafe {
-            let size = size_of::<T>();
-            let bytes: &[u8] = slice.get_unchecked(..size);
-            *slice = slice.get_unchecked(size..);
             let t: T = FromBytes::read_from_bytes(bytes)
                 .map_err(|_| crate::RapiraError::Other("zerocopy error"))?;
             Ok(t)
         }
-    }
 
-    #[inline]
-    pub fn convert_to_bytes<T>(item: &T, slice: &mut [u8], cursor: &mut usize)
-    where
-        T: Immutable + IntoBytes + Sized,
-    {
-        let bytes = item.as_bytes();
-        extend(slice, cursor, bytes);
+        #[inline]
+        pub fn from_slice_unchecked<T>(slice: &mut &[u8]) -> crate::Result<T>
+        where
+            T: FromBytes + Sized,
+        {
+            Self::from_slice(slice)
+        }
+
+        /// .
+        ///
+        /// # Panics
+        ///
+        /// Panics if .
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if .
+        ///
+        /// # Safety
+        ///
+        /// .
+        #[inline]
+        pub unsafe fn from_slice_unsafe<T>(slice: &mut &[u8]) -> crate::Result<T>
+        where
+            T: FromBytes + Sized,
+        {
+            unsafe {
+                let size = size_of::<T>();
+                let bytes: &[u8] = slice.get_unchecked(..size);
+                *slice = slice.get_unchecked(size..);
+                let t: T = FromBytes::read_from_bytes(bytes)
+                    .map_err(|_| crate::RapiraError::Other("zerocopy error"))?;
+                Ok(t)
+            }
+        }
+
+        #[inline]
+        pub fn convert_to_bytes<T>(item: &T, slice: &mut [u8], cursor: &mut usize)
+        where
+            T: Immutable + IntoBytes + Sized,
+        {
+            let bytes = item.as_bytes();
+            extend(slice, cursor, bytes);
+        }
+
+        #[inline]
+        pub fn try_convert_to_bytes<T>(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+        ) -> crate::Result<()>
+        where
+            T: Immutable + IntoBytes + Sized,
+        {
+            let bytes = item.as_bytes();
+            try_extend(slice, cursor, bytes)?;
+            Ok(())
+        }
     }
 
-    #[inline]
-    pub fn try_convert_to_bytes<T>(
-        item: &T,
-        slice: &mut [u8],
-        cursor: &mut usize,
-    ) -> crate::Result<()>
+    impl<T> RapiraWith<T> for zero
     where
-        T: Immutable + IntoBytes + Sized,
+        T: FromBytes + Immutable + IntoBytes + Sized,
     {
-        let bytes = item.as_bytes();
-        try_extend(slice, cursor, bytes)?;
-        Ok(())
+        const STATIC_SIZE: Option<usize> = Some(size_of::<T>());
+        const MIN_SIZE: usize = size_of::<T>();
+
+        #[inline]
+        fn size(item: &T) -> usize {
+            Self::size(item)
+        }
+
+        #[inline]
+        fn check_bytes(slice: &mut &[u8]) -> crate::Result<()> {
+            Self::check_bytes::<T>(PhantomData, slice)
+        }
+
+        #[inline]
+        fn from_slice(slice: &mut &[u8]) -> crate::Result<T> {
+            Self::from_slice(slice)
+        }
+
+        #[inline]
+        unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> crate::Result<T> {
+            unsafe { Self::from_slice_unsafe(slice) }
+        }
+
+        #[inline]
+        fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+            Self::convert_to_bytes(item, slice, cursor)
+        }
+
+        #[inline]
+        fn try_convert_to_bytes(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+        ) -> crate::Result<()> {
+            Self::try_convert_to_bytes(item, slice, cursor)
+        }
     }
 }
 
+#[cfg(feature = "zerocopy")]
+pub use zero_with::zero;
+
 #[cfg(feature = "bytemuck")]
-pub mod bytemuck {
+mod bytemuck_with {
     use core::{marker::PhantomData, mem::size_of};
 
-    use bytemuck::{Pod, Zeroable};
+    use ::bytemuck::Pod;
 
-    pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize>
-    where
-        T: Sized,
-    {
-        None
-    }
+    use crate::{RapiraWith, extend, try_extend};
 
-    pub const fn min_size<T>(_: PhantomData<T>) -> usize
-    where
-        T: Sized,
-    {
-        size_of::<T>()
-    }
+    /// `with` adapter for bytemuck `Pod` types: bytes of the value as is
+    #[allow(non_camel_case_types)]
+    pub enum bytemuck {}
 
-    #[inline]
-    pub fn size<T>(val: &T) -> usize
-    where
-        T: Sized + Pod + Zeroable,
-    {
-        size_of::<T>()
-    }
+    impl bytemuck {
+        pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize>
+        where
+            T: Sized,
+        {
+            Some(size_of::<T>())
+        }
 
-    #[inline]
-    pub fn convert_to_bytes<T>(item: &T, slice: &mut [u8], cursor: &mut usize)
-    where
-        T: Immutable + Pod + Zeroable + Sized,
-    {
-        let bytes = bytemuck::bytes_of(item);
-        extend(slice, cursor, bytes);
-    }
+        pub const fn min_size<T>(_: PhantomData<T>) -> usize
+        where
+            T: Sized,
+        {
+            size_of::<T>()
+        }
 
-    #[inline]
-    pub fn try_convert_to_bytes<T>(
-        item: &T,
-        slice: &mut [u8],
-        cursor: &mut usize,
-    ) -> crate::Result<()>
-    where
-        T: Immutable + Pod + Zeroable + Sized,
-    {
-        let bytes = bytemuck::bytes_of(item);
-        try_extend(slice, cursor, bytes)?;
-        Ok(())
-    }
+        #[inline]
+        pub fn size<T>(_: &T) -> usize
+        where
+            T: Pod,
+        {
+            size_of::<T>()
+        }
 
-    #[inline]
-    pub fn convert_from_bytes<T>(slice: &[u8], cursor: &mut usize) -> Option<T>
-    where
-        T: Immutable + Pod + Zeroable + Sized,
-    {
-        let bytes = slice.get(*cursor..*cursor + size_of::<T>())?;
-        *cursor += size_of::<T>();
-        Some(bytemuck::from_bytes(bytes))
+        #[inline]
+        pub fn check_bytes<T>(_: PhantomData<T>, slice: &mut &[u8]) -> crate::Result<()>
+        where
+            T: Sized,
+        {
+            let size = size_of::<T>();
+
+            *slice = slice.get(size..).ok_or(crate::RapiraError::SliceLen)?;
+
+            Ok(())
+        }
+
+        #[inline]
+        pub fn from_slice<T>(slice: &mut &[u8]) -> crate::Result<T>
+        where
+            T: Pod,
+        {
+            let size = size_of::<T>();
+            let bytes: &[u8] = slice.get(..size).ok_or(crate::RapiraError::SliceLen)?;
+
+            *slice = slice.get(size..).ok_or(crate::RapiraError::SliceLen)?;
+
+            Ok(::bytemuck::pod_read_unaligned(bytes))
+        }
+
+        #[inline]
+        pub fn convert_to_bytes<T>(item: &T, slice: &mut [u8], cursor: &mut usize)
+        where
+            T: Pod,
+        {
+            let bytes = ::bytemuck::bytes_of(item);
+            extend(slice, cursor, bytes);
+        }
+
+        #[inline]
+        pub fn try_convert_to_bytes<T>(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+        ) -> crate::Result<()>
+        where
+            T: Pod,
+        {
+            let bytes = ::bytemuck::bytes_of(item);
+            try_extend(slice, cursor, bytes)?;
+            Ok(())
+        }
     }
 
-    #[inline]
-    pub fn try_convert_from_bytes<T>(slice: &[u8], cursor: &mut usize) -> crate::Result<T>
+    impl<T> RapiraWith<T> for bytemuck
     where
-        T: Immutable + Pod + Zeroable + Sized,
+        T: Pod,
     {
-        let bytes = slice.get(*cursor..*cursor + size_of::<T>())?;
-        *cursor += size_of::<T>();
-        Ok(bytemuck::from_bytes(bytes))
+        const STATIC_SIZE: Option<usize> = Some(size_of::<T>());
+        const MIN_SIZE: usize = size_of::<T>();
+
+        #[inline]
+        fn size(item: &T) -> usize {
+            Self::size(item)
+        }
+
+        #[inline]
+        fn check_bytes(slice: &mut &[u8]) -> crate::Result<()> {
+            Self::check_bytes::<T>(PhantomData, slice)
+        }
+
+        #[inline]
+        fn from_slice(slice: &mut &[u8]) -> crate::Result<T> {
+            Self::from_slice(slice)
+        }
+
+        #[inline]
+        fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+            Self::convert_to_bytes(item, slice, cursor)
+        }
+
+        #[inline]
+        fn try_convert_to_bytes(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+        ) -> crate::Result<()> {
+            Self::try_convert_to_bytes(item, slice, cursor)
+        }
     }
 }
 
+#[cfg(feature = "bytemuck")]
+pub use bytemuck_with::bytemuck;
+
 #[cfg(feature = "postcard")]
-pub mod postcard {
+mod postcard_with {
     use core::{marker::PhantomData, mem::size_of};
 
-    use postcard::{experimental::serialized_size, take_from_bytes, to_slice};
+    use ::postcard::{experimental::serialized_size, take_from_bytes, to_slice};
     use serde::{Serialize, de::DeserializeOwned};
 
-    pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize>
-    where
-        T: Sized,
-    {
-        None
-    }
+    use crate::RapiraWith;
 
-    pub const fn min_size<T>(_: PhantomData<T>) -> usize
-    where
-        T: Sized,
-    {
-        size_of::<T>()
-    }
+    /// `with` adapter for serde types, encoded by postcard
+    #[allow(non_camel_case_types)]
+    pub enum postcard {}
 
-    #[inline]
-    pub fn size<T>(val: &T) -> usize
-    where
-        T: Sized + Serialize,
-    {
-        serialized_size(val).unwrap()
-    }
+    impl postcard {
+        pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize>
+        where
+            T: Sized,
+        {
+            None
+        }
 
-    #[inline]
-    pub fn check_bytes<T>(_: PhantomData<T>, slice: &mut &[u8]) -> crate::Result<()>
-    where
-        T: Sized + DeserializeOwned,
-    {
-        let (_, rem) = take_from_bytes::<T>(slice)?;
-        *slice = rem;
+        pub const fn min_size<T>(_: PhantomData<T>) -> usize
+        where
+            T: Sized,
+        {
+            size_of::<T>()
+        }
 
-        Ok(())
-    }
+        #[inline]
+        pub fn size<T>(val: &T) -> usize
+        where
+            T: Sized + Serialize,
+        {
+            serialized_size(val).unwrap()
+        }
 
-    #[inline]
-    pub fn from_slice<T>(slice: &mut &[u8]) -> crate::Result<T>
-    where
-        T: DeserializeOwned + Sized,
-    {
-        let (val, rem) = take_from_bytes::<T>(slice)?;
-        *slice = rem;
-        Ok(val)
-    }
+        #[inline]
+        pub fn check_bytes<T>(_: PhantomData<T>, slice: &mut &[u8]) -> crate::Result<()>
+        where
+            T: Sized + DeserializeOwned,
+        {
+            let (_, rem) = take_from_bytes::<T>(slice)?;
+            *slice = rem;
 
-    #[inline]
-    pub fn from_slice_unchecked<T>(slice: &mut &[u8]) -> crate::Result<T>
-    where
-        T: DeserializeOwned + Sized,
-    {
-        from_slice(slice)
-    }
+            Ok(())
+        }
 
-    /// .
-    ///
-    /// # Panics
-    ///
-    /// Panics if .
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if .
-    ///
-    /// # Safety
-    ///
-    /// .
-    #[inline]
-    pub unsafe fn from_slice_unsafe<T>(slice: &mut &[u8]) -> crate::Result<T>
-    where
-        T: DeserializeOwned + Sized,
-    {
-        from_slice(slice)
+        #[inline]
+        pub fn from_slice<T>(slice: &mut &[u8]) -> crate::Result<T>
+        where
+            T: DeserializeOwned + Sized,
+        {
+            let (val, rem) = take_from_bytes::<T>(slice)?;
+            *slice = rem;
+            Ok(val)
+        }
+
+        #[inline]
+        pub fn from_slice_unchecked<T>(slice: &mut &[u8]) -> crate::Result<T>
+        where
+            T: DeserializeOwned + Sized,
+        {
+            Self::from_slice(slice)
+        }
+
+        /// .
+        ///
+        /// # Panics
+        ///
+        /// Panics if .
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if .
+        ///
+        /// # Safety
+        ///
+        /// .
+        #[inline]
+        pub unsafe fn from_slice_unsafe<T>(slice: &mut &[u8]) -> crate::Result<T>
+        where
+            T: DeserializeOwned + Sized,
+        {
+            Self::from_slice(slice)
+        }
+
+        #[inline]
+        pub fn convert_to_bytes<T>(item: &T, slice: &mut [u8], cursor: &mut usize)
+        where
+            T: Serialize + Sized,
+        {
+            let bytes = slice.get_mut(*cursor..).unwrap();
+            let data = to_slice(item, bytes).unwrap();
+            *cursor += data.len();
+        }
+
+        #[inline]
+        pub fn try_convert_to_bytes<T>(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+        ) -> crate::Result<()>
+        where
+            T: Serialize + Sized,
+        {
+            let bytes = slice
+                .get_mut(*cursor..)
+                .ok_or(crate::RapiraError::SliceLen)?;
+            let data = to_slice(item, bytes)?;
+            *cursor += data.len();
+            Ok(())
+        }
     }
 
-    #[inline]
-    pub fn convert_to_bytes<T>(item: &T, slice: &mut [u8], cursor: &mut usize)
+    impl<T> RapiraWith<T> for postcard
     where
-        T: Serialize + Sized,
+        T: Serialize + DeserializeOwned,
     {
-        let bytes = slice.get_mut(*cursor..).unwrap();
-        let data = to_slice(item, bytes).unwrap();
-        *cursor += data.len();
-    }
+        const MIN_SIZE: usize = size_of::<T>();
 
-    #[inline]
-    pub fn try_convert_to_bytes<T>(
-        item: &T,
-        slice: &mut [u8],
-        cursor: &mut usize,
-    ) -> crate::Result<()>
-    where
-        T: Serialize + Sized,
-    {
-        let bytes = slice
-            .get_mut(*cursor..)
-            .ok_or(crate::RapiraError::SliceLen)?;
-        let data = to_slice(item, bytes)?;
-        *cursor += data.len();
-        Ok(())
+        #[inline]
+        fn size(item: &T) -> usize {
+            Self::size(item)
+        }
+
+        #[inline]
+        fn check_bytes(slice: &mut &[u8]) -> crate::Result<()> {
+            Self::check_bytes::<T>(PhantomData, slice)
+        }
+
+        #[inline]
+        fn from_slice(slice: &mut &[u8]) -> crate::Result<T> {
+            Self::from_slice(slice)
+        }
+
+        #[inline]
+        fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+            Self::convert_to_bytes(item, slice, cursor)
+        }
+
+        #[inline]
+        fn try_convert_to_bytes(
+            item: &T,
+            slice: &mut [u8],
+            cursor: &mut usize,
+        ) -> crate::Result<()> {
+            Self::try_convert_to_bytes(item, slice, cursor)
+        }
     }
 }
 
+#[cfg(feature = "postcard")]
+pub use postcard_with::postcard;
+
 #[cfg(feature = "serde_json")]
 impl crate::Rapira for serde_json::Value {
     const MIN_SIZE: usize = 1;
@@ -1202,6 +1540,9 @@ impl crate::Rapira for compact_str::CompactString {
     }
 }
 
+#[cfg(feature = "compact_str")]
+impl crate::LenPrefixed for compact_str::CompactString {}
+
 #[cfg(feature = "smol_str")]
 impl crate::Rapira for smol_str::SmolStr {
     const MIN_SIZE: usize = crate::LEN_SIZE;
@@ -1254,6 +1595,9 @@ impl crate::Rapira for smol_str::SmolStr {
     }
 }
 
+#[cfg(feature = "smol_str")]
+impl crate::LenPrefixed for smol_str::SmolStr {}
+
 #[cfg(feature = "ecow")]
 impl crate::Rapira for ecow::EcoString {
     const MIN_SIZE: usize = crate::LEN_SIZE;
@@ -1306,12 +1650,16 @@ impl crate::Rapira for ecow::EcoString {
     }
 }
 
+#[cfg(feature = "ecow")]
+impl crate::LenPrefixed for ecow::EcoString {}
+
 #[cfg(feature = "ecow")]
 impl<T> crate::Rapira for ecow::EcoVec<T>
 where
     T: crate::Rapira + Clone,
 {
     const MIN_SIZE: usize = crate::LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -1340,17 +1688,15 @@ where
     where
         Self: Sized,
     {
-        use crate::max_cap::{SMALLVEC_MAX_CAP, SMALLVEC_MAX_SIZE_OF};
-
         let len = u32::from_slice(slice)? as usize;
 
-        if len > SMALLVEC_MAX_CAP {
+        if len > T::SMALL_MAX_CAP {
             return Err(crate::RapiraError::MaxCapacity);
         }
 
         let size = std::mem::size_of::<Self>() * len;
 
-        if size > SMALLVEC_MAX_SIZE_OF {
+        if size > T::SMALL_MAX_SIZE_OF {
             return Err(crate::RapiraError::MaxSize);
         }
 
@@ -1365,21 +1711,19 @@ where
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> crate::Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> crate::Result<Self>
     where
         Self: Sized,
     {
-        use crate::max_cap::{SMALLVEC_MAX_CAP, SMALLVEC_MAX_SIZE_OF};
-
         let len = u32::from_slice(slice)? as usize;
 
-        if len > SMALLVEC_MAX_CAP {
+        if len > T::SMALL_MAX_CAP {
             return Err(crate::RapiraError::MaxCapacity);
         }
 
         let size = std::mem::size_of::<Self>() * len;
 
-        if size > SMALLVEC_MAX_SIZE_OF {
+        if size > T::SMALL_MAX_SIZE_OF {
             return Err(crate::RapiraError::MaxSize);
         }
 
@@ -1450,6 +1794,119 @@ where
             val.convert_to_bytes(slice, cursor);
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_versioned(slice, cursor, version);
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_versioned(version))
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<()>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        for _ in 0..len {
+            T::check_bytes_ctx(slice, flags)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        if len > T::SMALL_MAX_CAP {
+            return Err(crate::RapiraError::MaxCapacity);
+        }
+
+        let size = std::mem::size_of::<Self>() * len;
+
+        if size > T::SMALL_MAX_SIZE_OF {
+            return Err(crate::RapiraError::MaxSize);
+        }
+
+        let mut vec = Self::with_capacity(len);
+
+        for _ in 0..len {
+            let val = T::from_slice_ctx(slice, flags)?;
+            vec.push(val);
+        }
+
+        Ok(vec)
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(
+        &self,
+        slice: &mut [u8],
+        cursor: &mut usize,
+        flags: crate::RapiraFlags,
+    ) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+
+        for val in self.iter() {
+            val.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: crate::RapiraFlags) -> usize {
+        4 + self.iter().fold(0, |b, v| b + v.size_ctx(flags))
+    }
+}
+
+#[cfg(feature = "ecow")]
+impl<T> crate::LenPrefixed for ecow::EcoVec<T>
+where
+    T: crate::Rapira + Clone,
+{
+    #[inline]
+    fn decode_max_len(
+        slice: &mut &[u8],
+        max_len: usize,
+        decode: crate::constraints::Decode,
+    ) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        use crate::Rapira;
+
+        let len = u32::from_slice(slice)? as usize;
+
+        crate::constraints::check_len(len, false, Some(max_len))?;
+
+        let size = std::mem::size_of::<Self>() * len;
+
+        if size > T::SMALL_MAX_SIZE_OF {
+            return Err(crate::RapiraError::MaxSize);
+        }
+
+        let mut vec = Self::with_capacity(len);
+
+        for _ in 0..len {
+            let val = decode.read::<T>(slice)?;
+            vec.push(val);
+        }
+
+        Ok(vec)
+    }
 }
 
 #[cfg(feature = "indexmap")]
@@ -1460,6 +1917,7 @@ where
     S: core::hash::Hasher + core::default::Default,
 {
     const MIN_SIZE: usize = crate::LEN_SIZE;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -1494,17 +1952,15 @@ where
     where
         Self: Sized,
     {
-        use crate::max_cap::{VEC_MAX_CAP, VEC_MAX_SIZE_OF};
-
         let len = u32::from_slice(slice)? as usize;
 
-        if len > VEC_MAX_CAP {
+        if len > K::MAX_CAP.min(V::MAX_CAP) {
             return Err(crate::RapiraError::MaxCapacity);
         }
 
         let size = std::mem::size_of::<Self>() * len;
 
-        if size > VEC_MAX_SIZE_OF {
+        if size > K::MAX_SIZE_OF.min(V::MAX_SIZE_OF) {
             return Err(crate::RapiraError::MaxSize);
         }
 
@@ -1518,21 +1974,19 @@ where
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> crate::Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> crate::Result<Self>
     where
         Self: Sized,
     {
-        use crate::max_cap::{VEC_MAX_CAP, VEC_MAX_SIZE_OF};
-
         let len = u32::from_slice(slice)? as usize;
 
-        if len > VEC_MAX_CAP {
+        if len > K::MAX_CAP.min(V::MAX_CAP) {
             return Err(crate::RapiraError::MaxCapacity);
         }
 
         let size = std::mem::size_of::<Self>() * len;
 
-        if size > VEC_MAX_SIZE_OF {
+        if size > K::MAX_SIZE_OF.min(V::MAX_SIZE_OF) {
             return Err(crate::RapiraError::MaxSize);
         }
 
@@ -1599,6 +2053,122 @@ where
             value.convert_to_bytes(slice, cursor);
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+        for (key, value) in self {
+            key.convert_to_bytes_versioned(slice, cursor, version);
+            value.convert_to_bytes_versioned(slice, cursor, version);
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        4 + self.iter().fold(0, |b, (k, v)| {
+            b + k.size_versioned(version) + v.size_versioned(version)
+        })
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<()>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+        for _ in 0..len {
+            K::check_bytes_ctx(slice, flags)?;
+            V::check_bytes_ctx(slice, flags)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: crate::RapiraFlags) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = u32::from_slice(slice)? as usize;
+
+        if len > K::MAX_CAP.min(V::MAX_CAP) {
+            return Err(crate::RapiraError::MaxCapacity);
+        }
+
+        let size = std::mem::size_of::<Self>() * len;
+
+        if size > K::MAX_SIZE_OF.min(V::MAX_SIZE_OF) {
+            return Err(crate::RapiraError::MaxSize);
+        }
+
+        let mut map = Self::with_capacity_and_hasher(len, Default::default());
+        for _ in 0..len {
+            let key = K::from_slice_ctx(slice, flags)?;
+            let value = V::from_slice_ctx(slice, flags)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(
+        &self,
+        slice: &mut [u8],
+        cursor: &mut usize,
+        flags: crate::RapiraFlags,
+    ) {
+        let len = self.len() as u32;
+        len.convert_to_bytes(slice, cursor);
+        for (key, value) in self {
+            key.convert_to_bytes_ctx(slice, cursor, flags);
+            value.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: crate::RapiraFlags) -> usize {
+        4 + self
+            .iter()
+            .fold(0, |b, (k, v)| b + k.size_ctx(flags) + v.size_ctx(flags))
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: crate::Rapira, V: crate::Rapira, S> crate::LenPrefixed
+    for indexmap::IndexMap<K, V, core::hash::BuildHasherDefault<S>>
+where
+    K: Eq + core::hash::Hash,
+    S: core::hash::Hasher + core::default::Default,
+{
+    #[inline]
+    fn decode_max_len(
+        slice: &mut &[u8],
+        max_len: usize,
+        decode: crate::constraints::Decode,
+    ) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        use crate::Rapira;
+
+        let len = u32::from_slice(slice)? as usize;
+
+        crate::constraints::check_len(len, false, Some(max_len))?;
+
+        let size = std::mem::size_of::<Self>() * len;
+
+        if size > K::MAX_SIZE_OF.min(V::MAX_SIZE_OF) {
+            return Err(crate::RapiraError::MaxSize);
+        }
+
+        let mut map = Self::with_capacity_and_hasher(len, Default::default());
+        for _ in 0..len {
+            let key = decode.read::<K>(slice)?;
+            let value = decode.read::<V>(slice)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
 }
 
 #[cfg(feature = "uuid")]
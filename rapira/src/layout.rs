@@ -0,0 +1,101 @@
+//! Runtime of the binary-protocol attributes: `#[rapira(magic = b"..")]` on structs,
+//! `#[rapira(pad = N)]`, `#[rapira(align = N)]`, `#[rapira(count = field)]` and
+//! `#[rapira(if = expr)]` on fields.
+//!
+//! Alignment is relative to the start of the struct. Padding bytes must be zero
+//! (see [`fixed::read_padding`](crate::fixed::read_padding)).
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::Rapira;
+use crate::{RapiraError, Result};
+
+/// check and skip the `magic` bytes
+#[inline]
+pub fn read_magic(slice: &mut &[u8], magic: &[u8]) -> Result<()> {
+    match slice.strip_prefix(magic) {
+        Some(tail) => {
+            *slice = tail;
+            Ok(())
+        }
+        None => Err(RapiraError::Magic),
+    }
+}
+
+/// padding bytes before a field at `offset` with `#[rapira(align = N)]`
+#[inline]
+pub const fn align_pad(offset: usize, align: usize) -> usize {
+    offset.next_multiple_of(align) - offset
+}
+
+/// `size` rounded up to `align`
+#[inline]
+pub const fn align_up(size: usize, align: usize) -> usize {
+    size.next_multiple_of(align)
+}
+
+/// `STATIC_SIZE` of the fields before an aligned field, rounded up to `align`
+#[inline]
+pub const fn align_static(size: Option<usize>, align: usize) -> Option<usize> {
+    match size {
+        Some(size) => Some(size.next_multiple_of(align)),
+        None => None,
+    }
+}
+
+/// items of a `#[rapira(count = field)]` field, `field` must fit in `usize`
+#[inline]
+pub fn count<C: Copy + TryInto<usize>>(field: &C) -> Result<usize> {
+    (*field).try_into().map_err(|_| RapiraError::Count)
+}
+
+/// the `count` field of a `#[rapira(count = field)]` field must be equal to its length
+#[inline]
+pub fn check_count<C: Copy + TryInto<usize>>(field: &C, len: usize) -> Result<()> {
+    if count(field)? == len {
+        Ok(())
+    } else {
+        Err(RapiraError::Count)
+    }
+}
+
+/// `count` items without a length prefix, each by `read`, limited as `Vec<T>`
+#[cfg(feature = "alloc")]
+#[inline]
+pub fn read_counted<T: Rapira>(
+    slice: &mut &[u8],
+    count: usize,
+    mut read: impl FnMut(&mut &[u8]) -> Result<T>,
+) -> Result<Vec<T>> {
+    if count > T::MAX_CAP {
+        return Err(RapiraError::MaxCapacity);
+    }
+    if core::mem::size_of::<Vec<T>>() * count > T::MAX_SIZE_OF {
+        return Err(RapiraError::MaxSize);
+    }
+    if count.saturating_mul(T::MIN_SIZE) > slice.len() {
+        return Err(RapiraError::SliceLen);
+    }
+    let mut items = Vec::with_capacity(count);
+    for _ in 0..count {
+        items.push(read(slice)?);
+    }
+    Ok(items)
+}
+
+/// Structs without own framing (`magic`, `align`, `extensible`), their bytes are exactly
+/// their fields. Implemented by the derive, `#[rapira(flatten)]` fields must be `Flat`.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be a `#[rapira(flatten)]` field",
+    label = "not a derived struct without own framing",
+    note = "only structs without `magic`, `align`, `extensible`, `sparse`, `transparent` or `from`/`into` can be flattened"
+)]
+pub trait Flat {}
+
+/// `#[rapira(flatten)]` field check
+#[doc(hidden)]
+#[inline]
+pub const fn assert_flat<T: Flat + ?Sized>() {}
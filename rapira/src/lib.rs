@@ -3,32 +3,48 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod allocated;
+pub mod be;
+pub mod bits;
+pub mod constraints;
+pub mod envelope;
 pub mod error;
+pub mod extensible;
+pub mod fixed;
 mod from_u8;
 pub mod funcs;
 mod implements;
+pub mod layout;
 #[cfg(feature = "std")]
 mod macros;
 pub mod max_cap;
 mod primitive;
+mod with;
 
+pub use bits::BitField;
+pub use constraints::LenPrefixed;
+#[cfg(feature = "alloc")]
+pub use error::ValidationError;
 pub use error::{RapiraError, Result};
+pub use fixed::fixed_option;
 pub use from_u8::{EnumFromU8Error, FromU8};
+#[cfg(feature = "bytemuck")]
+pub use implements::bytemuck;
 #[cfg(feature = "postcard")]
 pub use implements::postcard;
 #[cfg(feature = "zerocopy")]
 pub use implements::zero;
 pub use primitive::{byte_rapira, bytes_rapira, str_rapira};
+pub use with::RapiraWith;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
 pub use funcs::{
-    check_bytes, deser_unchecked, deser_unsafe, deserialize, deserialize_ctx,
-    deserialize_versioned, size, size_ctx,
+    check_bytes, check_bytes_ctx, deser_unchecked, deser_unsafe, deserialize, deserialize_ctx,
+    deserialize_versioned, size, size_ctx, size_versioned,
 };
 #[cfg(feature = "alloc")]
-pub use funcs::{extend_vec, serialize, serialize_ctx};
+pub use funcs::{extend_vec, serialize, serialize_ctx, serialize_versioned};
 pub use rapira_derive::{FromU8, PrimitiveFromEnum, Rapira};
 
 /// Bitflags for context-aware serialization.
@@ -59,6 +75,34 @@ pub trait Rapira {
     const STATIC_SIZE: Option<usize> = None;
     const MIN_SIZE: usize;
 
+    /// max items of decoded `Vec<Self>`, `BTreeMap`s and `IndexMap`s with `Self` keys
+    /// or values (the smaller limit of both), checked before allocation.
+    /// Derived types set it by `#[rapira(max_cap = N)]`.
+    ///
+    /// ```
+    /// use rapira::Rapira;
+    ///
+    /// /// many small items are fine
+    /// #[derive(Rapira)]
+    /// #[rapira(max_cap = 4_000_000)]
+    /// struct Hash([u8; 32]);
+    ///
+    /// assert_eq!(Hash::MAX_CAP, 4_000_000);
+    /// assert_eq!(String::MAX_CAP, rapira::max_cap::VEC_MAX_CAP);
+    /// ```
+    const MAX_CAP: usize = max_cap::VEC_MAX_CAP;
+    /// max `size_of::<Vec<Self>>() * len` of `Vec` and `IndexMap`
+    const MAX_SIZE_OF: usize = max_cap::VEC_MAX_SIZE_OF;
+    /// max items of `SmallVec` and `EcoVec`
+    const SMALL_MAX_CAP: usize = max_cap::SMALLVEC_MAX_CAP;
+    /// max `size_of::<SmallVec<..>>() * len` of `SmallVec` and `EcoVec`
+    const SMALL_MAX_SIZE_OF: usize = max_cap::SMALLVEC_MAX_SIZE_OF;
+    /// the encoding has little-endian numbers or length prefixes: integers, floats,
+    /// `String`, `Vec` and containers of them; `#[rapira(endian = "big")]` rejects fields
+    /// of such types it has no `rapira::be` adapter for
+    #[doc(hidden)]
+    const LITTLE_ENDIAN: bool = false;
+
     /// size of bytes for serialize
     fn size(&self) -> usize;
 
@@ -113,12 +157,15 @@ pub trait Rapira {
     /// Enables backward-compatible deserialization: new code can read old data
     /// that is missing fields added in later versions. The version number is
     /// stored **externally** (e.g. in DB metadata), not inside the serialized bytes.
+    /// For blobs without external metadata use [`envelope::Versioned`], which
+    /// stores the version in a header in front of the value.
     ///
     /// Default implementation delegates to [`from_slice`](Rapira::from_slice).
-    /// The derive macro generates an override when `#[rapira(version = N)]` is
-    /// placed on a struct — fields annotated with `#[rapira(since = M)]` are
-    /// only read from the slice when `version >= M`, otherwise
-    /// [`Default::default()`] is used.
+    /// The derive macro generates an override for structs and enums: fields
+    /// annotated with `#[rapira(since = M)]` are only read from the slice when
+    /// `version >= M`, otherwise [`Default::default()`] is used. Enum variants
+    /// annotated with `#[rapira(since = M)]` are rejected with
+    /// [`RapiraError::EnumVariant`] when `version < M`.
     ///
     /// # Derive usage
     ///
@@ -140,23 +187,66 @@ pub trait Rapira {
     /// let user: User = rapira::deserialize_versioned(&bytes, schema_version)?;
     /// ```
     ///
+    /// # Removed fields
+    ///
+    /// A removed field is declared on the struct, so old data can still be
+    /// read: its value is decoded and discarded when `since <= version < until`.
+    /// `after` is the field (current or retired) it followed, by default it
+    /// was the first one; `since` defaults to 1.
+    ///
+    /// ```rust,ignore
+    /// #[derive(Rapira)]
+    /// #[rapira(version = 3)]
+    /// #[rapira(retired(name = nickname, ty = String, until = 3, after = name))]
+    /// struct User {
+    ///     name: String,
+    ///     age: u32,
+    /// }
+    /// ```
+    ///
+    /// # Migrations
+    ///
+    /// For changes `since` + [`Default`] can't express (changed field type,
+    /// split field), data of older versions can be decoded as a legacy type
+    /// and converted. The legacy type is a derived type itself, so it may
+    /// declare its own migrations and hops chain. Among `migrate` items the
+    /// one with the smallest `from >= version` is used. `downgrade` converts
+    /// the value back for [`convert_to_bytes_versioned`](Rapira::convert_to_bytes_versioned).
+    ///
+    /// ```rust,ignore
+    /// #[derive(Rapira)]
+    /// #[rapira(version = 3, migrate(from = 2, via = UserV2, with = User::from_v2))]
+    /// struct User {
+    ///     name: String,
+    ///     age: u64, // was `u32` in v2
+    /// }
+    /// ```
+    ///
     /// # Rules
     ///
     /// - Serialization (`convert_to_bytes`, `size`) always writes **all** fields
-    ///   (current version). Only deserialization is affected.
+    ///   (current version), use `convert_to_bytes_versioned` / `size_versioned`
+    ///   (`rapira::serialize_versioned`) to write an older layout.
+    /// - Retired field types written by `convert_to_bytes_versioned` must
+    ///   implement [`Default`].
     /// - `from_slice` always reads all fields regardless of version (use for
     ///   current-version data).
     /// - Fields with `#[rapira(since = M)]` must implement [`Default`].
-    /// - `since = 0` is invalid (versions start at 1).
-    /// - `since` value must not exceed the struct's `version`.
-    /// - `#[rapira(since)]` requires `#[rapira(version)]` on the struct.
+    /// - `since = 0` is invalid (versions start at 1), versions are `u16`.
+    /// - `since` value must not exceed the container's `version`.
+    /// - `#[rapira(since)]` requires `#[rapira(version)]` on the struct or enum.
     /// - `#[rapira(since)]` and `#[rapira(skip)]` cannot be combined.
-    /// - Structs without `#[rapira(version)]` use the default impl (delegates
-    ///   to `from_slice`), so existing code is unaffected.
+    /// - `migrate` `from` must be less than the container's `version`.
+    /// - Version ranges of retired fields must not overlap with a current or
+    ///   another retired field of the same name, `until` must not exceed the
+    ///   struct's `version`.
+    /// - Derived types without `#[rapira(version)]` read all fields, but still
+    ///   forward the version into their fields.
     /// - Version is propagated through collections (`Vec<T>`, `Option<T>`,
-    ///   `Box<T>`, `BTreeMap`, tuples, arrays, `SmallVec`, `ArrayVec`).
+    ///   `Box<T>`, `BTreeMap`, tuples, arrays, `SmallVec`, `ArrayVec`) and
+    ///   enum variant payloads.
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], _version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], _version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -171,6 +261,30 @@ pub trait Rapira {
 
     fn convert_to_bytes(&self, slice: &mut [u8], cursor: &mut usize);
 
+    /// Serialize with the layout of an older schema `version`, so that
+    /// [`from_slice_versioned`](Rapira::from_slice_versioned) with the same
+    /// `version` reads the value back. Default: delegates to `convert_to_bytes`.
+    ///
+    /// The derive macro omits fields with `#[rapira(since = M)]` when
+    /// `version < M`, writes [`Default::default()`] for retired fields and
+    /// converts by `migrate(.., downgrade = path)` for migrated versions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value can't be represented at `version`: an enum variant
+    /// added later, or a migrated version without `downgrade`.
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, _version: u16) {
+        self.convert_to_bytes(slice, cursor)
+    }
+
+    /// Size of [`convert_to_bytes_versioned`](Rapira::convert_to_bytes_versioned)
+    /// output. Default: delegates to `size`.
+    #[inline]
+    fn size_versioned(&self, _version: u16) -> usize {
+        self.size()
+    }
+
     /// Context-aware serialization. Default: delegates to `convert_to_bytes`.
     #[inline]
     fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, _flags: RapiraFlags) {
@@ -191,6 +305,15 @@ pub trait Rapira {
     fn size_ctx(&self, _flags: RapiraFlags) -> usize {
         self.size()
     }
+
+    /// Context-aware bytes check. Default: delegates to `check_bytes`.
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], _flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        Self::check_bytes(slice)
+    }
 }
 
 pub const LEN_SIZE: usize = 4;
@@ -247,7 +370,16 @@ pub const fn static_size<const N: usize>(arr: [Option<usize>; N]) -> Option<usiz
     Some(size)
 }
 
+/// `STATIC_SIZE` of an enum with a one byte tag, `arr` - static sizes of the variants
 pub const fn enum_size<const N: usize>(arr: [Option<usize>; N]) -> Option<usize> {
+    enum_size_tagged(arr, 1)
+}
+
+/// `STATIC_SIZE` of an enum with a `tag_size` bytes tag (`#[rapira(tag = u16)]`)
+pub const fn enum_size_tagged<const N: usize>(
+    arr: [Option<usize>; N],
+    tag_size: usize,
+) -> Option<usize> {
     let mut i = 0;
     let mut size = 0;
     let mut is_init = false;
@@ -268,7 +400,7 @@ pub const fn enum_size<const N: usize>(arr: [Option<usize>; N]) -> Option<usize>
         }
         i += 1;
     }
-    Some(size + 1)
+    Some(size + tag_size)
 }
 
 pub const fn min_size(arr: &'static [usize]) -> usize {
@@ -282,7 +414,13 @@ pub const fn min_size(arr: &'static [usize]) -> usize {
     size
 }
 
+/// `MIN_SIZE` of an enum with a one byte tag, `arr` - min sizes of the variants
 pub const fn enum_min_size(arr: &'static [usize]) -> usize {
+    enum_min_size_tagged(arr, 1)
+}
+
+/// `MIN_SIZE` of an enum with a `tag_size` bytes tag (`#[rapira(tag = u16)]`)
+pub const fn enum_min_size_tagged(arr: &'static [usize], tag_size: usize) -> usize {
     let mut i = 0;
     let mut size = 0;
     let mut is_init = false;
@@ -291,10 +429,10 @@ pub const fn enum_min_size(arr: &'static [usize]) -> usize {
         if !is_init {
             size = item;
             is_init = true;
-        } else if size < item {
+        } else if item < size {
             size = item;
         }
         i += 1;
     }
-    size + 1
+    size + tag_size
 }
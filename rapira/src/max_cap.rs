@@ -1,11 +1,17 @@
+//! Global collection limits, the defaults of the per-type limits
+//! [`Rapira::MAX_CAP`](crate::Rapira::MAX_CAP), `MAX_SIZE_OF`, `SMALL_MAX_CAP` and
+//! `SMALL_MAX_SIZE_OF`.
+//!
+//! The per-type limits are associated consts of `Rapira`, not a separate `MaxCapacity`
+//! trait: `Vec<T>`, `BTreeMap`, `IndexMap`, `SmallVec` and `EcoVec` read them from their
+//! item types with no extra bound, so manual `Rapira` impls keep working unchanged.
+
 /// 512k
 pub const VEC_MAX_CAP: usize = 512 * 1024;
 /// 2 gb
 pub const VEC_MAX_SIZE_OF: usize = 2 * 1024 * 1024 * 1024;
 
 /// 512
-#[cfg(feature = "smallvec")]
 pub const SMALLVEC_MAX_CAP: usize = 512;
 /// 1mb
-#[cfg(feature = "smallvec")]
 pub const SMALLVEC_MAX_SIZE_OF: usize = 1024 * 1024;
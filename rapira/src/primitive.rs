@@ -1,10 +1,16 @@
 use core::{
+    marker::PhantomData,
     mem::{MaybeUninit, size_of, transmute_copy},
     num::{NonZeroU32, NonZeroU64},
     time::Duration,
 };
 
-use crate::{Rapira, RapiraError, Result, enum_min_size, enum_size, push, static_size, try_push};
+use simdutf8::basic::from_utf8;
+
+use crate::{
+    LEN_SIZE, Rapira, RapiraError, RapiraFlags, RapiraWith, Result, enum_min_size, enum_size,
+    extend, push, static_size, try_extend, try_push,
+};
 
 impl Rapira for () {
     const STATIC_SIZE: Option<usize> = Some(0);
@@ -40,6 +46,41 @@ impl Rapira for () {
     }
 }
 
+/// zero-sized marker, no bound on `T`
+impl<T: ?Sized> Rapira for core::marker::PhantomData<T> {
+    const STATIC_SIZE: Option<usize> = Some(0);
+    const MIN_SIZE: usize = 0;
+
+    #[inline]
+    fn check_bytes(_: &mut &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice(_: &mut &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(core::marker::PhantomData)
+    }
+
+    #[inline]
+    unsafe fn from_slice_unsafe(_: &mut &[u8]) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(core::marker::PhantomData)
+    }
+
+    #[inline]
+    fn convert_to_bytes(&self, _: &mut [u8], _: &mut usize) {}
+
+    #[inline]
+    fn size(&self) -> usize {
+        0
+    }
+}
+
 impl Rapira for bool {
     const STATIC_SIZE: Option<usize> = Some(1);
     const MIN_SIZE: usize = 1;
@@ -97,11 +138,11 @@ impl Rapira for bool {
     }
 }
 
-pub mod byte_rapira {
-    use core::marker::PhantomData;
-
-    use super::*;
+/// `u8` as a single byte, `with` adapter for `u8` fields
+#[allow(non_camel_case_types)]
+pub enum byte_rapira {}
 
+impl byte_rapira {
     pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize> {
         Some(1)
     }
@@ -133,7 +174,7 @@ pub mod byte_rapira {
     /// See funcs::deser_unchecked
     #[inline]
     pub unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<u8> {
-        from_slice(slice)
+        Self::from_slice(slice)
     }
 
     /// ...
@@ -169,6 +210,41 @@ pub mod byte_rapira {
     }
 }
 
+impl RapiraWith<u8> for byte_rapira {
+    const STATIC_SIZE: Option<usize> = Some(1);
+    const MIN_SIZE: usize = 1;
+
+    #[inline]
+    fn size(item: &u8) -> usize {
+        Self::size(item)
+    }
+
+    #[inline]
+    fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+        Self::check_bytes::<u8>(PhantomData, slice)
+    }
+
+    #[inline]
+    fn from_slice(slice: &mut &[u8]) -> Result<u8> {
+        Self::from_slice(slice)
+    }
+
+    #[inline]
+    unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<u8> {
+        unsafe { Self::from_slice_unsafe(slice) }
+    }
+
+    #[inline]
+    fn convert_to_bytes(item: &u8, slice: &mut [u8], cursor: &mut usize) {
+        Self::convert_to_bytes(item, slice, cursor)
+    }
+
+    #[inline]
+    fn try_convert_to_bytes(item: &u8, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        Self::try_convert_to_bytes(item, slice, cursor)
+    }
+}
+
 #[inline(always)]
 pub fn into_arr<const N: usize>(slice: &[u8]) -> Result<[u8; N], RapiraError> {
     let slice: &[u8; N] = slice.first_chunk().ok_or(RapiraError::SliceLen)?;
@@ -180,6 +256,7 @@ macro_rules! impl_for_integer {
         impl Rapira for $type {
             const STATIC_SIZE: Option<usize> = Some(size_of::<$type>());
             const MIN_SIZE: usize = size_of::<$type>();
+            const LITTLE_ENDIAN: bool = size_of::<$type>() > 1;
 
             #[inline]
             fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -262,6 +339,7 @@ impl_for_integer!(u128);
 impl Rapira for usize {
     const STATIC_SIZE: Option<usize> = Some(size_of::<u32>());
     const MIN_SIZE: usize = size_of::<u32>();
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -312,6 +390,7 @@ impl Rapira for usize {
 impl Rapira for isize {
     const STATIC_SIZE: Option<usize> = Some(size_of::<i64>());
     const MIN_SIZE: usize = size_of::<i64>();
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -361,6 +440,7 @@ impl Rapira for isize {
 impl Rapira for NonZeroU32 {
     const STATIC_SIZE: Option<usize> = Some(size_of::<Self>());
     const MIN_SIZE: usize = size_of::<Self>();
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn check_bytes(slice: &mut &[u8]) -> Result<()>
@@ -426,6 +506,7 @@ impl Rapira for NonZeroU32 {
 impl Rapira for NonZeroU64 {
     const STATIC_SIZE: Option<usize> = Some(size_of::<Self>());
     const MIN_SIZE: usize = size_of::<Self>();
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn check_bytes(slice: &mut &[u8]) -> Result<()>
@@ -491,6 +572,7 @@ impl Rapira for NonZeroU64 {
 impl Rapira for f32 {
     const STATIC_SIZE: Option<usize> = Some(size_of::<Self>());
     const MIN_SIZE: usize = size_of::<Self>();
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -578,6 +660,7 @@ impl Rapira for f32 {
 impl Rapira for f64 {
     const STATIC_SIZE: Option<usize> = Some(size_of::<Self>());
     const MIN_SIZE: usize = size_of::<Self>();
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -665,6 +748,7 @@ impl Rapira for f64 {
 impl<T: Rapira> Rapira for Option<T> {
     const STATIC_SIZE: Option<usize> = None;
     const MIN_SIZE: usize = T::MIN_SIZE + 1;
+    const LITTLE_ENDIAN: bool = T::LITTLE_ENDIAN;
 
     #[inline]
     fn size(&self) -> usize {
@@ -689,7 +773,7 @@ impl<T: Rapira> Rapira for Option<T> {
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -771,11 +855,81 @@ impl<T: Rapira> Rapira for Option<T> {
             }
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        match self.as_ref() {
+            Some(s) => {
+                push(slice, cursor, 1);
+                s.convert_to_bytes_versioned(slice, cursor, version);
+            }
+            None => {
+                push(slice, cursor, 0);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        match self {
+            None => 1,
+            Some(t) => 1 + t.size_versioned(version),
+        }
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let b = byte_rapira::from_slice(slice)?;
+        if b != 0 {
+            T::check_bytes_ctx(slice, flags)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let b = byte_rapira::from_slice(slice)?;
+        if b != 0 {
+            let t = T::from_slice_ctx(slice, flags)?;
+            Ok(Some(t))
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        match self.as_ref() {
+            Some(s) => {
+                push(slice, cursor, 1);
+                s.convert_to_bytes_ctx(slice, cursor, flags);
+            }
+            None => {
+                push(slice, cursor, 0);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        match self {
+            None => 1,
+            Some(t) => 1 + t.size_ctx(flags),
+        }
+    }
 }
 
 impl<T: Rapira, E: Rapira> Rapira for Result<T, E> {
     const STATIC_SIZE: Option<usize> = enum_size([T::STATIC_SIZE, E::STATIC_SIZE]);
     const MIN_SIZE: usize = enum_min_size(&[T::MIN_SIZE, E::MIN_SIZE]);
+    const LITTLE_ENDIAN: bool = T::LITTLE_ENDIAN || E::LITTLE_ENDIAN;
 
     #[inline]
     fn size(&self) -> usize {
@@ -812,7 +966,7 @@ impl<T: Rapira, E: Rapira> Rapira for Result<T, E> {
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -884,12 +1038,83 @@ impl<T: Rapira, E: Rapira> Rapira for Result<T, E> {
             }
         }
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        match self.as_ref() {
+            Ok(s) => {
+                push(slice, cursor, 0);
+                s.convert_to_bytes_versioned(slice, cursor, version);
+            }
+            Err(e) => {
+                push(slice, cursor, 1);
+                e.convert_to_bytes_versioned(slice, cursor, version);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        match self {
+            Ok(t) => 1 + t.size_versioned(version),
+            Err(e) => 1 + e.size_versioned(version),
+        }
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let discriminant = byte_rapira::from_slice(slice)?;
+        match discriminant {
+            0 => T::check_bytes_ctx(slice, flags),
+            1 => E::check_bytes_ctx(slice, flags),
+            _ => Err(RapiraError::EnumVariant),
+        }
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let discriminant = byte_rapira::from_slice(slice)?;
+        match discriminant {
+            0 => Ok(Ok(T::from_slice_ctx(slice, flags)?)),
+            1 => Ok(Err(E::from_slice_ctx(slice, flags)?)),
+            _ => Err(RapiraError::EnumVariant),
+        }
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        match self.as_ref() {
+            Ok(s) => {
+                push(slice, cursor, 0);
+                s.convert_to_bytes_ctx(slice, cursor, flags);
+            }
+            Err(e) => {
+                push(slice, cursor, 1);
+                e.convert_to_bytes_ctx(slice, cursor, flags);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        match self {
+            Ok(t) => 1 + t.size_ctx(flags),
+            Err(e) => 1 + e.size_ctx(flags),
+        }
+    }
 }
 
 #[cfg(feature = "either")]
 impl<L: Rapira, R: Rapira> Rapira for either::Either<L, R> {
     const STATIC_SIZE: Option<usize> = enum_size([L::STATIC_SIZE, R::STATIC_SIZE]);
     const MIN_SIZE: usize = enum_min_size(&[L::MIN_SIZE, R::MIN_SIZE]);
+    const LITTLE_ENDIAN: bool = L::LITTLE_ENDIAN || R::LITTLE_ENDIAN;
 
     #[inline]
     fn size(&self) -> usize {
@@ -974,6 +1199,48 @@ impl<L: Rapira, R: Rapira> Rapira for either::Either<L, R> {
             }
         }
     }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()> {
+        let discriminant = byte_rapira::from_slice(slice)?;
+        match discriminant {
+            0 => L::check_bytes_ctx(slice, flags),
+            1 => R::check_bytes_ctx(slice, flags),
+            _ => Err(RapiraError::EnumVariant),
+        }
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self> {
+        let discriminant = byte_rapira::from_slice(slice)?;
+        match discriminant {
+            0 => Ok(either::Either::Left(L::from_slice_ctx(slice, flags)?)),
+            1 => Ok(either::Either::Right(R::from_slice_ctx(slice, flags)?)),
+            _ => Err(RapiraError::EnumVariant),
+        }
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        match self {
+            either::Either::Left(l) => {
+                push(slice, cursor, 0);
+                l.convert_to_bytes_ctx(slice, cursor, flags);
+            }
+            either::Either::Right(r) => {
+                push(slice, cursor, 1);
+                r.convert_to_bytes_ctx(slice, cursor, flags);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        1 + match self {
+            either::Either::Left(l) => l.size_ctx(flags),
+            either::Either::Right(r) => r.size_ctx(flags),
+        }
+    }
 }
 
 impl<const CAP: usize> Rapira for [u8; CAP] {
@@ -1041,6 +1308,7 @@ where
 {
     const STATIC_SIZE: Option<usize> = static_size([T::STATIC_SIZE; CAP]);
     const MIN_SIZE: usize = CAP * T::MIN_SIZE;
+    const LITTLE_ENDIAN: bool = T::LITTLE_ENDIAN;
 
     #[inline]
     fn size(&self) -> usize {
@@ -1091,7 +1359,7 @@ where
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -1194,11 +1462,76 @@ where
         }
         Ok(())
     }
+
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        for val in self.iter() {
+            val.convert_to_bytes_versioned(slice, cursor, version);
+        }
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        self.iter().fold(0, |b, v| b + v.size_versioned(version))
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()> {
+        for _ in 0..CAP {
+            T::check_bytes_ctx(slice, flags)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut arr: [MaybeUninit<T>; CAP] = unsafe { MaybeUninit::uninit().assume_init() };
+
+        for i in 0..CAP {
+            match T::from_slice_ctx(slice, flags) {
+                Ok(val) => {
+                    arr.get_mut(i).ok_or(RapiraError::SliceLen)?.write(val);
+                }
+                Err(err) => {
+                    if i != 0 {
+                        let s = arr.get_mut(0..i).ok_or(RapiraError::SliceLen)?;
+
+                        for item in s {
+                            unsafe {
+                                item.assume_init_drop();
+                            }
+                        }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        let arr: [T; CAP] = arr.map(|i| unsafe { i.assume_init() });
+
+        Ok(arr)
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        for val in self.iter() {
+            val.convert_to_bytes_ctx(slice, cursor, flags);
+        }
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        self.iter().fold(0, |b, v| b + v.size_ctx(flags))
+    }
 }
 
 impl<T0: Rapira, T1: Rapira> Rapira for (T0, T1) {
     const STATIC_SIZE: Option<usize> = static_size([T0::STATIC_SIZE, T1::STATIC_SIZE]);
     const MIN_SIZE: usize = T0::MIN_SIZE + T1::MIN_SIZE;
+    const LITTLE_ENDIAN: bool = T0::LITTLE_ENDIAN || T1::LITTLE_ENDIAN;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -1211,7 +1544,7 @@ impl<T0: Rapira, T1: Rapira> Rapira for (T0, T1) {
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -1267,6 +1600,48 @@ impl<T0: Rapira, T1: Rapira> Rapira for (T0, T1) {
         self.1.convert_to_bytes(slice, cursor);
     }
 
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        self.0.convert_to_bytes_versioned(slice, cursor, version);
+        self.1.convert_to_bytes_versioned(slice, cursor, version);
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        self.0.size_versioned(version) + self.1.size_versioned(version)
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        T0::check_bytes_ctx(slice, flags)?;
+        T1::check_bytes_ctx(slice, flags)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let t0 = T0::from_slice_ctx(slice, flags)?;
+        let t1 = T1::from_slice_ctx(slice, flags)?;
+        Ok((t0, t1))
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        self.0.convert_to_bytes_ctx(slice, cursor, flags);
+        self.1.convert_to_bytes_ctx(slice, cursor, flags);
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        self.0.size_ctx(flags) + self.1.size_ctx(flags)
+    }
+
     #[inline]
     fn size(&self) -> usize {
         let a = match T0::STATIC_SIZE {
@@ -1285,6 +1660,7 @@ impl<T0: Rapira, T1: Rapira, T2: Rapira> Rapira for (T0, T1, T2) {
     const STATIC_SIZE: Option<usize> =
         static_size([T0::STATIC_SIZE, T1::STATIC_SIZE, T2::STATIC_SIZE]);
     const MIN_SIZE: usize = T0::MIN_SIZE + T1::MIN_SIZE + T2::MIN_SIZE;
+    const LITTLE_ENDIAN: bool = T0::LITTLE_ENDIAN || T1::LITTLE_ENDIAN || T2::LITTLE_ENDIAN;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -1298,7 +1674,7 @@ impl<T0: Rapira, T1: Rapira, T2: Rapira> Rapira for (T0, T1, T2) {
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -1360,6 +1736,54 @@ impl<T0: Rapira, T1: Rapira, T2: Rapira> Rapira for (T0, T1, T2) {
         self.2.convert_to_bytes(slice, cursor);
     }
 
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        self.0.convert_to_bytes_versioned(slice, cursor, version);
+        self.1.convert_to_bytes_versioned(slice, cursor, version);
+        self.2.convert_to_bytes_versioned(slice, cursor, version);
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        self.0.size_versioned(version)
+            + self.1.size_versioned(version)
+            + self.2.size_versioned(version)
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        T0::check_bytes_ctx(slice, flags)?;
+        T1::check_bytes_ctx(slice, flags)?;
+        T2::check_bytes_ctx(slice, flags)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let t0 = T0::from_slice_ctx(slice, flags)?;
+        let t1 = T1::from_slice_ctx(slice, flags)?;
+        let t2 = T2::from_slice_ctx(slice, flags)?;
+        Ok((t0, t1, t2))
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        self.0.convert_to_bytes_ctx(slice, cursor, flags);
+        self.1.convert_to_bytes_ctx(slice, cursor, flags);
+        self.2.convert_to_bytes_ctx(slice, cursor, flags);
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        self.0.size_ctx(flags) + self.1.size_ctx(flags) + self.2.size_ctx(flags)
+    }
+
     #[inline]
     fn size(&self) -> usize {
         (match T0::STATIC_SIZE {
@@ -1383,6 +1807,8 @@ impl<T0: Rapira, T1: Rapira, T2: Rapira, T3: Rapira> Rapira for (T0, T1, T2, T3)
         T3::STATIC_SIZE,
     ]);
     const MIN_SIZE: usize = T0::MIN_SIZE + T1::MIN_SIZE + T2::MIN_SIZE + T3::MIN_SIZE;
+    const LITTLE_ENDIAN: bool =
+        T0::LITTLE_ENDIAN || T1::LITTLE_ENDIAN || T2::LITTLE_ENDIAN || T3::LITTLE_ENDIAN;
 
     #[inline]
     fn from_slice(slice: &mut &[u8]) -> Result<Self>
@@ -1397,7 +1823,7 @@ impl<T0: Rapira, T1: Rapira, T2: Rapira, T3: Rapira> Rapira for (T0, T1, T2, T3)
     }
 
     #[inline]
-    fn from_slice_versioned(slice: &mut &[u8], version: u8) -> Result<Self>
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<Self>
     where
         Self: Sized,
     {
@@ -1465,6 +1891,62 @@ impl<T0: Rapira, T1: Rapira, T2: Rapira, T3: Rapira> Rapira for (T0, T1, T2, T3)
         self.3.convert_to_bytes(slice, cursor);
     }
 
+    #[inline]
+    fn convert_to_bytes_versioned(&self, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        self.0.convert_to_bytes_versioned(slice, cursor, version);
+        self.1.convert_to_bytes_versioned(slice, cursor, version);
+        self.2.convert_to_bytes_versioned(slice, cursor, version);
+        self.3.convert_to_bytes_versioned(slice, cursor, version);
+    }
+
+    #[inline]
+    fn size_versioned(&self, version: u16) -> usize {
+        self.0.size_versioned(version)
+            + self.1.size_versioned(version)
+            + self.2.size_versioned(version)
+            + self.3.size_versioned(version)
+    }
+
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<()>
+    where
+        Self: Sized,
+    {
+        T0::check_bytes_ctx(slice, flags)?;
+        T1::check_bytes_ctx(slice, flags)?;
+        T2::check_bytes_ctx(slice, flags)?;
+        T3::check_bytes_ctx(slice, flags)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let t0 = T0::from_slice_ctx(slice, flags)?;
+        let t1 = T1::from_slice_ctx(slice, flags)?;
+        let t2 = T2::from_slice_ctx(slice, flags)?;
+        let t3 = T3::from_slice_ctx(slice, flags)?;
+        Ok((t0, t1, t2, t3))
+    }
+
+    #[inline]
+    fn convert_to_bytes_ctx(&self, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        self.0.convert_to_bytes_ctx(slice, cursor, flags);
+        self.1.convert_to_bytes_ctx(slice, cursor, flags);
+        self.2.convert_to_bytes_ctx(slice, cursor, flags);
+        self.3.convert_to_bytes_ctx(slice, cursor, flags);
+    }
+
+    #[inline]
+    fn size_ctx(&self, flags: RapiraFlags) -> usize {
+        self.0.size_ctx(flags)
+            + self.1.size_ctx(flags)
+            + self.2.size_ctx(flags)
+            + self.3.size_ctx(flags)
+    }
+
     #[inline]
     fn size(&self) -> usize {
         (match T0::STATIC_SIZE {
@@ -1483,15 +1965,12 @@ impl<T0: Rapira, T1: Rapira, T2: Rapira, T3: Rapira> Rapira for (T0, T1, T2, T3)
     }
 }
 
-/// for all similar &str
-pub mod str_rapira {
-    use core::marker::PhantomData;
-
-    use simdutf8::basic::from_utf8;
-
-    use super::*;
-    use crate::{LEN_SIZE, extend, try_extend};
+/// for all similar &str, `with` adapter for `String`-like fields
+/// (`AsRef<str>` + `From<&str>`)
+#[allow(non_camel_case_types)]
+pub enum str_rapira {}
 
+impl str_rapira {
     pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize> {
         None
     }
@@ -1576,13 +2055,54 @@ pub mod str_rapira {
     }
 }
 
-/// for all similar &[u8]
-pub mod bytes_rapira {
-    use core::marker::PhantomData;
+impl<T> RapiraWith<T> for str_rapira
+where
+    T: AsRef<str> + for<'a> From<&'a str>,
+{
+    const MIN_SIZE: usize = LEN_SIZE;
+
+    #[inline]
+    fn size(item: &T) -> usize {
+        Self::size(item.as_ref())
+    }
+
+    #[inline]
+    fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+        Self::check_bytes::<T>(PhantomData, slice)
+    }
+
+    #[inline]
+    fn from_slice(slice: &mut &[u8]) -> Result<T> {
+        Self::from_slice(slice).map(T::from)
+    }
+
+    #[inline]
+    unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<T> {
+        unsafe { Self::from_slice_unchecked(slice).map(T::from) }
+    }
+
+    #[inline]
+    unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<T> {
+        unsafe { Self::from_slice_unsafe(slice).map(T::from) }
+    }
+
+    #[inline]
+    fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+        Self::convert_to_bytes(item.as_ref(), slice, cursor)
+    }
+
+    #[inline]
+    fn try_convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        Self::try_convert_to_bytes(item.as_ref(), slice, cursor)
+    }
+}
 
-    use super::*;
-    use crate::{LEN_SIZE, extend, try_extend};
+/// for all similar &[u8], `with` adapter for `Vec<u8>`-like fields
+/// (`AsRef<[u8]>` + `From<&[u8]>`)
+#[allow(non_camel_case_types)]
+pub enum bytes_rapira {}
 
+impl bytes_rapira {
     pub const fn static_size<T>(_: PhantomData<T>) -> Option<usize> {
         None
     }
@@ -1616,7 +2136,7 @@ pub mod bytes_rapira {
     /// see funcs::deser_unchecked
     #[inline]
     pub unsafe fn from_slice_unchecked<'a>(slice: &mut &'a [u8]) -> Result<&'a [u8]> {
-        from_slice(slice)
+        Self::from_slice(slice)
     }
 
     /// ...
@@ -1654,10 +2174,48 @@ pub mod bytes_rapira {
     }
 }
 
+impl<T> RapiraWith<T> for bytes_rapira
+where
+    T: AsRef<[u8]> + for<'a> From<&'a [u8]>,
+{
+    const MIN_SIZE: usize = LEN_SIZE;
+
+    #[inline]
+    fn size(item: &T) -> usize {
+        Self::size(item.as_ref())
+    }
+
+    #[inline]
+    fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+        Self::check_bytes::<T>(PhantomData, slice)
+    }
+
+    #[inline]
+    fn from_slice(slice: &mut &[u8]) -> Result<T> {
+        Self::from_slice(slice).map(T::from)
+    }
+
+    #[inline]
+    unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<T> {
+        unsafe { Self::from_slice_unsafe(slice).map(T::from) }
+    }
+
+    #[inline]
+    fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+        Self::convert_to_bytes(item.as_ref(), slice, cursor)
+    }
+
+    #[inline]
+    fn try_convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        Self::try_convert_to_bytes(item.as_ref(), slice, cursor)
+    }
+}
+
 /// saved as seconds only (no nanoseconds stored)
 impl Rapira for Duration {
     const STATIC_SIZE: Option<usize> = Some(8);
     const MIN_SIZE: usize = 8;
+    const LITTLE_ENDIAN: bool = true;
 
     #[inline]
     fn size(&self) -> usize {
@@ -0,0 +1,130 @@
+use crate::{RapiraFlags, Result};
+
+/// Serialization of `T` by an adapter type, used by `#[rapira(with = Adapter)]` fields
+/// instead of `T: Rapira`.
+///
+/// Methods mirror [`Rapira`](crate::Rapira) with the value passed as the first argument,
+/// so `<Adapter as RapiraWith<T>>::method` is a drop-in for `<T as Rapira>::method`.
+/// Only `MIN_SIZE`, `size`, `check_bytes`, `from_slice` and `convert_to_bytes` are required:
+/// unchecked, unsafe, versioned and context-aware methods default to them.
+///
+/// Builtin adapters: [`byte_rapira`](crate::byte_rapira), [`str_rapira`](crate::str_rapira),
+/// [`bytes_rapira`](crate::bytes_rapira), [`be`](crate::be) (big-endian), `zero` (zerocopy),
+/// `bytemuck` and `postcard`.
+///
+/// ```
+/// use rapira::{Rapira, RapiraWith, Result};
+///
+/// /// `u64` stored as `u32`
+/// struct AsU32;
+///
+/// impl RapiraWith<u64> for AsU32 {
+///     const STATIC_SIZE: Option<usize> = Some(4);
+///     const MIN_SIZE: usize = 4;
+///
+///     fn size(_: &u64) -> usize {
+///         4
+///     }
+///
+///     fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+///         u32::check_bytes(slice)
+///     }
+///
+///     fn from_slice(slice: &mut &[u8]) -> Result<u64> {
+///         u32::from_slice(slice).map(u64::from)
+///     }
+///
+///     fn convert_to_bytes(item: &u64, slice: &mut [u8], cursor: &mut usize) {
+///         (*item as u32).convert_to_bytes(slice, cursor)
+///     }
+/// }
+///
+/// #[derive(Rapira, PartialEq, Debug)]
+/// struct Counter {
+///     #[rapira(with = AsU32)]
+///     value: u64,
+/// }
+///
+/// let bytes = rapira::serialize(&Counter { value: 7 });
+/// assert_eq!(bytes.len(), 4);
+/// assert_eq!(rapira::deserialize::<Counter>(&bytes).unwrap(), Counter { value: 7 });
+/// ```
+pub trait RapiraWith<T> {
+    const STATIC_SIZE: Option<usize> = None;
+    const MIN_SIZE: usize;
+
+    /// size of bytes for serialize
+    fn size(item: &T) -> usize;
+
+    /// check bytes, collections len, check utf-8, NonZero, f32 and others...
+    fn check_bytes(slice: &mut &[u8]) -> Result<()>;
+
+    /// see [`Rapira::from_slice`](crate::Rapira::from_slice)
+    fn from_slice(slice: &mut &[u8]) -> Result<T>;
+
+    /// # Safety
+    ///
+    /// see [`Rapira::from_slice_unchecked`](crate::Rapira::from_slice_unchecked)
+    #[inline]
+    unsafe fn from_slice_unchecked(slice: &mut &[u8]) -> Result<T> {
+        Self::from_slice(slice)
+    }
+
+    /// # Safety
+    ///
+    /// see [`Rapira::from_slice_unsafe`](crate::Rapira::from_slice_unsafe)
+    #[inline]
+    unsafe fn from_slice_unsafe(slice: &mut &[u8]) -> Result<T> {
+        Self::from_slice(slice)
+    }
+
+    /// Default: delegates to `from_slice`.
+    #[inline]
+    fn from_slice_versioned(slice: &mut &[u8], _version: u16) -> Result<T> {
+        Self::from_slice(slice)
+    }
+
+    /// Default: delegates to `from_slice`.
+    #[inline]
+    fn from_slice_ctx(slice: &mut &[u8], _flags: RapiraFlags) -> Result<T> {
+        Self::from_slice(slice)
+    }
+
+    /// Default: delegates to `check_bytes`.
+    #[inline]
+    fn check_bytes_ctx(slice: &mut &[u8], _flags: RapiraFlags) -> Result<()> {
+        Self::check_bytes(slice)
+    }
+
+    fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize);
+
+    #[inline]
+    fn try_convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) -> Result<()> {
+        Self::convert_to_bytes(item, slice, cursor);
+        Ok(())
+    }
+
+    /// Default: delegates to `convert_to_bytes`.
+    #[inline]
+    fn convert_to_bytes_versioned(item: &T, slice: &mut [u8], cursor: &mut usize, _version: u16) {
+        Self::convert_to_bytes(item, slice, cursor)
+    }
+
+    /// Default: delegates to `convert_to_bytes`.
+    #[inline]
+    fn convert_to_bytes_ctx(item: &T, slice: &mut [u8], cursor: &mut usize, _flags: RapiraFlags) {
+        Self::convert_to_bytes(item, slice, cursor)
+    }
+
+    /// Default: delegates to `size`.
+    #[inline]
+    fn size_versioned(item: &T, _version: u16) -> usize {
+        Self::size(item)
+    }
+
+    /// Default: delegates to `size`.
+    #[inline]
+    fn size_ctx(item: &T, _flags: RapiraFlags) -> usize {
+        Self::size(item)
+    }
+}
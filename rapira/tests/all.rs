@@ -175,6 +175,136 @@ fn test_enum() -> Result<()> {
     Ok(())
 }
 
+mod facade {
+    pub use rapira as codec;
+}
+
+#[derive(Debug, Rapira, PartialEq)]
+#[rapira(crate = "facade::codec")]
+struct ReexportedPath {
+    id: u32,
+    kind: ReexportedKind,
+}
+
+#[derive(Debug, Rapira, PartialEq)]
+#[rapira(crate = "facade::codec")]
+enum ReexportedKind {
+    A,
+    B,
+}
+
+#[test]
+fn test_crate_path() -> Result<()> {
+    let item = ReexportedPath {
+        id: 7,
+        kind: ReexportedKind::B,
+    };
+    assert_eq!(item, deserialize::<ReexportedPath>(&serialize(&item))?);
+    Ok(())
+}
+
+#[derive(Debug, Rapira, PartialEq)]
+#[rapira(tag = u16)]
+enum WideTagEnum {
+    A(u32),
+    #[idx = 300]
+    B(u32),
+}
+
+#[derive(Debug, Clone, Copy, Rapira, PartialEq)]
+#[rapira(tag = u16)]
+#[repr(u16)]
+enum WideSimpleEnum {
+    A = 1,
+    B = 1000,
+}
+
+#[derive(Debug, Rapira, PartialEq)]
+#[repr(u16)]
+enum ReprEnum {
+    A = 10,
+    B,
+    #[idx = 2]
+    C = 700,
+    #[rapira(other)]
+    Unknown = 5,
+}
+
+const SIGNED_BASE: i16 = 40;
+
+#[derive(Debug, Rapira, PartialEq)]
+#[rapira(tag = u8)]
+#[repr(i16)]
+enum SignedReprEnum {
+    A = SIGNED_BASE,
+    B = SIGNED_BASE + 1,
+}
+
+#[test]
+fn test_simple_enum_discriminants() -> Result<()> {
+    assert_eq!(ReprEnum::STATIC_SIZE, Some(2));
+    assert_eq!(serialize(&ReprEnum::B), 11u16.to_le_bytes());
+    assert_eq!(serialize(&ReprEnum::C), 2u16.to_le_bytes());
+    assert_eq!(ReprEnum::C, deserialize::<ReprEnum>(&2u16.to_le_bytes())?);
+    assert_eq!(
+        ReprEnum::Unknown,
+        deserialize::<ReprEnum>(&700u16.to_le_bytes())?
+    );
+    assert_eq!(
+        ReprEnum::Unknown,
+        deserialize::<ReprEnum>(&5u16.to_le_bytes())?
+    );
+    assert_eq!(SignedReprEnum::STATIC_SIZE, Some(1));
+    assert_eq!(serialize(&SignedReprEnum::B), [41]);
+    assert_eq!(SignedReprEnum::A, deserialize::<SignedReprEnum>(&[40])?);
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u16)]
+enum WideKind {
+    Small,
+    Large = 500,
+}
+
+#[derive(Debug, PartialEq, Rapira, PrimitiveFromEnum)]
+#[primitive(WideKind)]
+#[rapira(tag = u16)]
+enum WidePrimitiveEnum {
+    Small(u16),
+    Large(u32),
+}
+
+#[test]
+fn test_enum_wide_tag() -> Result<()> {
+    assert_eq!(WideTagEnum::STATIC_SIZE, Some(6));
+    let b = WideTagEnum::B(7);
+    let vec = serialize(&b);
+    assert_eq!(&vec[..2], &300u16.to_le_bytes());
+    assert_eq!(b, deserialize::<WideTagEnum>(&vec)?);
+    assert!(matches!(
+        deserialize::<WideTagEnum>(&[1, 0, 0, 0, 0, 0]),
+        Err(RapiraError::EnumVariant)
+    ));
+
+    assert_eq!(WideSimpleEnum::STATIC_SIZE, Some(2));
+    let vec = serialize(&WideSimpleEnum::B);
+    assert_eq!(vec, 1000u16.to_le_bytes());
+    assert_eq!(WideSimpleEnum::B, deserialize::<WideSimpleEnum>(&vec)?);
+    assert!(deserialize::<WideSimpleEnum>(&[0, 0]).is_err());
+
+    // the smallest variant
+    assert_eq!(WidePrimitiveEnum::MIN_SIZE, 4);
+    let large = WidePrimitiveEnum::Large(9);
+    let vec = serialize(&large);
+    assert_eq!(&vec[..2], &500u16.to_le_bytes());
+    assert_eq!(large, deserialize::<WidePrimitiveEnum>(&vec)?);
+    let small = WidePrimitiveEnum::Small(3);
+    assert_eq!(small, deserialize::<WidePrimitiveEnum>(&serialize(&small))?);
+
+    Ok(())
+}
+
 #[cfg(feature = "postcard")]
 #[test]
 fn test_postcard_fields() -> Result<()> {
@@ -214,6 +344,110 @@ fn test_postcard_fields() -> Result<()> {
     Ok(())
 }
 
+#[derive(Debug, Rapira, PartialEq)]
+struct OwnedAdapters {
+    #[rapira(with = rapira::str_rapira)]
+    name: Box<str>,
+    #[rapira(with = rapira::bytes_rapira)]
+    data: std::sync::Arc<[u8]>,
+}
+
+/// `u32` written as `u16` before version 2
+struct WideningU32;
+
+impl RapiraWith<u32> for WideningU32 {
+    const MIN_SIZE: usize = 2;
+
+    fn size(_: &u32) -> usize {
+        4
+    }
+
+    fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+        u32::check_bytes(slice)
+    }
+
+    fn from_slice(slice: &mut &[u8]) -> Result<u32> {
+        u32::from_slice(slice)
+    }
+
+    fn from_slice_versioned(slice: &mut &[u8], version: u16) -> Result<u32> {
+        match version {
+            1 => u16::from_slice(slice).map(u32::from),
+            _ => u32::from_slice(slice),
+        }
+    }
+
+    fn convert_to_bytes(item: &u32, slice: &mut [u8], cursor: &mut usize) {
+        item.convert_to_bytes(slice, cursor)
+    }
+
+    fn convert_to_bytes_versioned(item: &u32, slice: &mut [u8], cursor: &mut usize, version: u16) {
+        match version {
+            1 => (*item as u16).convert_to_bytes(slice, cursor),
+            _ => item.convert_to_bytes(slice, cursor),
+        }
+    }
+
+    fn size_versioned(_: &u32, version: u16) -> usize {
+        match version {
+            1 => 2,
+            _ => 4,
+        }
+    }
+}
+
+#[derive(Debug, Rapira, PartialEq)]
+#[rapira(version = 2)]
+struct VersionedAdapter {
+    #[rapira(with = WideningU32)]
+    count: u32,
+    flag: bool,
+}
+
+#[test]
+fn test_with_adapters() -> Result<()> {
+    let item = OwnedAdapters {
+        name: "name".into(),
+        data: vec![1, 2, 3].into(),
+    };
+    let vec = serialize(&item);
+    assert_eq!(vec, serialize(&(String::from("name"), vec![1u8, 2, 3])));
+    assert!(item == deserialize::<OwnedAdapters>(&vec)?);
+
+    let item = VersionedAdapter {
+        count: 7,
+        flag: true,
+    };
+    let v1 = serialize_versioned(&item, 1);
+    assert_eq!(v1, [7, 0, 1]);
+    assert_eq!(deserialize_versioned::<VersionedAdapter>(&v1, 1)?, item);
+    let v2 = serialize_versioned(&item, 2);
+    assert_eq!(v2, serialize(&item));
+    assert_eq!(deserialize_versioned::<VersionedAdapter>(&v2, 2)?, item);
+    Ok(())
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn test_bytemuck_fields() -> Result<()> {
+    #[derive(Debug, Rapira, PartialEq)]
+    struct PodFields {
+        #[rapira(with = rapira::bytemuck)]
+        arr: [u32; 4],
+        #[rapira(with = rapira::bytemuck)]
+        val: f64,
+    }
+
+    assert_eq!(PodFields::STATIC_SIZE, Some(24));
+    let fields = PodFields {
+        arr: [1, 2, 3, 4],
+        val: 0.5,
+    };
+    let vec = serialize(&fields);
+    assert_eq!(fields, deserialize::<PodFields>(&vec)?);
+    Ok(())
+}
+
 // --- Versioned deserialization tests ---
 
 #[derive(Debug, PartialEq, Rapira)]
@@ -343,3 +577,1682 @@ fn test_versioned_no_since_fields() {
     let deser: NoSinceFields = rapira::deserialize_versioned(&bytes, 1).unwrap();
     assert_eq!(item, deser);
 }
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 3)]
+enum Event {
+    Created(UserV2),
+    Renamed {
+        name: String,
+        #[rapira(since = 2)]
+        reason: Option<String>,
+    },
+    #[rapira(since = 3)]
+    Deleted,
+}
+
+#[test]
+fn test_versioned_enum_in_vec() {
+    // v1 data: versioned struct inside enum payload, field added in v2 absent
+    let mut v1_bytes = Vec::new();
+    rapira::extend_vec(&2u32, &mut v1_bytes); // count = 2
+    v1_bytes.push(0); // Event::Created
+    rapira::extend_vec(&"Alice".to_string(), &mut v1_bytes);
+    rapira::extend_vec(&30u32, &mut v1_bytes);
+    v1_bytes.push(1); // Event::Renamed
+    rapira::extend_vec(&"Bob".to_string(), &mut v1_bytes);
+
+    let events: Vec<Event> = rapira::deserialize_versioned(&v1_bytes, 1).unwrap();
+    assert_eq!(
+        events,
+        vec![
+            Event::Created(UserV2 {
+                name: "Alice".into(),
+                age: 30,
+                email: None,
+            }),
+            Event::Renamed {
+                name: "Bob".into(),
+                reason: None,
+            },
+        ]
+    );
+
+    // current version roundtrip
+    let events = vec![
+        Event::Renamed {
+            name: "Carol".into(),
+            reason: Some("typo".into()),
+        },
+        Event::Deleted,
+    ];
+    let bytes = rapira::serialize(&events);
+    let deser: Vec<Event> = rapira::deserialize_versioned(&bytes, 3).unwrap();
+    assert_eq!(events, deser);
+}
+
+#[test]
+fn test_versioned_enum_variant_since() {
+    let bytes = rapira::serialize(&Event::Deleted);
+    assert!(matches!(
+        rapira::deserialize_versioned::<Event>(&bytes, 2),
+        Err(RapiraError::EnumVariant)
+    ));
+    let deser: Event = rapira::deserialize_versioned(&bytes, 3).unwrap();
+    assert_eq!(deser, Event::Deleted);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromU8)]
+#[repr(u8)]
+enum ShapeKind {
+    Dot,
+    Circle,
+    Square,
+}
+
+#[derive(Debug, PartialEq, Rapira, PrimitiveFromEnum)]
+#[primitive(ShapeKind)]
+#[rapira(version = 2)]
+enum Shape {
+    Dot,
+    Circle {
+        radius: u32,
+        #[rapira(since = 2)]
+        label: Option<UserV2>,
+    },
+    #[rapira(since = 2)]
+    Square(u32),
+}
+
+#[test]
+fn test_versioned_primitive_enum() {
+    let mut v1_bytes = vec![ShapeKind::Circle as u8];
+    rapira::extend_vec(&5u32, &mut v1_bytes);
+
+    let shape: Shape = rapira::deserialize_versioned(&v1_bytes, 1).unwrap();
+    assert_eq!(
+        shape,
+        Shape::Circle {
+            radius: 5,
+            label: None
+        }
+    );
+
+    let bytes = rapira::serialize(&Shape::Square(3));
+    assert!(matches!(
+        rapira::deserialize_versioned::<Shape>(&bytes, 1),
+        Err(RapiraError::EnumVariant)
+    ));
+    let shape: Shape = rapira::deserialize_versioned(&bytes, 2).unwrap();
+    assert_eq!(shape, Shape::Square(3));
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 4)]
+#[rapira(retired(name = nickname, ty = String, until = 3, after = name))]
+#[rapira(retired(name = tags, ty = Vec<String>, since = 2, until = 4, after = nickname))]
+#[rapira(retired(name = age, ty = u32, until = 2))]
+struct UserV4 {
+    name: String,
+    #[rapira(since = 2)]
+    age: u64,
+}
+
+#[test]
+fn test_versioned_retired_fields() {
+    // v1: age(u32), name, nickname
+    let mut v1_bytes = Vec::new();
+    rapira::extend_vec(&30u32, &mut v1_bytes);
+    rapira::extend_vec(&"Alice".to_string(), &mut v1_bytes);
+    rapira::extend_vec(&"Al".to_string(), &mut v1_bytes);
+
+    let user: UserV4 = rapira::deserialize_versioned(&v1_bytes, 1).unwrap();
+    assert_eq!(
+        user,
+        UserV4 {
+            name: "Alice".into(),
+            age: 0,
+        }
+    );
+
+    // v2: name, nickname, tags, age(u64)
+    let mut v2_bytes = Vec::new();
+    rapira::extend_vec(&"Bob".to_string(), &mut v2_bytes);
+    rapira::extend_vec(&"B".to_string(), &mut v2_bytes);
+    rapira::extend_vec(&vec!["x".to_string()], &mut v2_bytes);
+    rapira::extend_vec(&25u64, &mut v2_bytes);
+
+    let user: UserV4 = rapira::deserialize_versioned(&v2_bytes, 2).unwrap();
+    assert_eq!(
+        user,
+        UserV4 {
+            name: "Bob".into(),
+            age: 25,
+        }
+    );
+
+    // v3: name, tags, age
+    let mut v3_bytes = Vec::new();
+    rapira::extend_vec(&"Carol".to_string(), &mut v3_bytes);
+    rapira::extend_vec(&Vec::<String>::new(), &mut v3_bytes);
+    rapira::extend_vec(&41u64, &mut v3_bytes);
+
+    let user: UserV4 = rapira::deserialize_versioned(&v3_bytes, 3).unwrap();
+    assert_eq!(user.name, "Carol");
+    assert_eq!(user.age, 41);
+
+    // current version has no retired fields
+    let user = UserV4 {
+        name: "Dave".into(),
+        age: 50,
+    };
+    let bytes = rapira::serialize(&user);
+    assert_eq!(bytes.len(), 4 + 4 + 8);
+    let deser: UserV4 = rapira::deserialize_versioned(&bytes, 4).unwrap();
+    assert_eq!(user, deser);
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 1)]
+struct AccountV1 {
+    full_name: String,
+    balance: u32,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(
+    version = 2,
+    migrate(from = 1, via = AccountV1, with = AccountV2::from_v1, downgrade = AccountV2::to_v1)
+)]
+struct AccountV2 {
+    first_name: String,
+    last_name: String,
+    balance: u32,
+}
+
+impl AccountV2 {
+    fn from_v1(old: AccountV1) -> Self {
+        let (first_name, last_name) = old.full_name.split_once(' ').unwrap_or_default();
+        AccountV2 {
+            first_name: first_name.into(),
+            last_name: last_name.into(),
+            balance: old.balance,
+        }
+    }
+
+    fn to_v1(&self) -> AccountV1 {
+        AccountV1 {
+            full_name: format!("{} {}", self.first_name, self.last_name),
+            balance: self.balance,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 3)]
+#[rapira(migrate(from = 2, via = AccountV2, try_with = Account::try_from_v2))]
+struct Account {
+    first_name: String,
+    last_name: String,
+    balance: u64,
+}
+
+impl Account {
+    fn try_from_v2(old: AccountV2) -> rapira::Result<Self> {
+        if old.first_name.is_empty() {
+            return Err(RapiraError::Other("empty name"));
+        }
+        Ok(Account {
+            first_name: old.first_name,
+            last_name: old.last_name,
+            balance: old.balance.into(),
+        })
+    }
+}
+
+#[test]
+fn test_versioned_migrations() {
+    let v1 = AccountV1 {
+        full_name: "Ada Lovelace".into(),
+        balance: 10,
+    };
+    let bytes = rapira::serialize(&v1);
+
+    // v1 -> v2 -> v3
+    let account: Account = rapira::deserialize_versioned(&bytes, 1).unwrap();
+    assert_eq!(
+        account,
+        Account {
+            first_name: "Ada".into(),
+            last_name: "Lovelace".into(),
+            balance: 10,
+        }
+    );
+
+    let v2 = AccountV2 {
+        first_name: "Alan".into(),
+        last_name: "Turing".into(),
+        balance: 20,
+    };
+    let bytes = rapira::serialize(&v2);
+    let account: Account = rapira::deserialize_versioned(&bytes, 2).unwrap();
+    assert_eq!(account.balance, 20);
+
+    let bytes = rapira::serialize(&AccountV1 {
+        full_name: String::new(),
+        balance: 0,
+    });
+    assert!(matches!(
+        rapira::deserialize_versioned::<Account>(&bytes, 1),
+        Err(RapiraError::Other("empty name"))
+    ));
+
+    let bytes = rapira::serialize(&account);
+    let deser: Account = rapira::deserialize_versioned(&bytes, 3).unwrap();
+    assert_eq!(account, deser);
+}
+
+#[test]
+fn test_serialize_versioned() {
+    let user = UserV3 {
+        name: "Alice".into(),
+        age: 30,
+        email: Some("a@b.com".into()),
+        score: 7,
+    };
+
+    let v1_bytes = rapira::serialize_versioned(&user, 1);
+    let mut expected = Vec::new();
+    rapira::extend_vec(&"Alice".to_string(), &mut expected);
+    rapira::extend_vec(&30u32, &mut expected);
+    assert_eq!(v1_bytes, expected);
+    assert_eq!(rapira::size_versioned(&user, 1), expected.len());
+
+    let v2_bytes = rapira::serialize_versioned(&user, 2);
+    let deser: UserV3 = rapira::deserialize_versioned(&v2_bytes, 2).unwrap();
+    assert_eq!(deser.email, user.email);
+    assert_eq!(deser.score, 0);
+
+    assert_eq!(
+        rapira::serialize_versioned(&user, 3),
+        rapira::serialize(&user)
+    );
+}
+
+#[test]
+fn test_serialize_versioned_enums_and_retired() {
+    let events = vec![
+        Event::Created(UserV2 {
+            name: "Alice".into(),
+            age: 30,
+            email: Some("a@b.com".into()),
+        }),
+        Event::Renamed {
+            name: "Bob".into(),
+            reason: Some("typo".into()),
+        },
+    ];
+    let v1_bytes = rapira::serialize_versioned(&events, 1);
+    let deser: Vec<Event> = rapira::deserialize_versioned(&v1_bytes, 1).unwrap();
+    assert_eq!(
+        deser,
+        vec![
+            Event::Created(UserV2 {
+                name: "Alice".into(),
+                age: 30,
+                email: None,
+            }),
+            Event::Renamed {
+                name: "Bob".into(),
+                reason: None,
+            },
+        ]
+    );
+
+    // retired fields are written as default values
+    let user = UserV4 {
+        name: "Carol".into(),
+        age: 41,
+    };
+    for version in 1..=4 {
+        let bytes = rapira::serialize_versioned(&user, version);
+        let deser: UserV4 = rapira::deserialize_versioned(&bytes, version).unwrap();
+        assert_eq!(deser.name, user.name);
+    }
+    let v2_bytes = rapira::serialize_versioned(&user, 2);
+    assert_eq!(v2_bytes.len(), 4 + 5 + 4 + 4 + 8);
+}
+
+#[test]
+fn test_serialize_versioned_downgrade() {
+    let account = AccountV2 {
+        first_name: "Ada".into(),
+        last_name: "Lovelace".into(),
+        balance: 10,
+    };
+    let v1_bytes = rapira::serialize_versioned(&account, 1);
+    let old: AccountV1 = rapira::deserialize(&v1_bytes).unwrap();
+    assert_eq!(old.full_name, "Ada Lovelace");
+
+    let deser: AccountV2 = rapira::deserialize_versioned(&v1_bytes, 1).unwrap();
+    assert_eq!(deser, account);
+}
+
+#[test]
+#[should_panic(expected = "added in version 3")]
+fn test_serialize_versioned_new_variant() {
+    rapira::serialize_versioned(&Event::Deleted, 2);
+}
+
+// --- Flattened fields ---
+
+#[derive(Debug, PartialEq, Clone, Default, Rapira)]
+#[rapira(version = 2)]
+struct Audit {
+    created: u64,
+    #[rapira(since = 2)]
+    updated: u64,
+}
+
+#[derive(Debug, PartialEq, Clone, Rapira)]
+struct Owner {
+    user_id: u32,
+    org_id: u32,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 2, debug)]
+struct Record {
+    #[idx = 1]
+    name: String,
+    #[rapira(flatten)]
+    #[idx = 0]
+    owner: Owner,
+    #[rapira(flatten)]
+    #[idx = 2]
+    audit: Audit,
+}
+
+/// `Record` flattened by hand
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 2)]
+struct RecordFlat {
+    user_id: u32,
+    org_id: u32,
+    name: String,
+    created: u64,
+    #[rapira(since = 2)]
+    updated: u64,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct OwnerTuple(#[rapira(flatten)] Owner, bool);
+
+/// `OwnerTuple` flattened by hand
+#[derive(Debug, PartialEq, Rapira)]
+struct OwnerTupleFlat(u32, u32, bool);
+
+#[test]
+fn test_flatten() -> Result<()> {
+    let record = Record {
+        name: "doc".into(),
+        owner: Owner {
+            user_id: 1,
+            org_id: 2,
+        },
+        audit: Audit {
+            created: 10,
+            updated: 20,
+        },
+    };
+    let flat = RecordFlat {
+        user_id: 1,
+        org_id: 2,
+        name: "doc".into(),
+        created: 10,
+        updated: 20,
+    };
+
+    assert_eq!(Record::MIN_SIZE, RecordFlat::MIN_SIZE);
+    assert_eq!(Record::STATIC_SIZE, RecordFlat::STATIC_SIZE);
+    assert_eq!(OwnerTuple::STATIC_SIZE, Some(9));
+
+    let bytes = serialize(&record);
+    assert_eq!(bytes, serialize(&flat));
+    assert_eq!(size(&record), size(&flat));
+    assert_eq!(deserialize::<Record>(&bytes)?, record);
+    assert_eq!(Record::debug_from_slice(&mut bytes.as_slice())?, record);
+
+    // `since` fields of the flattened struct follow the parent version
+    let v1 = serialize_versioned(&record, 1);
+    assert_eq!(v1, serialize_versioned(&flat, 1));
+    assert_eq!(v1.len(), 4 + 4 + 7 + 8);
+    let old: Record = deserialize_versioned(&v1, 1)?;
+    assert_eq!(
+        old.audit,
+        Audit {
+            created: 10,
+            updated: 0
+        }
+    );
+
+    let tuple = OwnerTuple(record.owner.clone(), true);
+    assert_eq!(serialize(&tuple), [1, 0, 0, 0, 2, 0, 0, 0, 1]);
+    assert_eq!(serialize(&tuple), serialize(&OwnerTupleFlat(1, 2, true)));
+    Ok(())
+}
+
+// --- Container representation: transparent, from/into, try_from ---
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(transparent)]
+struct UserId(u64);
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(transparent)]
+struct Cached<T> {
+    value: T,
+    #[rapira(skip)]
+    hits: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Rapira)]
+#[rapira(try_from = "String")]
+struct Email(String);
+
+impl TryFrom<String> for Email {
+    type Error = &'static str;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value.contains('@') {
+            Ok(Email(value))
+        } else {
+            Err("missing @")
+        }
+    }
+}
+
+impl From<Email> for String {
+    fn from(email: Email) -> String {
+        email.0
+    }
+}
+
+/// bitflags-like struct encoded as `u32`
+#[derive(Debug, PartialEq, Clone, Copy, Rapira)]
+#[rapira(from = "u32", into = "u32")]
+struct Perms {
+    read: bool,
+    write: bool,
+}
+
+impl From<u32> for Perms {
+    fn from(bits: u32) -> Self {
+        Perms {
+            read: bits & 1 != 0,
+            write: bits & 2 != 0,
+        }
+    }
+}
+
+impl From<Perms> for u32 {
+    fn from(perms: Perms) -> u32 {
+        perms.read as u32 | (perms.write as u32) << 1
+    }
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct Member {
+    id: UserId,
+    email: Email,
+    perms: Perms,
+}
+
+#[test]
+fn test_transparent() -> Result<()> {
+    assert_eq!(UserId::STATIC_SIZE, Some(8));
+    assert_eq!(serialize(&UserId(5)), serialize(&5u64));
+    assert_eq!(deserialize::<UserId>(&serialize(&5u64))?, UserId(5));
+
+    let cached = Cached {
+        value: "text".to_owned(),
+        hits: 3,
+    };
+    let bytes = serialize(&cached);
+    assert_eq!(bytes, serialize(&"text".to_owned()));
+    let cached: Cached<String> = deserialize(&bytes)?;
+    assert_eq!(cached.hits, 0);
+    assert_eq!(cached.value, "text");
+    Ok(())
+}
+
+#[test]
+fn test_from_into() -> Result<()> {
+    assert_eq!(Perms::STATIC_SIZE, Some(4));
+    let perms = Perms {
+        read: true,
+        write: true,
+    };
+    assert_eq!(serialize(&perms), serialize(&3u32));
+    assert_eq!(
+        deserialize::<Perms>(&serialize(&1u32))?,
+        Perms {
+            read: true,
+            write: false,
+        }
+    );
+
+    let email = Email("a@b.com".to_owned());
+    let bytes = serialize(&email);
+    assert_eq!(bytes, serialize(&"a@b.com".to_owned()));
+    assert_eq!(deserialize::<Email>(&bytes)?, email);
+
+    let member = Member {
+        id: UserId(1),
+        email,
+        perms,
+    };
+    let bytes = serialize(&member);
+    assert_eq!(bytes.len(), size(&member));
+    assert_eq!(deserialize::<Member>(&bytes)?, member);
+    check_bytes::<Member>(&bytes)?;
+    Ok(())
+}
+
+#[test]
+fn test_try_from_error() {
+    let bytes = serialize(&"invalid".to_owned());
+    assert!(matches!(
+        deserialize::<Email>(&bytes),
+        Err(RapiraError::Conversion)
+    ));
+    assert!(matches!(
+        check_bytes::<Email>(&bytes),
+        Err(RapiraError::Conversion)
+    ));
+    assert!(matches!(
+        deserialize_ctx::<Email>(&bytes, RapiraFlags::NONE),
+        Err(RapiraError::Conversion)
+    ));
+}
+
+// --- Custom defaults of absent fields ---
+
+/// no `Default`: there is no meaningful zero value
+#[derive(Debug, PartialEq, Clone, Copy, Rapira)]
+struct Timestamp(u64);
+
+fn epoch() -> Timestamp {
+    Timestamp(1_600_000_000)
+}
+
+#[derive(Debug, PartialEq)]
+struct Handle(Option<u32>);
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 2)]
+struct Session {
+    id: u32,
+    #[rapira(skip, default = Handle(None))]
+    handle: Handle,
+    #[rapira(since = 2, default_with = epoch)]
+    started: Timestamp,
+    #[rapira(since = 2, default = 30)]
+    ttl: u32,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 2)]
+enum SessionEvent {
+    Opened(u32, #[rapira(since = 2, default_with = epoch)] Timestamp),
+}
+
+#[test]
+fn test_field_defaults() -> Result<()> {
+    let session = Session {
+        id: 7,
+        handle: Handle(Some(3)),
+        started: Timestamp(5),
+        ttl: 60,
+    };
+    let bytes = serialize(&session);
+    assert_eq!(bytes.len(), 4 + 8 + 4);
+    let decoded: Session = deserialize(&bytes)?;
+    assert_eq!(decoded.handle, Handle(None));
+    assert_eq!((decoded.started, decoded.ttl), (Timestamp(5), 60));
+
+    let v1 = serialize_versioned(&session, 1);
+    assert_eq!(v1.len(), 4);
+    let old: Session = deserialize_versioned(&v1, 1)?;
+    assert_eq!(
+        old,
+        Session {
+            id: 7,
+            handle: Handle(None),
+            started: epoch(),
+            ttl: 30,
+        }
+    );
+
+    let event = SessionEvent::Opened(1, Timestamp(2));
+    let v1 = serialize_versioned(&event, 1);
+    assert_eq!(
+        deserialize_versioned::<SessionEvent>(&v1, 1)?,
+        SessionEvent::Opened(1, epoch())
+    );
+    Ok(())
+}
+
+// --- Validation hooks ---
+
+fn non_empty(value: &str) -> Result<()> {
+    if value.is_empty() {
+        Err(RapiraError::validation("must not be empty"))
+    } else {
+        Ok(())
+    }
+}
+
+fn ordered(window: &Window) -> Result<()> {
+    if window.start <= window.end {
+        Ok(())
+    } else {
+        Err(RapiraError::validation(format!(
+            "start {} > end {}",
+            window.start, window.end
+        )))
+    }
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(validate = ordered)]
+struct Window {
+    start: u32,
+    end: u32,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct Schedule {
+    #[rapira(validate = non_empty)]
+    name: String,
+    window: Window,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+enum Job {
+    Run {
+        #[rapira(validate = non_empty)]
+        cmd: String,
+    },
+    Every(u32, Window),
+}
+
+fn validation_error(err: RapiraError) -> ValidationError {
+    match err {
+        RapiraError::Validation(err) => *err,
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
+#[test]
+fn test_validate() -> Result<()> {
+    let schedule = Schedule {
+        name: "backup".into(),
+        window: Window { start: 1, end: 5 },
+    };
+    let bytes = serialize(&schedule);
+    assert_eq!(deserialize::<Schedule>(&bytes)?, schedule);
+    check_bytes::<Schedule>(&bytes)?;
+
+    let bytes = serialize(&Schedule {
+        name: String::new(),
+        window: Window { start: 1, end: 5 },
+    });
+    let err = validation_error(deserialize::<Schedule>(&bytes).unwrap_err());
+    assert_eq!(err.path, ["name"]);
+    assert_eq!(err.to_string(), "name: must not be empty");
+    assert!(check_bytes::<Schedule>(&bytes).is_err());
+
+    let bytes = serialize(&Schedule {
+        name: "backup".into(),
+        window: Window { start: 5, end: 1 },
+    });
+    let err = validation_error(deserialize::<Schedule>(&bytes).unwrap_err());
+    assert_eq!(err.to_string(), "window: start 5 > end 1");
+    let err = validation_error(check_bytes::<Schedule>(&bytes).unwrap_err());
+    assert_eq!(err.path, ["window"]);
+    assert!(deserialize_versioned::<Schedule>(&bytes, 1).is_err());
+    assert!(deserialize_ctx::<Schedule>(&bytes, RapiraFlags::NONE).is_err());
+    // unsafe decoding skips validation
+    let window = serialize(&Window { start: 5, end: 1 });
+    assert!(unsafe { deser_unsafe::<Window>(&window) }.is_ok());
+
+    let bytes = serialize(&Job::Run { cmd: String::new() });
+    let err = validation_error(deserialize::<Job>(&bytes).unwrap_err());
+    assert_eq!(err.path, ["Run.cmd"]);
+    let bytes = serialize(&Job::Every(1, Window { start: 2, end: 1 }));
+    let err = validation_error(check_bytes::<Job>(&bytes).unwrap_err());
+    assert_eq!(err.path, ["Every.1"]);
+    Ok(())
+}
+
+// --- Field constraints ---
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+struct Upload {
+    #[rapira(max_len = 8)]
+    name: String,
+    #[rapira(non_empty, max_len = 4)]
+    tags: Vec<String>,
+    #[rapira(range = 1..=100)]
+    priority: u32,
+    #[rapira(range = 0.0..1.0)]
+    ratio: f64,
+    #[rapira(max_len = 600_000)]
+    samples: Vec<bool>,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+enum Command {
+    Rename(#[rapira(max_len = 8)] String),
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct Signer {
+    #[rapira(max_len = 2)]
+    name: String,
+    #[rapira(range = 1..=5)]
+    org: u32,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct Signed {
+    id: u32,
+    #[rapira(flatten)]
+    signer: Signer,
+}
+
+#[test]
+fn test_constraints() -> Result<()> {
+    let upload = Upload {
+        name: "avatar".into(),
+        tags: vec!["png".into()],
+        priority: 10,
+        ratio: 0.5,
+        samples: vec![true; 3],
+    };
+    let bytes = serialize(&upload);
+    assert_eq!(deserialize::<Upload>(&bytes)?, upload);
+    assert_eq!(deserialize_versioned::<Upload>(&bytes, 1)?, upload);
+    assert_eq!(
+        deserialize_ctx::<Upload>(&bytes, RapiraFlags::NONE)?,
+        upload
+    );
+    check_bytes::<Upload>(&bytes)?;
+
+    let invalid = |upload: Upload| {
+        let bytes = serialize(&upload);
+        let err = validation_error(deserialize::<Upload>(&bytes).unwrap_err());
+        let check_err = validation_error(check_bytes::<Upload>(&bytes).unwrap_err());
+        assert_eq!(err, check_err);
+        err.to_string()
+    };
+    assert_eq!(
+        invalid(Upload {
+            name: "too long name".into(),
+            ..upload.clone()
+        }),
+        "name: len 13 exceeds max_len 8"
+    );
+    assert_eq!(
+        invalid(Upload {
+            tags: Vec::new(),
+            ..upload.clone()
+        }),
+        "tags: must not be empty"
+    );
+    assert_eq!(
+        invalid(Upload {
+            priority: 0,
+            ..upload.clone()
+        }),
+        "priority: out of range"
+    );
+    assert_eq!(
+        invalid(Upload {
+            ratio: 1.0,
+            ..upload.clone()
+        }),
+        "ratio: out of range"
+    );
+
+    // the length prefix is checked before the field is allocated
+    let mut bytes = serialize(&"avatar".to_owned());
+    bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+    let err = validation_error(deserialize::<Upload>(&bytes).unwrap_err());
+    assert_eq!(err.to_string(), "tags: len 4294967295 exceeds max_len 4");
+
+    // max_len replaces the global capacity limit
+    let samples = vec![false; rapira::max_cap::VEC_MAX_CAP + 1];
+    assert!(deserialize::<Vec<bool>>(&serialize(&samples)).is_err());
+    let upload = Upload { samples, ..upload };
+    let bytes = serialize(&upload);
+    assert_eq!(deserialize::<Upload>(&bytes)?, upload);
+    assert_eq!(deserialize_versioned::<Upload>(&bytes, 1)?, upload);
+    assert_eq!(
+        deserialize_ctx::<Upload>(&bytes, RapiraFlags::NONE)?,
+        upload
+    );
+
+    let bytes = serialize(&Command::Rename("too long name".into()));
+    let err = validation_error(deserialize::<Command>(&bytes).unwrap_err());
+    assert_eq!(err.path, ["Rename.0"]);
+
+    use rapira::constraints::{ConstraintSchema, FieldConstraints};
+    let field = |field, max_len, non_empty, range| FieldConstraints {
+        field,
+        max_len,
+        non_empty,
+        range,
+        flatten: &[],
+    };
+    assert_eq!(
+        Upload::CONSTRAINTS,
+        [
+            field("name", Some(8), false, None),
+            field("tags", Some(4), true, None),
+            field("priority", None, false, Some("1..=100")),
+            field("ratio", None, false, Some("0.0..1.0")),
+            field("samples", Some(600_000), false, None),
+        ]
+    );
+    assert_eq!(
+        Command::CONSTRAINTS,
+        [field("Rename.0", Some(8), false, None)]
+    );
+    assert!(Record::flat_constraints().is_empty());
+
+    // fields of a flattened struct are the container's own fields on the wire
+    let err = validation_error(
+        deserialize::<Signed>(&serialize(&Signed {
+            id: 1,
+            signer: Signer {
+                name: "abc".into(),
+                org: 1,
+            },
+        }))
+        .unwrap_err(),
+    );
+    assert_eq!(err.to_string(), "signer.name: len 3 exceeds max_len 2");
+    assert_eq!(Signed::CONSTRAINTS[0].field, "signer");
+    assert_eq!(Signed::CONSTRAINTS[0].flatten, Signer::CONSTRAINTS);
+    assert_eq!(
+        Signed::flat_constraints(),
+        [
+            (
+                "signer.name".to_owned(),
+                field("name", Some(2), false, None)
+            ),
+            (
+                "signer.org".to_owned(),
+                field("org", None, false, Some("1..=5"))
+            ),
+        ]
+    );
+    Ok(())
+}
+
+// --- Per-type collection limits ---
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(max_cap = 2)]
+struct Document {
+    body: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Rapira)]
+#[rapira(max_cap = 1_000_000)]
+struct Digest([u8; 4]);
+
+#[test]
+fn test_max_capacity() -> Result<()> {
+    let doc = |body: &str| Document { body: body.into() };
+    let docs = vec![doc("a"), doc("b")];
+    assert_eq!(deserialize::<Vec<Document>>(&serialize(&docs))?, docs);
+    let docs = vec![doc("a"), doc("b"), doc("c")];
+    assert!(matches!(
+        deserialize::<Vec<Document>>(&serialize(&docs)),
+        Err(RapiraError::MaxCapacity)
+    ));
+    // maps use the smaller limit of the key and value types
+    let map = std::collections::BTreeMap::from([(1u32, doc("a")), (2, doc("b")), (3, doc("c"))]);
+    let bytes = serialize(&map);
+    assert!(matches!(
+        deserialize::<std::collections::BTreeMap<u32, Document>>(&bytes),
+        Err(RapiraError::MaxCapacity)
+    ));
+    assert!(matches!(
+        deserialize_versioned::<std::collections::BTreeMap<u32, Document>>(&bytes, 1),
+        Err(RapiraError::MaxCapacity)
+    ));
+    let map = std::collections::BTreeMap::from([(1u32, doc("a")), (2, doc("b"))]);
+    assert_eq!(
+        deserialize::<std::collections::BTreeMap<u32, Document>>(&serialize(&map))?,
+        map
+    );
+
+    let digests = vec![Digest([1; 4]); max_cap::VEC_MAX_CAP + 1];
+    assert_eq!(deserialize::<Vec<Digest>>(&serialize(&digests))?, digests);
+    Ok(())
+}
+
+// --- Bit-packed fields ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Rapira)]
+enum Role {
+    Guest,
+    Member,
+    Admin,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct Permissions {
+    #[rapira(bits = 1)]
+    read: bool,
+    #[rapira(bits = 1)]
+    write: bool,
+    #[rapira(bits = 2)]
+    role: Role,
+    #[rapira(bits = 5)]
+    retries: u8,
+    id: u32,
+    #[rapira(bits = 1)]
+    active: bool,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct Packed(#[rapira(bits = 40)] u64, #[rapira(bits = 30)] u32);
+
+#[test]
+fn test_bits() -> Result<()> {
+    assert_eq!(Permissions::STATIC_SIZE, Some(2 + 4 + 1));
+    let perms = Permissions {
+        read: true,
+        write: false,
+        role: Role::Admin,
+        retries: 17,
+        id: 7,
+        active: true,
+    };
+    let bytes = serialize(&perms);
+    assert_eq!(bytes, [0b0001_1001, 0b1, 7, 0, 0, 0, 1]);
+    assert_eq!(deserialize::<Permissions>(&bytes)?, perms);
+    assert_eq!(deserialize_versioned::<Permissions>(&bytes, 1)?, perms);
+    assert_eq!(unsafe { deser_unsafe::<Permissions>(&bytes)? }, perms);
+    check_bytes::<Permissions>(&bytes)?;
+
+    // role tag 3 has no variant, unused bits must be zero
+    for invalid in [[0b0000_1100, 0], [0, 0b10]] {
+        let mut bytes = bytes.clone();
+        bytes[..2].copy_from_slice(&invalid);
+        assert!(matches!(
+            deserialize::<Permissions>(&bytes),
+            Err(RapiraError::BitField)
+        ));
+        assert!(check_bytes::<Permissions>(&bytes).is_err());
+    }
+
+    let too_many = Permissions {
+        retries: 32,
+        ..perms
+    };
+    let mut bytes = vec![0; too_many.size()];
+    assert!(matches!(
+        too_many.try_convert_to_bytes(&mut bytes, &mut 0),
+        Err(RapiraError::BitField)
+    ));
+
+    // 70 bits do not fit one bitfield
+    assert_eq!(Packed::STATIC_SIZE, Some(5 + 4));
+    let packed = Packed((1 << 40) - 1, 3);
+    assert_eq!(deserialize::<Packed>(&serialize(&packed))?, packed);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "value does not fit in 5 bits")]
+fn test_bits_overflow() {
+    let perms = Permissions {
+        read: true,
+        write: false,
+        role: Role::Admin,
+        retries: 32,
+        id: 7,
+        active: true,
+    };
+    serialize(&perms);
+}
+
+// --- Sparse structs ---
+
+#[derive(Debug, Default, Clone, PartialEq, Rapira)]
+#[rapira(sparse, version = 2)]
+struct Settings {
+    theme: Option<String>,
+    font_size: u16,
+    #[rapira(max_len = 8)]
+    tags: Vec<String>,
+    autosave: bool,
+    #[rapira(since = 2)]
+    locale: Option<String>,
+    #[rapira(range = ..=10)]
+    zoom: u16,
+    timeout: Option<u32>,
+    offset: i64,
+    retries: u32,
+    #[rapira(skip)]
+    cached: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Rapira)]
+#[rapira(sparse)]
+struct SparsePair(Option<u16>, String);
+
+#[test]
+fn test_sparse() -> Result<()> {
+    // only the presence bitmap
+    let empty = Settings::default();
+    assert_eq!(serialize(&empty), [0, 0]);
+    assert_eq!(deserialize::<Settings>(&[0, 0])?, empty);
+
+    let settings = Settings {
+        font_size: 14,
+        timeout: Some(30),
+        ..Default::default()
+    };
+    let bytes = serialize(&settings);
+    assert_eq!(bytes, [0b0100_0010, 0, 14, 0, 30, 0, 0, 0]);
+    assert_eq!(settings.size(), bytes.len());
+    assert_eq!(deserialize::<Settings>(&bytes)?, settings);
+    assert_eq!(deserialize_versioned::<Settings>(&bytes, 2)?, settings);
+    assert_eq!(unsafe { deser_unsafe::<Settings>(&bytes)? }, settings);
+    check_bytes::<Settings>(&bytes)?;
+
+    let full = Settings {
+        theme: Some("dark".into()),
+        font_size: 12,
+        tags: vec!["a".into()],
+        autosave: true,
+        locale: Some("en".into()),
+        zoom: 2,
+        timeout: Some(5),
+        offset: -1,
+        retries: 3,
+        cached: true,
+    };
+    let bytes = serialize(&full);
+    assert_eq!(&bytes[..2], [0xff, 1]);
+    assert_eq!(
+        deserialize::<Settings>(&bytes)?,
+        Settings {
+            cached: false,
+            ..full.clone()
+        }
+    );
+
+    // `locale` has no presence bit in version 1
+    let v1 = serialize_versioned(&full, 1);
+    assert_eq!(v1[0], 0xff);
+    assert_eq!(v1.len(), size_versioned(&full, 1));
+    assert_eq!(v1.len(), bytes.len() - 1 - 6);
+    assert_eq!(
+        deserialize_versioned::<Settings>(&v1, 1)?,
+        Settings {
+            locale: None,
+            cached: false,
+            ..full.clone()
+        }
+    );
+
+    // unused bits of the bitmap must be zero, constraints are checked
+    assert!(matches!(
+        deserialize::<Settings>(&[0, 0b10]),
+        Err(RapiraError::BitField)
+    ));
+    assert!(check_bytes::<Settings>(&[0, 0b10]).is_err());
+    let out_of_range = serialize(&Settings {
+        zoom: 11,
+        ..Default::default()
+    });
+    assert!(matches!(
+        deserialize::<Settings>(&out_of_range),
+        Err(RapiraError::Validation(_))
+    ));
+    assert!(check_bytes::<Settings>(&out_of_range).is_err());
+
+    let pair = SparsePair(None, "x".into());
+    let bytes = serialize(&pair);
+    assert_eq!(bytes, [0b10, 1, 0, 0, 0, b'x']);
+    assert_eq!(deserialize::<SparsePair>(&bytes)?, pair);
+    check_bytes::<SparsePair>(&bytes)?;
+    Ok(())
+}
+
+// --- Fixed layout ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Rapira)]
+#[rapira(fixed)]
+enum Figure {
+    Empty,
+    Circle(u16),
+    Rect { w: u32, h: u32 },
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct FixedRecord {
+    id: u32,
+    #[rapira(fixed)]
+    parent: Option<u64>,
+    shape: Figure,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(fixed)]
+struct Slot(Option<u16>, Option<Figure>);
+
+#[test]
+fn test_fixed() -> Result<()> {
+    assert_eq!(Figure::STATIC_SIZE, Some(1 + 8));
+    assert_eq!(FixedRecord::STATIC_SIZE, Some(4 + 9 + 9));
+    assert_eq!(Slot::STATIC_SIZE, Some(3 + 10));
+
+    let record = FixedRecord {
+        id: 1,
+        parent: None,
+        shape: Figure::Circle(5),
+    };
+    let bytes = serialize(&record);
+    assert_eq!(bytes.len(), 22);
+    assert_eq!(bytes[4..13], [0; 9]);
+    assert_eq!(bytes[13..16], [1, 5, 0]);
+    assert_eq!(deserialize::<FixedRecord>(&bytes)?, record);
+    assert_eq!(deserialize_versioned::<FixedRecord>(&bytes, 1)?, record);
+    assert_eq!(unsafe { deser_unsafe::<FixedRecord>(&bytes)? }, record);
+    check_bytes::<FixedRecord>(&bytes)?;
+
+    for shape in [Figure::Empty, Figure::Rect { w: 2, h: 3 }] {
+        let record = FixedRecord {
+            parent: Some(9),
+            shape,
+            ..record
+        };
+        let bytes = serialize(&record);
+        assert_eq!(bytes.len(), 22);
+        assert_eq!(deserialize::<FixedRecord>(&bytes)?, record);
+    }
+
+    // padding must be zero
+    let mut padded = bytes.clone();
+    padded[21] = 1;
+    assert!(matches!(
+        deserialize::<FixedRecord>(&padded),
+        Err(RapiraError::Padding)
+    ));
+    assert!(check_bytes::<FixedRecord>(&padded).is_err());
+    let mut padded = bytes;
+    padded[5] = 1;
+    assert!(matches!(
+        deserialize::<FixedRecord>(&padded),
+        Err(RapiraError::Padding)
+    ));
+
+    let slot = Slot(Some(7), None);
+    let bytes = serialize(&slot);
+    assert_eq!(bytes, [1, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(deserialize::<Slot>(&bytes)?, slot);
+    Ok(())
+}
+
+// --- Binary layout ---
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+#[rapira(magic = b"RPR1")]
+struct FileHeader {
+    kind: u16,
+    #[rapira(pad = 2)]
+    len: u32,
+    #[rapira(count = len)]
+    entries: Vec<u16>,
+    #[rapira(align = 8)]
+    stamp: u64,
+    #[rapira(if = *kind == 2)]
+    extra: u32,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(magic = b"PK")]
+struct PacketHead(u16, #[rapira(align = 8)] u32);
+
+#[test]
+fn test_layout() -> Result<()> {
+    assert_eq!(PacketHead::STATIC_SIZE, Some(12));
+    assert_eq!(FileHeader::MIN_SIZE, 4 + 2 + 2 + 4 + 4 + 8);
+
+    let head = PacketHead(1, 2);
+    let bytes = serialize(&head);
+    assert_eq!(bytes, [b'P', b'K', 1, 0, 0, 0, 0, 0, 2, 0, 0, 0]);
+    assert_eq!(deserialize::<PacketHead>(&bytes)?, head);
+
+    let header = FileHeader {
+        kind: 2,
+        len: 3,
+        entries: vec![1, 2, 3],
+        stamp: 7,
+        extra: 9,
+    };
+    let bytes = serialize(&header);
+    assert_eq!(bytes.len(), 36);
+    assert_eq!(bytes.len(), size(&header));
+    assert_eq!(bytes[..4], *b"RPR1");
+    assert_eq!(bytes[6..12], [0, 0, 3, 0, 0, 0]);
+    assert_eq!(bytes[12..18], [1, 0, 2, 0, 3, 0]);
+    assert_eq!(bytes[18..24], [0; 6]);
+    assert_eq!(deserialize::<FileHeader>(&bytes)?, header);
+    assert_eq!(deserialize_versioned::<FileHeader>(&bytes, 1)?, header);
+    assert_eq!(unsafe { deser_unsafe::<FileHeader>(&bytes)? }, header);
+    check_bytes::<FileHeader>(&bytes)?;
+
+    // `extra` is absent unless `kind == 2`
+    let header = FileHeader {
+        kind: 1,
+        extra: 0,
+        ..header
+    };
+    let bytes = serialize(&header);
+    assert_eq!(bytes.len(), 32);
+    assert_eq!(deserialize::<FileHeader>(&bytes)?, header);
+    check_bytes::<FileHeader>(&bytes)?;
+
+    let mut wrong = bytes.clone();
+    wrong[0] = b'X';
+    assert!(matches!(
+        deserialize::<FileHeader>(&wrong),
+        Err(RapiraError::Magic)
+    ));
+    assert!(check_bytes::<FileHeader>(&wrong).is_err());
+    let mut wrong = bytes.clone();
+    wrong[20] = 1;
+    assert!(matches!(
+        deserialize::<FileHeader>(&wrong),
+        Err(RapiraError::Padding)
+    ));
+    assert!(check_bytes::<FileHeader>(&wrong).is_err());
+    // more entries than bytes
+    let mut wrong = bytes;
+    wrong[8] = 200;
+    assert!(matches!(
+        deserialize::<FileHeader>(&wrong),
+        Err(RapiraError::SliceLen)
+    ));
+
+    // `count` field must match the length
+    let mismatch = FileHeader { len: 2, ..header };
+    let mut buf = vec![0; size(&mismatch)];
+    assert!(matches!(
+        mismatch.try_convert_to_bytes(&mut buf, &mut 0),
+        Err(RapiraError::Count)
+    ));
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+enum Sized2 {
+    Empty,
+    Full(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+struct CountedEnums {
+    len: u32,
+    #[rapira(count = len)]
+    items: Vec<Sized2>,
+}
+
+#[test]
+fn test_count_variable_items() -> Result<()> {
+    // `MIN_SIZE` of an enum is the smallest variant
+    assert_eq!(Sized2::MIN_SIZE, 1);
+    let counted = CountedEnums {
+        len: 2,
+        items: vec![Sized2::Empty, Sized2::Empty],
+    };
+    let bytes = serialize(&counted);
+    assert_eq!(bytes, [2, 0, 0, 0, 0, 0]);
+    assert_eq!(deserialize::<CountedEnums>(&bytes)?, counted);
+    check_bytes::<CountedEnums>(&bytes)?;
+
+    let counted = CountedEnums {
+        len: 2,
+        items: vec![Sized2::Full(7), Sized2::Empty],
+    };
+    assert_eq!(deserialize::<CountedEnums>(&serialize(&counted))?, counted);
+    Ok(())
+}
+
+// --- Big endian ---
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+#[rapira(endian = "big")]
+struct BeRecord {
+    id: u32,
+    port: u16,
+    ratio: f32,
+    seq: core::num::NonZeroU32,
+    name: String,
+    ports: Vec<u16>,
+    #[rapira(endian = "little")]
+    flags: u16,
+    len: u16,
+    #[rapira(count = len)]
+    items: Vec<i16>,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(tag = u16, endian = "big")]
+enum BeMessage {
+    Ping,
+    #[idx = 258]
+    Data(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Rapira)]
+#[rapira(tag = u16, endian = "big")]
+#[repr(u16)]
+enum BeKind {
+    A = 1,
+    B = 1000,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+struct BeFields {
+    #[rapira(with = rapira::be::u32)]
+    value: u32,
+    #[rapira(endian = "big")]
+    size: u64,
+    plain: u32,
+}
+
+#[test]
+fn test_big_endian() -> Result<()> {
+    let record = BeRecord {
+        id: 0x01020304,
+        port: 0x0506,
+        ratio: 1.0,
+        seq: core::num::NonZeroU32::new(7).unwrap(),
+        name: "ab".to_owned(),
+        ports: vec![1, 0x0203],
+        flags: 0x0102,
+        len: 2,
+        items: vec![-2, 3],
+    };
+    let bytes = serialize(&record);
+    assert_eq!(
+        bytes,
+        [
+            1, 2, 3, 4, 5, 6, 0x3F, 0x80, 0, 0, 0, 0, 0, 7, 0, 0, 0, 2, b'a', b'b', 0, 0, 0, 2, 0,
+            1, 2, 3, 2, 1, 0, 2, 0xFF, 0xFE, 0, 3
+        ]
+    );
+    assert_eq!(bytes.len(), size(&record));
+    assert_eq!(deserialize::<BeRecord>(&bytes)?, record);
+    assert_eq!(deserialize_versioned::<BeRecord>(&bytes, 1)?, record);
+    assert_eq!(unsafe { deser_unsafe::<BeRecord>(&bytes)? }, record);
+    check_bytes::<BeRecord>(&bytes)?;
+
+    // the zero `NonZeroU32` is rejected
+    let mut wrong = bytes.clone();
+    wrong[13] = 0;
+    assert!(matches!(
+        deserialize::<BeRecord>(&wrong),
+        Err(RapiraError::NonZero)
+    ));
+    // big-endian length prefix
+    let mut wrong = bytes;
+    wrong[17] = 0;
+    wrong[14] = 1;
+    assert!(deserialize::<BeRecord>(&wrong).is_err());
+
+    let bytes = serialize(&BeMessage::Data(9));
+    assert_eq!(bytes, [1, 2, 0, 0, 0, 9]);
+    assert_eq!(deserialize::<BeMessage>(&bytes)?, BeMessage::Data(9));
+    assert_eq!(serialize(&BeMessage::Ping), [0, 0]);
+
+    let bytes = serialize(&BeKind::B);
+    assert_eq!(bytes, [0x03, 0xE8]);
+    assert_eq!(deserialize::<BeKind>(&bytes)?, BeKind::B);
+    assert_eq!(deserialize::<BeKind>(&[0, 1])?, BeKind::A);
+
+    let fields = BeFields {
+        value: 1,
+        size: 2,
+        plain: 3,
+    };
+    let bytes = serialize(&fields);
+    assert_eq!(bytes, [0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 3, 0, 0, 0]);
+    assert_eq!(BeFields::STATIC_SIZE, Some(16));
+    assert_eq!(deserialize::<BeFields>(&bytes)?, fields);
+    Ok(())
+}
+
+// --- Extensible enums ---
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+#[rapira(extensible, tag = u32)]
+enum RpcV1 {
+    Ping,
+    Echo(String),
+    #[rapira(unknown)]
+    Unknown {
+        tag: u32,
+        raw: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+#[rapira(extensible, tag = u32)]
+enum RpcV2 {
+    Ping,
+    Echo(String, u32),
+    Sum { a: u32, b: u32 },
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(extensible, endian = "big")]
+enum BeEvent {
+    Tick(u16),
+    #[rapira(unknown)]
+    Other(u8, Vec<u8>),
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(extensible)]
+enum Signal {
+    Start,
+    #[rapira(unknown)]
+    Unknown {
+        tag: u32,
+        raw: Vec<u8>,
+    },
+}
+
+#[test]
+fn test_extensible_enum() -> Result<()> {
+    assert_eq!(RpcV1::STATIC_SIZE, None);
+    assert_eq!(RpcV2::MIN_SIZE, 4 + 4);
+    assert_eq!(serialize(&RpcV1::Ping), [0, 0, 0, 0, 0, 0, 0, 0]);
+
+    // a variant the old build doesn't know is captured and written back as is
+    let sum = RpcV2::Sum { a: 1, b: 2 };
+    let bytes = serialize(&sum);
+    assert_eq!(bytes, [2, 0, 0, 0, 8, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]);
+    let unknown = deserialize::<RpcV1>(&bytes)?;
+    assert_eq!(
+        unknown,
+        RpcV1::Unknown {
+            tag: 2,
+            raw: vec![1, 0, 0, 0, 2, 0, 0, 0],
+        }
+    );
+    assert_eq!(size(&unknown), bytes.len());
+    assert_eq!(serialize(&unknown), bytes);
+    assert_eq!(unsafe { deser_unsafe::<RpcV1>(&bytes)? }, unknown);
+    check_bytes::<RpcV1>(&bytes)?;
+    assert_eq!(deserialize::<RpcV2>(&serialize(&unknown))?, sum);
+
+    // a field appended to a known variant is skipped by the old build
+    let echo = RpcV2::Echo("hi".to_owned(), 7);
+    let bytes = serialize(&echo);
+    assert_eq!(bytes.len(), size(&echo));
+    assert_eq!(deserialize::<RpcV1>(&bytes)?, RpcV1::Echo("hi".to_owned()));
+    assert_eq!(
+        deserialize_versioned::<RpcV1>(&bytes, 1)?,
+        RpcV1::Echo("hi".to_owned())
+    );
+    check_bytes::<RpcV1>(&bytes)?;
+    assert_eq!(deserialize::<RpcV2>(&bytes)?, echo);
+
+    // without an `unknown` variant unknown tags are errors
+    let bytes = serialize(&RpcV1::Unknown {
+        tag: 9,
+        raw: vec![1],
+    });
+    assert!(matches!(
+        deserialize::<RpcV2>(&bytes),
+        Err(RapiraError::EnumVariant)
+    ));
+    assert!(check_bytes::<RpcV2>(&bytes).is_err());
+    // the payload must fit
+    assert!(matches!(
+        deserialize::<RpcV1>(&bytes[..bytes.len() - 1]),
+        Err(RapiraError::SliceLen)
+    ));
+    let mut buf = vec![0; 8];
+    assert!(matches!(
+        RpcV2::Sum { a: 1, b: 2 }.try_convert_to_bytes(&mut buf, &mut 0),
+        Err(RapiraError::SliceLen)
+    ));
+
+    let bytes = serialize(&BeEvent::Tick(0x0102));
+    assert_eq!(bytes, [0, 0, 0, 0, 2, 1, 2]);
+    assert_eq!(
+        deserialize::<BeEvent>(&[5, 0, 0, 0, 1, 9])?,
+        BeEvent::Other(5, vec![9])
+    );
+
+    // a `u32` tag field on a `u8` tag
+    let unknown = deserialize::<Signal>(&[3, 1, 0, 0, 0, 9])?;
+    assert_eq!(
+        unknown,
+        Signal::Unknown {
+            tag: 3,
+            raw: vec![9]
+        }
+    );
+    assert_eq!(serialize(&unknown), [3, 1, 0, 0, 0, 9]);
+    let wide = Signal::Unknown {
+        tag: 300,
+        raw: Vec::new(),
+    };
+    let mut buf = vec![0; size(&wide)];
+    assert!(matches!(
+        wide.try_convert_to_bytes(&mut buf, &mut 0),
+        Err(RapiraError::EnumVariant)
+    ));
+    Ok(())
+}
+
+// --- Extensible structs ---
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+#[rapira(extensible)]
+struct ProfileV1 {
+    id: u32,
+    name: String,
+    #[rapira(rest)]
+    rest: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Rapira)]
+#[rapira(extensible)]
+struct ProfileV2 {
+    id: u32,
+    name: String,
+    age: u16,
+    tags: Vec<u32>,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(extensible)]
+struct ProfileId {
+    id: u32,
+}
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(extensible, endian = "big")]
+struct BePoint(u16, u16);
+
+#[test]
+fn test_extensible_struct() -> Result<()> {
+    assert_eq!(BePoint::STATIC_SIZE, Some(8));
+    assert_eq!(ProfileV1::STATIC_SIZE, None);
+    let bytes = serialize(&BePoint(1, 2));
+    assert_eq!(bytes, [0, 0, 0, 4, 0, 1, 0, 2]);
+    assert_eq!(deserialize::<BePoint>(&bytes)?, BePoint(1, 2));
+
+    let v2 = ProfileV2 {
+        id: 1,
+        name: "ann".to_owned(),
+        age: 30,
+        tags: vec![5],
+    };
+    let bytes = serialize(&v2);
+    assert_eq!(bytes[..4], [21, 0, 0, 0]);
+    assert_eq!(bytes.len(), size(&v2));
+
+    // the old build keeps the fields it doesn't know and writes them back
+    let mut v1 = deserialize::<ProfileV1>(&bytes)?;
+    assert_eq!(v1.rest, [30, 0, 1, 0, 0, 0, 5, 0, 0, 0]);
+    assert_eq!(serialize(&v1), bytes);
+    assert_eq!(unsafe { deser_unsafe::<ProfileV1>(&bytes)? }, v1);
+    assert_eq!(deserialize_versioned::<ProfileV1>(&bytes, 1)?, v1);
+    check_bytes::<ProfileV1>(&bytes)?;
+    v1.name = "bob".to_owned();
+    let bytes = serialize(&v1);
+    assert_eq!(bytes.len(), size(&v1));
+    let updated = deserialize::<ProfileV2>(&bytes)?;
+    assert_eq!(
+        updated,
+        ProfileV2 {
+            name: "bob".to_owned(),
+            ..v2.clone()
+        }
+    );
+
+    // without a `rest` field the unknown bytes are skipped
+    let list = serialize(&vec![v2.clone(), updated]);
+    assert_eq!(
+        deserialize::<Vec<ProfileId>>(&list)?,
+        [ProfileId { id: 1 }, ProfileId { id: 1 }]
+    );
+    check_bytes::<Vec<ProfileId>>(&list)?;
+
+    // the payload must hold the known fields
+    let short = serialize(&ProfileId { id: 1 });
+    assert!(deserialize::<ProfileV2>(&short).is_err());
+    let mut buf = vec![0; size(&v2) - 1];
+    assert!(matches!(
+        v2.try_convert_to_bytes(&mut buf, &mut 0),
+        Err(RapiraError::SliceLen)
+    ));
+    Ok(())
+}
@@ -210,3 +210,271 @@ fn ctx_size_matches_plain_size() {
         rapira::size_ctx(&msg, RapiraFlags::NONE)
     );
 }
+
+// --- `with` adapters see the flags ---
+
+/// `u64` adapter, big endian with REVERSE, rejects zero with NON_ZERO
+struct RevWith;
+
+const NON_ZERO: u64 = 2;
+
+impl rapira::RapiraWith<u64> for RevWith {
+    const STATIC_SIZE: Option<usize> = Some(8);
+    const MIN_SIZE: usize = 8;
+
+    fn size(_: &u64) -> usize {
+        8
+    }
+
+    fn check_bytes(slice: &mut &[u8]) -> rapira::Result<()> {
+        u64::check_bytes(slice)
+    }
+
+    fn check_bytes_ctx(slice: &mut &[u8], flags: RapiraFlags) -> rapira::Result<()> {
+        let val = u64::from_slice(slice)?;
+        if flags.has(NON_ZERO) && val == 0 {
+            return Err(rapira::RapiraError::NonZero);
+        }
+        Ok(())
+    }
+
+    fn from_slice(slice: &mut &[u8]) -> rapira::Result<u64> {
+        u64::from_slice(slice)
+    }
+
+    fn from_slice_ctx(slice: &mut &[u8], flags: RapiraFlags) -> rapira::Result<u64> {
+        RevU64::from_slice_ctx(slice, flags).map(|v| v.0)
+    }
+
+    fn convert_to_bytes(item: &u64, slice: &mut [u8], cursor: &mut usize) {
+        item.convert_to_bytes(slice, cursor)
+    }
+
+    fn convert_to_bytes_ctx(item: &u64, slice: &mut [u8], cursor: &mut usize, flags: RapiraFlags) {
+        RevU64(*item).convert_to_bytes_ctx(slice, cursor, flags)
+    }
+}
+
+#[derive(rapira::Rapira, Debug, PartialEq)]
+struct WithCtx {
+    #[rapira(with = RevWith)]
+    id: u64,
+    tag: bool,
+}
+
+#[derive(rapira::Rapira, Debug, PartialEq)]
+enum WithCtxEnum {
+    Id(#[rapira(with = RevWith)] u64),
+    Empty,
+}
+
+#[test]
+fn ctx_with_adapter() {
+    let val = WithCtx {
+        id: 0x0102030405060708,
+        tag: true,
+    };
+    let flags = RapiraFlags::new(REVERSE);
+    let bytes = rapira::serialize_ctx(&val, flags);
+    assert_eq!(bytes, [1, 2, 3, 4, 5, 6, 7, 8, 1]);
+    assert_eq!(rapira::size_ctx(&val, flags), 9);
+    let decoded: WithCtx = rapira::deserialize_ctx(&bytes, flags).unwrap();
+    assert_eq!(decoded, val);
+
+    let val = WithCtxEnum::Id(0x0102030405060708);
+    let bytes = rapira::serialize_ctx(&val, flags);
+    assert_eq!(bytes, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    let decoded: WithCtxEnum = rapira::deserialize_ctx(&bytes, flags).unwrap();
+    assert_eq!(decoded, val);
+}
+
+#[test]
+fn ctx_with_adapter_check_bytes() {
+    let zero = rapira::serialize(&WithCtx { id: 0, tag: false });
+    assert!(rapira::check_bytes::<WithCtx>(&zero).is_ok());
+    assert!(rapira::check_bytes_ctx::<WithCtx>(&zero, RapiraFlags::NONE).is_ok());
+    assert!(rapira::check_bytes_ctx::<WithCtx>(&zero, RapiraFlags::new(NON_ZERO)).is_err());
+
+    let zero = rapira::serialize(&WithCtxEnum::Id(0));
+    assert!(rapira::check_bytes_ctx::<WithCtxEnum>(&zero, RapiraFlags::new(NON_ZERO)).is_err());
+}
+
+// --- omit_if / only_if fields ---
+
+const INTERNAL: u64 = 1 << 8;
+const MOBILE: u64 = 1 << 9;
+
+#[derive(rapira::Rapira, Debug, PartialEq, Default, Clone)]
+struct Account {
+    id: u32,
+    #[rapira(only_if = INTERNAL)]
+    password_hash: String,
+    #[rapira(omit_if = MOBILE)]
+    audit: Vec<u32>,
+    name: String,
+}
+
+#[derive(rapira::Rapira, Debug, PartialEq)]
+enum Event {
+    Login(u32, #[rapira(only_if = INTERNAL)] String),
+    Logout {
+        id: u32,
+        #[rapira(omit_if = MOBILE | INTERNAL)]
+        reason: String,
+    },
+}
+
+fn account() -> Account {
+    Account {
+        id: 7,
+        password_hash: "hash".into(),
+        audit: vec![1, 2],
+        name: "user".into(),
+    }
+}
+
+#[test]
+fn ctx_conditional_fields() {
+    let full = account();
+    let internal = RapiraFlags::new(INTERNAL);
+    let mobile = RapiraFlags::new(MOBILE);
+
+    assert_eq!(Account::STATIC_SIZE, None);
+    assert_eq!(Account::MIN_SIZE, 4 + 4);
+
+    let db = rapira::serialize_ctx(&full, internal);
+    assert_eq!(db.len(), rapira::size_ctx(&full, internal));
+    assert_eq!(db.len(), 4 + 8 + 12 + 8);
+    assert!(rapira::check_bytes_ctx::<Account>(&db, internal).is_ok());
+    let decoded: Account = rapira::deserialize_ctx(&db, internal).unwrap();
+    assert_eq!(decoded, full);
+
+    let client = rapira::serialize_ctx(&full, mobile);
+    assert_eq!(client.len(), rapira::size_ctx(&full, mobile));
+    assert_eq!(client.len(), 4 + 8);
+    assert!(rapira::check_bytes_ctx::<Account>(&client, mobile).is_ok());
+    let decoded: Account = rapira::deserialize_ctx(&client, mobile).unwrap();
+    assert_eq!(
+        decoded,
+        Account {
+            password_hash: String::new(),
+            audit: vec![],
+            ..full.clone()
+        }
+    );
+
+    // plain methods behave as `RapiraFlags::NONE`
+    let plain = rapira::serialize(&full);
+    assert_eq!(plain, rapira::serialize_ctx(&full, RapiraFlags::NONE));
+    assert_eq!(plain.len(), rapira::size(&full));
+    let decoded: Account = rapira::deserialize(&plain).unwrap();
+    assert_eq!(
+        decoded,
+        Account {
+            password_hash: String::new(),
+            ..full
+        }
+    );
+}
+
+#[test]
+fn ctx_conditional_variant_fields() {
+    let internal = RapiraFlags::new(INTERNAL);
+
+    let login = Event::Login(1, "ip".into());
+    let bytes = rapira::serialize_ctx(&login, internal);
+    assert_eq!(bytes.len(), 1 + 4 + 6);
+    assert!(rapira::check_bytes_ctx::<Event>(&bytes, internal).is_ok());
+    assert_eq!(
+        rapira::deserialize_ctx::<Event>(&bytes, internal).unwrap(),
+        login
+    );
+    let bytes = rapira::serialize(&login);
+    assert_eq!(bytes.len(), 1 + 4);
+    assert_eq!(
+        rapira::deserialize::<Event>(&bytes).unwrap(),
+        Event::Login(1, String::new())
+    );
+
+    let logout = Event::Logout {
+        id: 2,
+        reason: "timeout".into(),
+    };
+    let bytes = rapira::serialize_ctx(&logout, internal);
+    assert_eq!(bytes.len(), 1 + 4);
+    assert_eq!(rapira::size_ctx(&logout, internal), 1 + 4);
+    assert_eq!(
+        rapira::deserialize_ctx::<Event>(&bytes, internal).unwrap(),
+        Event::Logout {
+            id: 2,
+            reason: String::new()
+        }
+    );
+    let bytes = rapira::serialize(&logout);
+    assert_eq!(rapira::deserialize::<Event>(&bytes).unwrap(), logout);
+}
+
+#[derive(rapira::Rapira, Debug, PartialEq)]
+struct Redacted {
+    id: u32,
+    #[rapira(omit_if = MOBILE, default = "<redacted>".to_owned())]
+    secret: String,
+}
+
+#[test]
+fn ctx_conditional_field_default() {
+    let item = Redacted {
+        id: 1,
+        secret: "token".into(),
+    };
+    let mobile = RapiraFlags::new(MOBILE);
+    let bytes = rapira::serialize_ctx(&item, mobile);
+    assert_eq!(bytes.len(), 4);
+    let decoded: Redacted = rapira::deserialize_ctx(&bytes, mobile).unwrap();
+    assert_eq!(decoded.secret, "<redacted>");
+}
+
+// --- flags reach fields nested in containers ---
+
+#[derive(rapira::Rapira, Debug, PartialEq)]
+struct Accounts {
+    list: Vec<Account>,
+    owner: Option<Box<Account>>,
+    pair: (u16, [Account; 1]),
+}
+
+#[test]
+fn ctx_conditional_fields_nested() {
+    let mobile = RapiraFlags::new(MOBILE);
+    let val = Accounts {
+        list: vec![account(), account()],
+        owner: Some(Box::new(account())),
+        pair: (1, [account()]),
+    };
+
+    // `id` + `name` of each account, `audit` and `password_hash` omitted
+    let bytes = rapira::serialize_ctx(&val, mobile);
+    assert_eq!(bytes.len(), (4 + 2 * 12) + (1 + 12) + (2 + 12));
+    assert_eq!(bytes.len(), rapira::size_ctx(&val, mobile));
+    assert!(rapira::check_bytes_ctx::<Accounts>(&bytes, mobile).is_ok());
+
+    let stripped = || Account {
+        password_hash: String::new(),
+        audit: vec![],
+        ..account()
+    };
+    let decoded: Accounts = rapira::deserialize_ctx(&bytes, mobile).unwrap();
+    assert_eq!(
+        decoded,
+        Accounts {
+            list: vec![stripped(), stripped()],
+            owner: Some(Box::new(stripped())),
+            pair: (1, [stripped()]),
+        }
+    );
+
+    assert_eq!(
+        rapira::serialize_ctx(&val.list, RapiraFlags::new(INTERNAL)).len(),
+        4 + 2 * (4 + 8 + 12 + 8)
+    );
+}
@@ -0,0 +1,93 @@
+use rapira::{
+    Rapira, RapiraError,
+    envelope::{self, HEADER_MIN_SIZE, Header, MAGIC, Versioned},
+};
+
+#[derive(Debug, PartialEq, Rapira)]
+#[rapira(version = 300)]
+struct Doc {
+    title: String,
+    #[rapira(since = 2)]
+    pages: u32,
+    #[rapira(since = 300)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn envelope_roundtrip() {
+    let doc = Doc {
+        title: "rapira".into(),
+        pages: 12,
+        tags: vec!["binary".into()],
+    };
+    let item = Versioned::new(doc, 300);
+    let bytes = rapira::serialize(&item);
+
+    assert_eq!(&bytes[..2], &MAGIC);
+    assert_eq!(bytes.len(), HEADER_MIN_SIZE + rapira::size(&item.value));
+
+    let decoded: Versioned<Doc> = rapira::deserialize(&bytes).unwrap();
+    assert_eq!(decoded, item);
+    rapira::check_bytes::<Versioned<Doc>>(&bytes).unwrap();
+}
+
+#[test]
+fn envelope_reads_old_version() {
+    // v1 layout: title only
+    let old = envelope::serialize(&"old".to_string(), 1);
+    assert_eq!(
+        envelope::read_header(&old).unwrap(),
+        Header {
+            version: 1,
+            schema_hash: None
+        }
+    );
+
+    let doc: Doc = envelope::deserialize(&old).unwrap();
+    assert_eq!(
+        doc,
+        Doc {
+            title: "old".into(),
+            pages: 0,
+            tags: vec![],
+        }
+    );
+
+    let decoded: Versioned<Doc> = rapira::deserialize(&old).unwrap();
+    assert_eq!(decoded.version, 1);
+    assert_eq!(decoded.value, doc);
+}
+
+#[test]
+fn envelope_schema_hash() {
+    let item = Versioned::new(7u64, 1).with_schema_hash(0xDEAD_BEEF);
+    let bytes = rapira::serialize(&item);
+    assert_eq!(bytes.len(), HEADER_MIN_SIZE + 8 + 8);
+
+    let header = envelope::read_header(&bytes).unwrap();
+    assert_eq!(header.schema_hash, Some(0xDEAD_BEEF));
+
+    let val: u64 = envelope::deserialize_with_hash(&bytes, 0xDEAD_BEEF).unwrap();
+    assert_eq!(val, 7);
+
+    let err = envelope::deserialize_with_hash::<u64>(&bytes, 1).unwrap_err();
+    assert!(matches!(err, RapiraError::SchemaHash));
+}
+
+#[test]
+fn envelope_bad_header() {
+    let mut bytes = envelope::serialize(&1u32, 1);
+
+    bytes[2] = 0b10;
+    assert!(matches!(
+        envelope::deserialize::<u32>(&bytes),
+        Err(RapiraError::EnvelopeHeader)
+    ));
+
+    bytes[2] = 0;
+    bytes[0] = b'X';
+    assert!(matches!(
+        rapira::check_bytes::<Versioned<u32>>(&bytes),
+        Err(RapiraError::EnvelopeHeader)
+    ));
+}
@@ -48,3 +48,162 @@ fn test_generics() -> Result<()> {
 
     Ok(())
 }
+
+/// marker `H` isn't serialized, no `H: Rapira` bound
+#[derive(Rapira, Debug, PartialEq)]
+struct Id<H> {
+    raw: u64,
+    _h: std::marker::PhantomData<H>,
+}
+
+#[derive(Debug, PartialEq)]
+struct NotRapira;
+
+trait Code {
+    fn code(&self) -> u32;
+    fn from_code(code: u32) -> Self;
+}
+
+/// value stored as its `u32` code
+struct AsU32;
+
+impl<T: Code> RapiraWith<T> for AsU32 {
+    const STATIC_SIZE: Option<usize> = Some(4);
+    const MIN_SIZE: usize = 4;
+
+    fn size(_: &T) -> usize {
+        4
+    }
+
+    fn check_bytes(slice: &mut &[u8]) -> Result<()> {
+        u32::check_bytes(slice)
+    }
+
+    fn from_slice(slice: &mut &[u8]) -> Result<T> {
+        u32::from_slice(slice).map(T::from_code)
+    }
+
+    fn convert_to_bytes(item: &T, slice: &mut [u8], cursor: &mut usize) {
+        item.code().convert_to_bytes(slice, cursor)
+    }
+}
+
+impl Code for NotRapira {
+    fn code(&self) -> u32 {
+        7
+    }
+
+    fn from_code(_: u32) -> Self {
+        NotRapira
+    }
+}
+
+#[derive(Rapira, Debug, PartialEq)]
+#[rapira(bound = "T: Code")]
+struct Coded<T> {
+    #[rapira(with = AsU32)]
+    value: T,
+}
+
+/// `AsU32: RapiraWith<T>` is inferred
+#[derive(Rapira, Debug, PartialEq)]
+struct CodedPair<T> {
+    #[rapira(with = AsU32)]
+    value: T,
+    count: u16,
+}
+
+/// `Id<H>: Rapira` is inferred for the embedded id, not `H: Rapira`
+#[derive(Rapira, Debug, PartialEq)]
+enum Tagged<T, H> {
+    Value(T, Id<H>),
+    #[allow(dead_code)]
+    Empty,
+}
+
+#[derive(Rapira, Debug, PartialEq)]
+struct NotDefault(u32);
+
+#[derive(Rapira, Debug, PartialEq)]
+struct Post<H> {
+    id: Id<H>,
+    title: String,
+}
+
+/// a recursive field type bounds the parameters, `T: Rapira`
+#[derive(Rapira, Debug, PartialEq)]
+struct Tree<T> {
+    value: T,
+    children: Vec<Tree<T>>,
+}
+
+/// `T: Default + PartialEq` is inferred for `value`, `Option<U>` needs only `U: Rapira`
+#[derive(Rapira, Debug, PartialEq)]
+#[rapira(sparse)]
+struct Sparse<T, U> {
+    value: T,
+    extra: Option<U>,
+}
+
+#[test]
+fn test_inferred_bounds() -> Result<()> {
+    let id = Id::<NotRapira> {
+        raw: 42,
+        _h: std::marker::PhantomData,
+    };
+    let vec = serialize(&id);
+    assert_eq!(vec.len(), 8);
+    assert!(id == deserialize::<Id<NotRapira>>(&vec)?);
+
+    let coded = Coded { value: NotRapira };
+    let vec = serialize(&coded);
+    assert!(coded == deserialize::<Coded<NotRapira>>(&vec)?);
+
+    let pair = CodedPair {
+        value: NotRapira,
+        count: 3,
+    };
+    let vec = serialize(&pair);
+    assert_eq!(vec.len(), 6);
+    assert!(pair == deserialize::<CodedPair<NotRapira>>(&vec)?);
+
+    let tagged = Tagged::<u32, NotRapira>::Value(
+        5,
+        Id {
+            raw: 1,
+            _h: std::marker::PhantomData,
+        },
+    );
+    let vec = serialize(&tagged);
+    assert!(tagged == deserialize::<Tagged<u32, NotRapira>>(&vec)?);
+
+    let post = Post::<NotRapira> {
+        id: Id {
+            raw: 3,
+            _h: std::marker::PhantomData,
+        },
+        title: "hi".to_owned(),
+    };
+    let vec = serialize(&post);
+    assert!(post == deserialize::<Post<NotRapira>>(&vec)?);
+
+    let tree = Tree {
+        value: 1u32,
+        children: vec![Tree {
+            value: 2,
+            children: Vec::new(),
+        }],
+    };
+    let vec = serialize(&tree);
+    assert!(tree == deserialize::<Tree<u32>>(&vec)?);
+
+    let sparse = Sparse::<u32, NotDefault> {
+        value: 0,
+        extra: Some(NotDefault(7)),
+    };
+    let vec = serialize(&sparse);
+    assert_eq!(vec, [0b10, 7, 0, 0, 0]);
+    assert!(sparse == deserialize::<Sparse<u32, NotDefault>>(&vec)?);
+
+    Ok(())
+}
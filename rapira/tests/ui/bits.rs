@@ -0,0 +1,13 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+struct Flags {
+    #[rapira(bits = 0)]
+    empty: bool,
+    #[rapira(bits = 65)]
+    wide: u64,
+    #[rapira(bits = 1, with = rapira::byte_rapira)]
+    adapted: bool,
+}
+
+fn main() {}
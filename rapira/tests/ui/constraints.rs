@@ -0,0 +1,13 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+struct Constraints {
+    #[rapira(skip, max_len = 4)]
+    skipped: String,
+    #[rapira(with = rapira::str_rapira, non_empty)]
+    adapted: String,
+    #[rapira(skip, range = 1..=2)]
+    ranged: u32,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use rapira::Rapira;
+
+fn zero() -> u32 {
+    0
+}
+
+#[derive(Rapira)]
+struct Defaults {
+    #[rapira(default = 1)]
+    plain: u32,
+    #[rapira(skip, default = 1, default_with = zero)]
+    both: u32,
+}
+
+fn main() {}
@@ -0,0 +1,55 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+#[rapira(endian = "middle")]
+struct MiddleEndian {
+    value: u32,
+}
+
+#[derive(Rapira)]
+struct BigTuple {
+    #[rapira(endian = "big")]
+    inner: (u32, u32),
+}
+
+#[derive(Rapira)]
+struct BigWith {
+    #[rapira(endian = "big", with = rapira::byte_rapira)]
+    value: u8,
+}
+
+#[derive(Rapira)]
+#[rapira(endian = "big")]
+struct BigMaxLen {
+    #[rapira(max_len = 8)]
+    name: String,
+}
+
+#[derive(Rapira)]
+#[rapira(sparse, endian = "big")]
+struct SparseBig {
+    value: Option<u32>,
+}
+
+type Id = u32;
+
+#[derive(Rapira)]
+#[rapira(endian = "big")]
+struct BigLittleFields {
+    maybe: Option<u32>,
+    arr: [u16; 2],
+    pair: (u32, bool),
+    id: Id,
+    ids: Vec<Id>,
+    #[rapira(endian = "little")]
+    little: Option<u32>,
+    flag: bool,
+}
+
+#[derive(Rapira)]
+#[rapira(endian = "big")]
+enum BigLittleVariant {
+    Value(Option<u64>),
+}
+
+fn main() {}
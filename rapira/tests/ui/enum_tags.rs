@@ -0,0 +1,47 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+enum Duplicate {
+    A(u32),
+    #[idx = 0]
+    B(u32),
+}
+
+#[derive(Rapira)]
+enum OutOfRange {
+    A(u32),
+    #[idx = 300]
+    B(u32),
+}
+
+#[derive(Rapira)]
+#[rapira(tag = u64)]
+enum WrongTag {
+    A(u32),
+}
+
+#[derive(Rapira)]
+#[repr(i16)]
+enum SignedRepr {
+    A = 1,
+}
+
+#[derive(Rapira)]
+#[rapira(tag = u8)]
+#[repr(i16)]
+enum Truncated {
+    A = -1,
+    B = 255,
+}
+
+const OFFSET: u16 = 1;
+
+#[derive(Rapira)]
+#[repr(u16)]
+enum SameTag {
+    #[idx = 2]
+    A = 0,
+    B = OFFSET + 1,
+}
+
+fn main() {}
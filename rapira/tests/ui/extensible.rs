@@ -0,0 +1,41 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+enum NotExtensible {
+    A(u32),
+    #[rapira(unknown)]
+    Unknown { tag: u8, raw: Vec<u8> },
+}
+
+#[derive(Rapira)]
+#[rapira(extensible)]
+enum UnknownFirst {
+    #[rapira(unknown)]
+    Unknown { tag: u8, raw: Vec<u8> },
+    A(u32),
+}
+
+#[derive(Rapira)]
+#[rapira(extensible, tag = u32)]
+enum NarrowUnknownTag {
+    A(u32),
+    #[rapira(unknown)]
+    Unknown { tag: u16, raw: Vec<u8> },
+}
+
+#[derive(Rapira)]
+#[rapira(extensible)]
+enum UnknownRaw {
+    A(u32),
+    #[rapira(unknown)]
+    Unknown(u8),
+}
+
+#[derive(Rapira)]
+#[rapira(extensible, fixed)]
+enum FixedExtensible {
+    A(u32),
+    B(u16),
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+struct RestNotExtensible {
+    id: u32,
+    #[rapira(rest)]
+    rest: Vec<u8>,
+}
+
+#[derive(Rapira)]
+#[rapira(extensible)]
+struct TwoRest {
+    #[rapira(rest)]
+    first: Vec<u8>,
+    #[rapira(rest)]
+    second: Vec<u8>,
+}
+
+#[derive(Rapira)]
+#[rapira(extensible)]
+struct RestWithKeys {
+    id: u32,
+    #[rapira(rest, max_len = 8)]
+    rest: Vec<u8>,
+}
+
+#[derive(Rapira)]
+#[rapira(extensible, sparse)]
+struct SparseExtensible {
+    id: Option<u32>,
+}
+
+fn main() {}
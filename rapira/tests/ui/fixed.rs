@@ -0,0 +1,15 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+struct NotOption {
+    #[rapira(fixed)]
+    value: u32,
+}
+
+#[derive(Rapira)]
+struct Adapted {
+    #[rapira(fixed, with = rapira::byte_rapira)]
+    value: Option<u8>,
+}
+
+fn main() {}
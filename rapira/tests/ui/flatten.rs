@@ -0,0 +1,16 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+struct Inner {
+    id: u32,
+}
+
+#[derive(Rapira)]
+struct Outer {
+    #[rapira(flatten, with = rapira::byte_rapira)]
+    inner: Inner,
+    #[rapira(flatten, skip)]
+    skipped: Inner,
+}
+
+fn main() {}
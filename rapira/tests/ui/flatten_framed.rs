@@ -0,0 +1,41 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+#[rapira(magic = b"HDR")]
+struct Header {
+    id: u32,
+}
+
+#[derive(Rapira)]
+#[rapira(extensible)]
+struct Meta {
+    id: u32,
+}
+
+#[derive(Rapira)]
+#[rapira(sparse)]
+struct Sparse {
+    id: Option<u32>,
+}
+
+#[derive(Rapira)]
+enum Kind {
+    A(u32),
+    B,
+}
+
+#[derive(Rapira)]
+struct Outer {
+    #[rapira(flatten)]
+    header: Header,
+    #[rapira(flatten)]
+    meta: Meta,
+    #[rapira(flatten)]
+    sparse: Sparse,
+    #[rapira(flatten)]
+    kind: Kind,
+    #[rapira(flatten)]
+    id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+struct CountLater {
+    #[rapira(count = len)]
+    items: Vec<u32>,
+    len: u32,
+}
+
+#[derive(Rapira)]
+struct SkippedIf {
+    kind: u16,
+    #[rapira(if = *kind == 1, skip)]
+    extra: u32,
+}
+
+#[derive(Rapira)]
+struct OddAlign {
+    #[rapira(align = 3)]
+    value: u32,
+}
+
+#[derive(Rapira)]
+#[rapira(magic = "RPR1")]
+struct TextMagic {
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+#[rapira(transparent, version = 2)]
+struct Versioned(u32);
+
+#[derive(Rapira)]
+#[rapira(transparent)]
+struct TwoFields {
+    a: u32,
+    b: u32,
+}
+
+#[derive(Clone, Rapira)]
+#[rapira(from = "u32", try_from = "u32")]
+struct Both(u32);
+
+#[derive(Clone, Rapira)]
+#[rapira(from = u32, debug)]
+struct NotString(u32);
+
+fn main() {}
@@ -0,0 +1,11 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+#[rapira(version = 2)]
+#[rapira(retired(name = old, ty = u32, until = 3))]
+#[rapira(retired(name = other, ty = u32))]
+struct User {
+    name: String,
+}
+
+fn main() {}
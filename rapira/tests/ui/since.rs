@@ -0,0 +1,18 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+struct NoVersion {
+    #[rapira(since = 2)]
+    name: String,
+}
+
+#[derive(Rapira)]
+#[rapira(version = 2)]
+struct TooNew {
+    #[rapira(since = 3)]
+    name: String,
+    #[rapira(since = 0)]
+    age: u32,
+}
+
+fn main() {}
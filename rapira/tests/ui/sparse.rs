@@ -0,0 +1,20 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+#[rapira(sparse, debug)]
+struct Debugged {
+    a: Option<u32>,
+}
+
+#[derive(Rapira)]
+#[rapira(sparse)]
+struct Packed {
+    #[rapira(bits = 1)]
+    a: bool,
+}
+
+#[derive(Rapira)]
+#[rapira(transparent, sparse)]
+struct Transparent(u32);
+
+fn main() {}
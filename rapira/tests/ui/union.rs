@@ -0,0 +1,9 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+union Bits {
+    a: u32,
+    b: f32,
+}
+
+fn main() {}
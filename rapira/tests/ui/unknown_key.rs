@@ -0,0 +1,10 @@
+use rapira::Rapira;
+
+#[derive(Rapira)]
+#[rapira(versoin = 2)]
+struct User {
+    #[rapira(skip, wiht = rapira::byte_rapira)]
+    id: u8,
+}
+
+fn main() {}